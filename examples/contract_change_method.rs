@@ -1,7 +1,7 @@
+use near_jsonrpc_client::transaction::TransactionBuilder;
 use near_jsonrpc_client::{methods, JsonRpcClient};
 use near_jsonrpc_primitives::types::query::QueryResponseKind;
 use near_jsonrpc_primitives::types::transactions::TransactionInfo;
-use near_primitives::transaction::{Action, FunctionCallAction, Transaction};
 use near_primitives::types::BlockReference;
 
 use serde_json::json;
@@ -36,24 +36,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let other_account = utils::input("Enter the account to be rated: ")?;
     let rating = utils::input("Enter a rating: ")?.parse::<f32>()?;
 
-    let transaction = Transaction {
-        signer_id: signer.account_id.clone(),
-        public_key: signer.public_key.clone(),
-        nonce: current_nonce + 1,
-        receiver_id: "nosedive.testnet".parse()?,
-        block_hash: access_key_query_response.block_hash,
-        actions: vec![Action::FunctionCall(FunctionCallAction {
-            method_name: "rate".to_string(),
-            args: json!({
-                "account_id": other_account,
-                "rating": rating,
-            })
-            .to_string()
-            .into_bytes(),
-            gas: 100_000_000_000_000, // 100 TeraGas
-            deposit: 0,
-        })],
-    };
+    let transaction =
+        TransactionBuilder::new(signer.account_id.clone(), "nosedive.testnet".parse()?)
+            .public_key(signer.public_key.clone())
+            .nonce(current_nonce + 1)
+            .block_hash(access_key_query_response.block_hash)
+            .function_call(
+                "rate",
+                json!({
+                    "account_id": other_account,
+                    "rating": rating,
+                })
+                .to_string()
+                .into_bytes(),
+                100_000_000_000_000, // 100 TeraGas
+                0,
+            )
+            .build()?;
 
     let request = methods::broadcast_tx_async::RpcBroadcastTxAsyncRequest {
         signed_transaction: transaction.sign(&signer),
@@ -47,14 +47,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let new_account_id = utils::input("What's the new Account ID: ")?;
     let mut initial_deposit = None;
-    while let None = initial_deposit {
-        match utils::input("How much do you want to fund this account with (in Ⓝ units)? ")?
-            .parse()?
+    while initial_deposit.is_none() {
+        if let deposit @ 1.. =
+            utils::input("How much do you want to fund this account with (in Ⓝ units)? ")?
+                .parse()?
         {
-            deposit @ 1.. => {
-                initial_deposit.replace(deposit);
-            }
-            _ => {}
+            initial_deposit.replace(deposit);
         }
     }
 
@@ -0,0 +1,227 @@
+//! A blocking NEAR JSON RPC client.
+//!
+//! This exposes the same [`call`](JsonRpcClient::call) surface as the
+//! [async client](crate::JsonRpcClient), only synchronously, by blocking the calling thread
+//! until the request completes. The request is driven on the calling thread, within a runtime
+//! shared by all blocking clients, while the tasks it spawns, like those of pooled connections,
+//! run on the runtime's single worker thread. All the method types in
+//! [`methods`](crate::methods) are shared unchanged.
+//!
+//! Available with the `blocking` feature flag.
+//!
+//! **Note**: this client must not be used from within an async runtime,
+//! doing so will cause a panic.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use near_jsonrpc_client::{blocking::JsonRpcClient, methods};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JsonRpcClient::connect("https://rpc.testnet.near.org");
+//!
+//! let server_status = client.call(methods::status::RpcStatusRequest)?;
+//!
+//! println!("{:?}", server_status);
+//! # Ok(())
+//! # }
+//! ```
+use std::fmt;
+
+use lazy_static::lazy_static;
+
+use super::{methods, CallMeta, CallOptions, MethodCallResult};
+
+#[cfg(any(
+    feature = "reqwest-transport",
+    feature = "hyper-transport",
+    feature = "isahc-transport"
+))]
+use super::AsUrl;
+
+lazy_static! {
+    /// The runtime all blocking clients drive their calls on.
+    ///
+    /// Its single worker thread owns the connections pooled by the default connector, so they
+    /// stay usable by every client, instead of being tied to the runtime of the client that
+    /// opened them.
+    static ref RUNTIME: tokio::runtime::Runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("near-jsonrpc-client-blocking")
+        .enable_all()
+        .build()
+        .expect("fatal: failed to initialize the blocking client runtime");
+}
+
+/// A blocking NEAR JSON RPC Client.
+#[derive(Clone)]
+pub struct JsonRpcClient {
+    client: crate::JsonRpcClient,
+}
+
+impl JsonRpcClient {
+    /// Connect to a JSON RPC server using the default connector.
+    #[cfg(any(
+        feature = "reqwest-transport",
+        feature = "hyper-transport",
+        feature = "isahc-transport"
+    ))]
+    pub fn connect<U: AsUrl>(server_addr: U) -> JsonRpcClient {
+        crate::JsonRpcClient::connect(server_addr).into()
+    }
+
+    /// Get the server address the client connects to.
+    pub fn server_addr(&self) -> &str {
+        self.client.server_addr()
+    }
+
+    /// RPC method executor for the client, blocks until the call completes.
+    #[allow(clippy::result_large_err)]
+    pub fn call<M>(&self, method: M) -> MethodCallResult<M::Response, M::Error>
+    where
+        M: methods::RpcMethod,
+    {
+        RUNTIME.block_on(self.client.call(method))
+    }
+
+    /// RPC method executor for the client, with options applying to this call only.
+    #[allow(clippy::result_large_err)]
+    pub fn call_with_options<M>(
+        &self,
+        method: M,
+        options: CallOptions,
+    ) -> MethodCallResult<M::Response, M::Error>
+    where
+        M: methods::RpcMethod,
+    {
+        RUNTIME.block_on(self.client.call_with_options(method, options))
+    }
+
+    /// RPC method executor for the client, also returning metadata about the call.
+    #[allow(clippy::result_large_err)]
+    pub fn call_with_meta<M>(
+        &self,
+        method: M,
+    ) -> MethodCallResult<(M::Response, CallMeta), M::Error>
+    where
+        M: methods::RpcMethod,
+    {
+        RUNTIME.block_on(self.client.call_with_meta(method))
+    }
+
+    /// Get a shared reference to the headers.
+    pub fn headers(&self) -> &http::HeaderMap {
+        self.client.headers()
+    }
+
+    /// Get an exclusive reference to the headers.
+    pub fn headers_mut(&mut self) -> &mut http::HeaderMap {
+        self.client.headers_mut()
+    }
+
+    /// Get a shared reference to the underlying async client.
+    pub fn as_async(&self) -> &crate::JsonRpcClient {
+        &self.client
+    }
+
+    /// Convert this client into the underlying async client.
+    pub fn into_async(self) -> crate::JsonRpcClient {
+        self.client
+    }
+}
+
+impl From<crate::JsonRpcClient> for JsonRpcClient {
+    fn from(client: crate::JsonRpcClient) -> Self {
+        JsonRpcClient { client }
+    }
+}
+
+impl fmt::Debug for JsonRpcClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("JsonRpcClient").field(&self.client).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::transport::testing::{json_rpc_result, FnTransport};
+    use crate::transport::*;
+
+    struct HealthyTransport;
+
+    impl Transport for HealthyTransport {
+        fn send(
+            &self,
+            request: TransportRequest,
+        ) -> BoxFuture<'_, Result<TransportResponse, TransportError>> {
+            Box::pin(async move {
+                let payload: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+                let body = serde_json::to_vec(&serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": payload["id"],
+                    "result": null,
+                }))
+                .unwrap();
+                Ok(TransportResponse::new(StatusCode::OK, body))
+            })
+        }
+    }
+
+    #[test]
+    fn blocking_call() {
+        let client: JsonRpcClient = crate::JsonRpcClient::with_transport(HealthyTransport)
+            .connect("http://localhost:3030")
+            .into();
+
+        let response = client.call(methods::health::RpcHealthRequest);
+
+        assert!(
+            matches!(response, Ok(methods::health::RpcHealthResponse)),
+            "expected an Ok(RpcHealthResponse), found [{:?}]",
+            response
+        );
+    }
+
+    /// Responds from a task on the runtime that sent the first request, the way a pooled
+    /// connection is driven by the runtime that opened it.
+    struct PooledTransport(Mutex<Option<tokio::runtime::Handle>>);
+
+    impl Transport for PooledTransport {
+        fn send(
+            &self,
+            request: TransportRequest,
+        ) -> BoxFuture<'_, Result<TransportResponse, TransportError>> {
+            let handle = self
+                .0
+                .lock()
+                .unwrap()
+                .get_or_insert_with(tokio::runtime::Handle::current)
+                .clone();
+            let respond = FnTransport::new(|_, payload| {
+                Ok(json_rpc_result(&payload["id"], serde_json::Value::Null))
+            });
+            Box::pin(async move {
+                handle
+                    .spawn(async move { respond.send(request).await })
+                    .await
+                    .unwrap()
+            })
+        }
+    }
+
+    #[test]
+    fn shared_connections() {
+        let connector = crate::JsonRpcClient::with_transport(PooledTransport(Mutex::new(None)));
+        let clients: Vec<JsonRpcClient> = (0..2)
+            .map(|_| connector.connect("http://localhost:3030").into())
+            .collect();
+
+        // the second client uses the connection opened by the first
+        for client in clients {
+            assert!(client.call(methods::health::RpcHealthRequest).is_ok());
+        }
+    }
+}
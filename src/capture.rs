@@ -0,0 +1,333 @@
+//! Capturing exchanges with the server, and replaying them.
+//!
+//! The [`Capture`] layer records every request and raw response as an [`Exchange`], with the
+//! method, the params and the response as JSON, and how long the server took to respond. The
+//! exchanges are written to a directory, one JSON file each, or sent over a channel.
+//!
+//! A [`ReplayTransport`] then serves captured exchanges in place of the server, which makes it
+//! possible to reproduce how the client handles a provider's responses offline.
+//!
+//! Captures hold the payloads as they are, signed transactions included, so they shouldn't
+//! be shared carelessly.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use near_jsonrpc_client::capture::{Capture, ReplayTransport};
+//! use near_jsonrpc_client::{methods, JsonRpcClient};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // capture the exchanges with a provider
+//! let client = JsonRpcClient::connect("https://rpc.testnet.near.org")
+//!     .layer(Capture::to_dir("captures")?);
+//! client.call(methods::status::RpcStatusRequest).await?;
+//!
+//! // and replay them later on
+//! let client = JsonRpcClient::with_transport(ReplayTransport::from_dir("captures")?)
+//!     .connect("https://rpc.testnet.near.org");
+//! let status = client.call(methods::status::RpcStatusRequest).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::time::Duration;
+use std::{fmt, fs, io};
+
+use serde_json::{json, Value};
+
+use crate::layer::{Layer, Next};
+use crate::retry::Instant;
+use crate::transport::{
+    BoxFuture, StatusCode, Transport, TransportError, TransportRequest, TransportResponse,
+};
+
+/// A request sent to the server, and its raw response, see the [module-level documentation](self).
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Exchange {
+    /// The address of the server the request was sent to.
+    pub server_addr: String,
+    /// The method called.
+    pub method: String,
+    /// The params the method was called with.
+    pub params: Value,
+    /// The HTTP status of the response.
+    pub status: StatusCode,
+    /// The response, or the raw body as a string if it isn't JSON.
+    pub response: Value,
+    /// How long the server took to respond.
+    pub latency: Duration,
+}
+
+impl Exchange {
+    fn new(request: &TransportRequest, response: &TransportResponse, latency: Duration) -> Self {
+        let payload: Value = serde_json::from_slice(&request.body).unwrap_or_default();
+        Self {
+            server_addr: request.server_addr.clone(),
+            method: payload["method"].as_str().unwrap_or_default().to_string(),
+            params: payload["params"].clone(),
+            status: response.status,
+            response: serde_json::from_slice(&response.body)
+                .unwrap_or_else(|_| String::from_utf8_lossy(&response.body).into()),
+            latency,
+        }
+    }
+
+    /// Serialize the exchange, as written by [`Capture::to_dir`].
+    pub fn to_json(&self) -> Value {
+        json!({
+            "server_addr": self.server_addr,
+            "method": self.method,
+            "params": self.params,
+            "status": self.status.as_u16(),
+            "response": self.response,
+            "latency_ms": self.latency.as_millis() as u64,
+        })
+    }
+
+    /// Deserialize an exchange, as written by [`Capture::to_dir`].
+    pub fn from_json(value: &Value) -> Option<Self> {
+        Some(Self {
+            server_addr: value["server_addr"].as_str()?.to_string(),
+            method: value["method"].as_str()?.to_string(),
+            params: value["params"].clone(),
+            status: StatusCode::from_u16(value["status"].as_u64()? as u16).ok()?,
+            response: value["response"].clone(),
+            latency: Duration::from_millis(value["latency_ms"].as_u64().unwrap_or_default()),
+        })
+    }
+}
+
+enum Sink {
+    Dir { path: PathBuf, count: AtomicU64 },
+    Channel(Mutex<mpsc::Sender<Exchange>>),
+}
+
+/// A [`Layer`] capturing every exchange with the server, see the [module-level documentation](self).
+///
+/// Failures to write or send captured exchanges are ignored, so that they don't fail calls.
+pub struct Capture(Sink);
+
+impl Capture {
+    /// Write the exchanges to the specified directory, creating it if needed.
+    ///
+    /// Each exchange is written to a file of its own, named after its sequence number and
+    /// method, e.g. `000001-status.json`. The writes are blocking.
+    pub fn to_dir<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        fs::create_dir_all(&path)?;
+        Ok(Self(Sink::Dir {
+            path: path.as_ref().to_path_buf(),
+            count: AtomicU64::new(0),
+        }))
+    }
+
+    /// Send the exchanges over the specified channel.
+    pub fn to_channel(sender: mpsc::Sender<Exchange>) -> Self {
+        Self(Sink::Channel(Mutex::new(sender)))
+    }
+
+    fn record(&self, exchange: Exchange) {
+        match self.0 {
+            Sink::Dir {
+                ref path,
+                ref count,
+            } => {
+                let count = count.fetch_add(1, Ordering::Relaxed) + 1;
+                let file = path.join(format!("{:06}-{}.json", count, exchange.method));
+                if let Ok(json) = serde_json::to_vec_pretty(&exchange.to_json()) {
+                    let _ = fs::write(file, json);
+                }
+            }
+            Sink::Channel(ref sender) => {
+                let _ = sender.lock().unwrap().send(exchange);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Capture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Sink::Dir { ref path, .. } => f.debug_tuple("Capture").field(path).finish(),
+            Sink::Channel(_) => f.debug_tuple("Capture").field(&"channel").finish(),
+        }
+    }
+}
+
+impl Layer for Capture {
+    fn handle<'a>(
+        &'a self,
+        request: TransportRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<TransportResponse, TransportError>> {
+        Box::pin(async move {
+            let start = Instant::now();
+            let response = next.run(request.clone()).await?;
+            self.record(Exchange::new(&request, &response, start.elapsed()));
+            Ok(response)
+        })
+    }
+}
+
+/// A [`Transport`] serving captured exchanges in place of the server, see the
+/// [module-level documentation](self).
+///
+/// Requests are matched with exchanges by method and params. Exchanges matching the same
+/// request are served in the order they were captured, the last one repeating once the others
+/// are exhausted. Requests without a match fail with [`TransportError::SendError`].
+#[derive(Debug)]
+pub struct ReplayTransport {
+    exchanges: Mutex<HashMap<(String, String), VecDeque<Exchange>>>,
+}
+
+impl ReplayTransport {
+    /// Create a transport replaying the specified exchanges.
+    pub fn new<I: IntoIterator<Item = Exchange>>(exchanges: I) -> Self {
+        let mut by_request = HashMap::<_, VecDeque<_>>::new();
+        for exchange in exchanges {
+            by_request
+                .entry((exchange.method.clone(), exchange.params.to_string()))
+                .or_default()
+                .push_back(exchange);
+        }
+        Self {
+            exchanges: Mutex::new(by_request),
+        }
+    }
+
+    /// Create a transport replaying the exchanges written to the specified directory by
+    /// [`Capture::to_dir`], in order.
+    pub fn from_dir<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut files = fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        files.retain(|file| file.extension().map_or(false, |ext| ext == "json"));
+        files.sort();
+        let mut exchanges = vec![];
+        for file in files {
+            let value: Value = serde_json::from_slice(&fs::read(&file)?)?;
+            exchanges.push(Exchange::from_json(&value).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("not a captured exchange: {}", file.display()),
+                )
+            })?);
+        }
+        Ok(Self::new(exchanges))
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn send(
+        &self,
+        request: TransportRequest,
+    ) -> BoxFuture<'_, Result<TransportResponse, TransportError>> {
+        Box::pin(async move {
+            let payload: Value = serde_json::from_slice(&request.body)
+                .map_err(|err| TransportError::SendError(err.into()))?;
+            let method = payload["method"].as_str().unwrap_or_default().to_string();
+            let key = (method, payload["params"].to_string());
+            let exchange = {
+                let mut exchanges = self.exchanges.lock().unwrap();
+                let matching = exchanges.get_mut(&key).ok_or_else(|| {
+                    TransportError::SendError(
+                        format!("no captured exchange for a `{}` request", key.0).into(),
+                    )
+                })?;
+                match matching.len() {
+                    1 => matching[0].clone(),
+                    _ => matching.pop_front().unwrap(),
+                }
+            };
+            let mut response = exchange.response;
+            // the client doesn't check ids, but answer with the request's all the same
+            if response.get("id").is_some() {
+                response["id"] = payload["id"].clone();
+            }
+            let body = match response {
+                Value::String(raw) => raw.into_bytes(),
+                response => serde_json::to_vec(&response)
+                    .map_err(|err| TransportError::RecvError(err.into()))?,
+            };
+            Ok(TransportResponse::new(exchange.status, body))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::testing::{json_rpc_result, FnTransport};
+    use crate::{methods, JsonRpcClient};
+
+    /// Responds to `health` with a `null` result, and to anything else with a block height.
+    fn node() -> impl Transport {
+        FnTransport::new(|_, payload| {
+            let result = match payload["method"].as_str() {
+                Some("health") => Value::Null,
+                _ => json!({ "height": 42 }),
+            };
+            Ok(json_rpc_result(&payload["id"], result))
+        })
+    }
+
+    #[tokio::test]
+    async fn capture_and_replay() {
+        let (sender, receiver) = mpsc::channel();
+        let client = JsonRpcClient::with_transport(node())
+            .connect("http://localhost:3030")
+            .layer(Capture::to_channel(sender));
+        client
+            .call(methods::health::RpcHealthRequest)
+            .await
+            .unwrap();
+
+        let exchange = receiver.try_recv().unwrap();
+        assert_eq!(exchange.server_addr, "http://localhost:3030");
+        assert_eq!(exchange.method, "health");
+        assert_eq!(exchange.status, StatusCode::OK);
+        assert_eq!(exchange.response["result"], Value::Null);
+        let json = exchange.to_json();
+        assert_eq!(
+            Exchange::from_json(&json).map(|exchange| exchange.to_json()),
+            Some(json)
+        );
+
+        let dir = std::env::temp_dir().join(format!("near-jsonrpc-capture-{}", std::process::id()));
+        let client = client.layer(Capture::to_dir(&dir).unwrap());
+        client
+            .call(methods::health::RpcHealthRequest)
+            .await
+            .unwrap();
+        assert!(dir.join("000001-health.json").exists());
+
+        let client = JsonRpcClient::with_transport(ReplayTransport::from_dir(&dir).unwrap())
+            .connect("http://localhost:3030");
+        fs::remove_dir_all(&dir).unwrap();
+        for _ in 0..2 {
+            client
+                .call(methods::health::RpcHealthRequest)
+                .await
+                .unwrap();
+        }
+        let err = client
+            .call(methods::status::RpcStatusRequest)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                crate::errors::JsonRpcError::TransportError(
+                    crate::errors::RpcTransportError::SendError(_)
+                )
+            ),
+            "expected a missing exchange, found [{:?}]",
+            err
+        );
+    }
+}
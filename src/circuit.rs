@@ -0,0 +1,276 @@
+//! Circuit breaking for unhealthy endpoints.
+//!
+//! A [`CircuitBreaker`] configured on the client, with [`JsonRpcClient::circuit_breaker`](crate::JsonRpcClient::circuit_breaker),
+//! keeps track of consecutive failures against each endpoint. Connection failures, timeouts and `5xx`
+//! responses count as failures, any other response counts as a success.
+//!
+//! Once an endpoint fails too many times in a row, its circuit opens, and calls to it fail fast with
+//! [`RpcTransportError::CircuitOpen`](crate::errors::RpcTransportError::CircuitOpen), without sending
+//! anything, for a cool-down period. Afterwards, the circuit is half-open: a single call is let through
+//! as a probe, closing the circuit if it succeeds, or opening it again if it fails.
+//!
+//! The breaker can be shared across clients, its state is keyed by the endpoint address.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use near_jsonrpc_client::{circuit::{CircuitBreaker, CircuitState}, JsonRpcClient};
+//!
+//! let breaker = CircuitBreaker::new()
+//!     .failure_threshold(3)
+//!     .cool_down(Duration::from_secs(10));
+//!
+//! let client = JsonRpcClient::connect("https://rpc.testnet.near.org").circuit_breaker(breaker.clone());
+//!
+//! assert_eq!(breaker.state("https://rpc.testnet.near.org"), CircuitState::Closed);
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::retry::Instant;
+
+/// The state of the circuit for an endpoint.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CircuitState {
+    /// Calls go through as usual.
+    Closed,
+    /// Calls fail fast, until the cool-down period is over.
+    Open,
+    /// A single call is let through, to probe the endpoint.
+    HalfOpen,
+}
+
+#[derive(Debug)]
+enum Circuit {
+    Closed { failures: u32 },
+    Open { since: Instant },
+    HalfOpen { probing: bool },
+}
+
+/// Stops calls to endpoints that keep failing, see the [module-level documentation](self).
+///
+/// Cloning the breaker is cheap, and shares its state with the original.
+#[derive(Clone, Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cool_down: Duration,
+    circuits: Arc<Mutex<HashMap<String, Circuit>>>,
+}
+
+impl CircuitBreaker {
+    /// Create a new circuit breaker, opening after 5 consecutive failures, for 30 seconds.
+    pub fn new() -> Self {
+        Self {
+            failure_threshold: 5,
+            cool_down: Duration::from_secs(30),
+            circuits: Default::default(),
+        }
+    }
+
+    /// Set the number of consecutive failures after which the circuit opens.
+    ///
+    /// Values below 1 are treated as 1.
+    pub fn failure_threshold(mut self, failures: u32) -> Self {
+        self.failure_threshold = failures.max(1);
+        self
+    }
+
+    /// Set how long the circuit stays open before letting a probe call through.
+    pub fn cool_down(mut self, cool_down: Duration) -> Self {
+        self.cool_down = cool_down;
+        self
+    }
+
+    /// Get the state of the circuit for the specified endpoint.
+    ///
+    /// Endpoints that haven't been called yet are reported as closed.
+    pub fn state(&self, endpoint: &str) -> CircuitState {
+        let circuits = self.circuits.lock().unwrap();
+        circuits
+            .get(endpoint)
+            .map_or(CircuitState::Closed, |circuit| self.circuit_state(circuit))
+    }
+
+    /// Get the state of the circuits for all the endpoints called so far.
+    pub fn circuits(&self) -> Vec<(String, CircuitState)> {
+        let circuits = self.circuits.lock().unwrap();
+        circuits
+            .iter()
+            .map(|(endpoint, circuit)| (endpoint.clone(), self.circuit_state(circuit)))
+            .collect()
+    }
+
+    /// Close the circuit for the specified endpoint, forgetting its failures.
+    pub fn reset(&self, endpoint: &str) {
+        self.circuits.lock().unwrap().remove(endpoint);
+    }
+
+    fn circuit_state(&self, circuit: &Circuit) -> CircuitState {
+        match circuit {
+            Circuit::Closed { .. } => CircuitState::Closed,
+            Circuit::Open { since } if since.elapsed() < self.cool_down => CircuitState::Open,
+            Circuit::Open { .. } | Circuit::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Get permission to call the endpoint, or `None` if its circuit is open.
+    pub(crate) fn acquire(&self, endpoint: &str) -> Option<Permit> {
+        let mut circuits = self.circuits.lock().unwrap();
+        let circuit = circuits
+            .entry(endpoint.to_string())
+            .or_insert(Circuit::Closed { failures: 0 });
+        let probe = match circuit {
+            Circuit::Closed { .. } => false,
+            Circuit::Open { since } if since.elapsed() < self.cool_down => return None,
+            Circuit::HalfOpen { probing: true } => return None,
+            Circuit::Open { .. } | Circuit::HalfOpen { probing: false } => {
+                *circuit = Circuit::HalfOpen { probing: true };
+                true
+            }
+        };
+        Some(Permit {
+            breaker: self.clone(),
+            endpoint: endpoint.to_string(),
+            probe,
+            done: false,
+        })
+    }
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Permission to make a single call, that must be followed by reporting its outcome.
+pub(crate) struct Permit {
+    breaker: CircuitBreaker,
+    endpoint: String,
+    probe: bool,
+    done: bool,
+}
+
+impl Permit {
+    pub(crate) fn record(mut self, success: bool) {
+        self.done = true;
+        let mut circuits = self.breaker.circuits.lock().unwrap();
+        let circuit = match circuits.get_mut(&self.endpoint) {
+            Some(circuit) => circuit,
+            None => return,
+        };
+        match circuit {
+            Circuit::Closed { failures } if !success => {
+                *failures += 1;
+                if *failures >= self.breaker.failure_threshold {
+                    *circuit = Circuit::Open {
+                        since: Instant::now(),
+                    };
+                }
+            }
+            _ if self.probe && !success => {
+                *circuit = Circuit::Open {
+                    since: Instant::now(),
+                }
+            }
+            Circuit::Closed { .. } | Circuit::HalfOpen { .. } if success => {
+                *circuit = Circuit::Closed { failures: 0 }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        // a probe abandoned before completing, let another call through
+        if self.probe && !self.done {
+            let mut circuits = self.breaker.circuits.lock().unwrap();
+            if let Some(circuit @ Circuit::HalfOpen { .. }) = circuits.get_mut(&self.endpoint) {
+                *circuit = Circuit::HalfOpen { probing: false };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::errors::{JsonRpcError, RpcTransportError};
+    use crate::transport::testing::{json_rpc_result, FnTransport};
+    use crate::transport::TransportResponse;
+    use crate::{methods, JsonRpcClient};
+
+    #[tokio::test]
+    async fn trips_and_recovers() {
+        const ADDR: &str = "http://localhost:3030";
+
+        // responds with `503 Service Unavailable` while `failing` is set
+        let failing = Arc::new(std::sync::atomic::AtomicBool::new(true));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let transport = FnTransport::new({
+            let (failing, calls) = (failing.clone(), calls.clone());
+            move |_, payload| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Ok(match failing.load(Ordering::SeqCst) {
+                    true => TransportResponse::new(http::StatusCode::SERVICE_UNAVAILABLE, vec![]),
+                    false => json_rpc_result(&payload["id"], serde_json::Value::Null),
+                })
+            }
+        });
+
+        let breaker = CircuitBreaker::new()
+            .failure_threshold(2)
+            .cool_down(Duration::from_millis(50));
+        let client = JsonRpcClient::with_transport(transport)
+            .connect(ADDR)
+            .circuit_breaker(breaker.clone());
+
+        for _ in 0..2 {
+            let response = client.call(methods::health::RpcHealthRequest).await;
+            assert!(response.is_err());
+        }
+        assert_eq!(breaker.state(ADDR), CircuitState::Open);
+        assert_eq!(
+            breaker.circuits(),
+            vec![(ADDR.to_string(), CircuitState::Open)]
+        );
+
+        let response = client.call(methods::health::RpcHealthRequest).await;
+        assert!(
+            matches!(
+                response,
+                Err(JsonRpcError::TransportError(RpcTransportError::CircuitOpen))
+            ),
+            "expected the circuit to be open, found [{:?}]",
+            response
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(breaker.state(ADDR), CircuitState::HalfOpen);
+
+        // the probe fails, opening the circuit again
+        let response = client.call(methods::health::RpcHealthRequest).await;
+        assert!(response.is_err());
+        assert_eq!(breaker.state(ADDR), CircuitState::Open);
+
+        std::thread::sleep(Duration::from_millis(60));
+        failing.store(false, Ordering::SeqCst);
+
+        let response = client.call(methods::health::RpcHealthRequest).await;
+        assert!(
+            response.is_ok(),
+            "expected the probe to succeed, found [{:?}]",
+            response
+        );
+        assert_eq!(breaker.state(ADDR), CircuitState::Closed);
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+}
@@ -0,0 +1,101 @@
+//! DNS resolution with an IP family preference, for the `reqwest` transport.
+
+use std::io;
+use std::net::SocketAddr;
+
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+
+/// Which IP family to connect over, when a server's hostname resolves to both.
+#[derive(Eq, Copy, Hash, Clone, Debug, PartialEq)]
+pub enum IpPreference {
+    /// Try addresses in the order they were resolved, racing the other family
+    /// if the first attempt stalls (happy eyeballs).
+    HappyEyeballs,
+    /// Try IPv4 addresses first, falling back to IPv6 if they stall.
+    PreferIpv4,
+    /// Try IPv6 addresses first, falling back to IPv4 if they stall.
+    PreferIpv6,
+    /// Only connect over IPv4.
+    Ipv4Only,
+    /// Only connect over IPv6.
+    Ipv6Only,
+}
+
+impl Default for IpPreference {
+    fn default() -> Self {
+        IpPreference::HappyEyeballs
+    }
+}
+
+impl IpPreference {
+    /// Filter and order resolved addresses according to this preference.
+    fn apply(self, mut addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+        match self {
+            IpPreference::HappyEyeballs => {}
+            // the sort is stable, preserving the resolver's order within each family
+            IpPreference::PreferIpv4 => addrs.sort_by_key(SocketAddr::is_ipv6),
+            IpPreference::PreferIpv6 => addrs.sort_by_key(SocketAddr::is_ipv4),
+            IpPreference::Ipv4Only => addrs.retain(SocketAddr::is_ipv4),
+            IpPreference::Ipv6Only => addrs.retain(SocketAddr::is_ipv6),
+        }
+        addrs
+    }
+}
+
+/// Resolves hostnames with the system resolver, applying an [`IpPreference`].
+pub(crate) struct PreferenceResolver {
+    pub(crate) preference: IpPreference,
+}
+
+impl Resolve for PreferenceResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let preference = self.preference;
+        Box::pin(async move {
+            let host = name.as_str();
+            let addrs = tokio::net::lookup_host((host, 0)).await?.collect();
+            let addrs = preference.apply(addrs);
+            if addrs.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no addresses matching {:?} found for {}", preference, host),
+                )
+                .into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ip_preference() {
+        let addrs: Vec<SocketAddr> = vec![
+            "[2001:db8::1]:0".parse().unwrap(),
+            "192.0.2.1:0".parse().unwrap(),
+            "[2001:db8::2]:0".parse().unwrap(),
+            "192.0.2.2:0".parse().unwrap(),
+        ];
+
+        assert_eq!(IpPreference::HappyEyeballs.apply(addrs.clone()), addrs);
+        assert_eq!(
+            IpPreference::PreferIpv4.apply(addrs.clone()),
+            [addrs[1], addrs[3], addrs[0], addrs[2]]
+        );
+        assert_eq!(
+            IpPreference::PreferIpv6.apply(addrs.clone()),
+            [addrs[0], addrs[2], addrs[1], addrs[3]]
+        );
+        assert_eq!(
+            IpPreference::Ipv4Only.apply(addrs.clone()),
+            [addrs[1], addrs[3]]
+        );
+        assert_eq!(
+            IpPreference::Ipv6Only.apply(addrs.clone()),
+            [addrs[0], addrs[2]]
+        );
+    }
+}
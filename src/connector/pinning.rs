@@ -0,0 +1,241 @@
+//! TLS certificate pinning, for the `reqwest` transport's rustls backend.
+
+use std::error::Error;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::SystemTime;
+use std::{fmt, io};
+
+use base64::Engine;
+use rustls::client::{ServerCertVerified, ServerCertVerifier};
+use rustls::{Certificate, CertificateError, ServerName};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// A pinned server certificate, or public key.
+///
+/// Pins are matched against the server's end-entity certificate.
+///
+/// Public key pins can be parsed from the `sha256/<base64 encoded hash>` format
+/// used by HPKP, which can be computed with:
+///
+/// ```sh
+/// openssl x509 -in cert.pem -pubkey -noout \
+///   | openssl pkey -pubin -outform der \
+///   | openssl dgst -sha256 -binary | base64
+/// ```
+#[derive(Eq, Hash, Clone, Debug, PartialEq)]
+pub struct CertificatePin(Pin);
+
+#[derive(Eq, Hash, Clone, Debug, PartialEq)]
+enum Pin {
+    Spki([u8; 32]),
+    Certificate([u8; 32]),
+}
+
+impl CertificatePin {
+    /// Pin the SHA-256 hash of the DER encoded `SubjectPublicKeyInfo` of the server's certificate.
+    ///
+    /// This pin survives certificate renewals, so long as the key is reused.
+    pub fn spki_sha256(hash: [u8; 32]) -> Self {
+        Self(Pin::Spki(hash))
+    }
+
+    /// Pin the SHA-256 hash of the server's DER encoded certificate.
+    pub fn certificate_sha256(hash: [u8; 32]) -> Self {
+        Self(Pin::Certificate(hash))
+    }
+
+    fn matches(&self, certificate: &[u8]) -> bool {
+        match self.0 {
+            Pin::Spki(hash) => subject_public_key_info(certificate)
+                .map_or(false, |spki| Sha256::digest(spki)[..] == hash[..]),
+            Pin::Certificate(hash) => Sha256::digest(certificate)[..] == hash[..],
+        }
+    }
+}
+
+impl FromStr for CertificatePin {
+    type Err = InvalidCertificatePin;
+
+    fn from_str(pin: &str) -> Result<Self, Self::Err> {
+        let hash = pin
+            .strip_prefix("sha256/")
+            .and_then(|hash| base64::engine::general_purpose::STANDARD.decode(hash).ok())
+            .and_then(|hash| <[u8; 32]>::try_from(hash).ok())
+            .ok_or(InvalidCertificatePin { _priv: () })?;
+
+        Ok(Self::spki_sha256(hash))
+    }
+}
+
+/// An error returned when a certificate pin couldn't be parsed.
+#[derive(Debug, Error)]
+#[error("invalid certificate pin, expected `sha256/<base64 encoded hash>`")]
+pub struct InvalidCertificatePin {
+    _priv: (),
+}
+
+/// The error reported to rustls when none of the pins match.
+#[derive(Debug)]
+struct CertificatePinMismatch;
+
+impl Error for CertificatePinMismatch {}
+impl fmt::Display for CertificatePinMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "the server's certificate doesn't match any of the pinned certificates"
+        )
+    }
+}
+
+/// Accepts servers presenting a pinned end-entity certificate.
+///
+/// Certificate authorities aren't consulted, possession of the pinned key is
+/// still proven by the handshake signature, which rustls verifies separately.
+struct PinningVerifier {
+    pins: Vec<CertificatePin>,
+}
+
+impl ServerCertVerifier for PinningVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        if self.pins.iter().any(|pin| pin.matches(&end_entity.0)) {
+            return Ok(ServerCertVerified::assertion());
+        }
+        Err(rustls::Error::InvalidCertificate(CertificateError::Other(
+            Arc::new(CertificatePinMismatch),
+        )))
+    }
+}
+
+/// Create a rustls configuration that only trusts the pinned certificates.
+pub(crate) fn client_config(pins: Vec<CertificatePin>) -> rustls::ClientConfig {
+    let mut config = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_custom_certificate_verifier(Arc::new(PinningVerifier { pins }))
+        .with_no_client_auth();
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    config
+}
+
+/// Whether the error was caused by a server failing to present a pinned certificate.
+pub(crate) fn is_pin_mismatch(err: &(dyn Error + 'static)) -> bool {
+    let mut source = Some(err);
+    while let Some(err) = source {
+        if let Some(rustls::Error::InvalidCertificate(CertificateError::Other(err))) =
+            err.downcast_ref::<rustls::Error>()
+        {
+            if err.is::<CertificatePinMismatch>() {
+                return true;
+            }
+        }
+        // io errors skip the error they wrap when reporting their source
+        if let Some(inner) = err.downcast_ref::<io::Error>().and_then(io::Error::get_ref) {
+            if is_pin_mismatch(inner) {
+                return true;
+            }
+        }
+        source = err.source();
+    }
+    false
+}
+
+/// Extract the DER encoded `SubjectPublicKeyInfo` from a DER encoded X.509 certificate.
+fn subject_public_key_info(certificate: &[u8]) -> Option<&[u8]> {
+    let (_, certificate, _) = der_element(certificate, 0x30)?;
+    let (_, mut tbs_certificate, _) = der_element(certificate, 0x30)?;
+
+    // version
+    if tbs_certificate.first() == Some(&0xa0) {
+        tbs_certificate = der_element(tbs_certificate, 0xa0)?.2;
+    }
+    // serialNumber, signature, issuer, validity, subject
+    for tag in [0x02, 0x30, 0x30, 0x30, 0x30] {
+        tbs_certificate = der_element(tbs_certificate, tag)?.2;
+    }
+
+    der_element(tbs_certificate, 0x30).map(|(spki, _, _)| spki)
+}
+
+/// Split a DER element with the specified tag off the input.
+///
+/// Returns the whole element, its contents, and the remaining input.
+fn der_element(input: &[u8], tag: u8) -> Option<(&[u8], &[u8], &[u8])> {
+    if *input.first()? != tag {
+        return None;
+    }
+    let (len, header_len) = match *input.get(1)? {
+        len @ 0..=0x7f => (len as usize, 2),
+        0x81..=0x84 => {
+            let len_len = (input[1] & 0x7f) as usize;
+            let len = input
+                .get(2..2 + len_len)?
+                .iter()
+                .fold(0, |len, byte| (len << 8) | *byte as usize);
+            (len, 2 + len_len)
+        }
+        _ => return None,
+    };
+    let end = header_len.checked_add(len)?;
+    let element = input.get(..end)?;
+    Some((element, &element[header_len..], &input[end..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CERTIFICATE: &str = "MIIBizCCATGgAwIBAgIUT1zoAH6oR2I7NdPHIVQEPqa6SFcwCgYIKoZIzj0EAwIwGzEZMBcGA1UEAwwQcnBjLm5lYXIuaW52YWxpZDAeFw0yNjEwMTQwMzMxMzhaFw0zNjEwMTEwMzMxMzhaMBsxGTAXBgNVBAMMEHJwYy5uZWFyLmludmFsaWQwWTATBgcqhkjOPQIBBggqhkjOPQMBBwNCAATYkdVrrZGAUXhF74M4ZTIA+CUjfrAeRps6H2LDDBAnA50HEO6McKw/2JgI+lis++tesOwmzb8KVrYBkMBNPE2/o1MwUTAdBgNVHQ4EFgQUZFdsQOjJ40EkSST3FIbOXMgGsGYwHwYDVR0jBBgwFoAUZFdsQOjJ40EkSST3FIbOXMgGsGYwDwYDVR0TAQH/BAUwAwEB/zAKBggqhkjOPQQDAgNIADBFAiAQLCyo0CfrSzmdoUvWniky5e+u5TJIFU80LvNYubr6jgIhAMALJ06SdoompSgLVa/z+JYRJgiIsY6nkey8SnbufqIT";
+
+    #[test]
+    fn pins() {
+        let certificate = base64::engine::general_purpose::STANDARD
+            .decode(CERTIFICATE)
+            .unwrap();
+
+        let spki_pin: CertificatePin = "sha256/gx+OiTbww9lfof+ZV5qq8PSHH+c+3346NuP72/CGbFI="
+            .parse()
+            .expect("should have been a valid pin");
+        assert!(spki_pin.matches(&certificate));
+
+        let certificate_pin = CertificatePin::certificate_sha256(
+            base64::engine::general_purpose::STANDARD
+                .decode("n451xDicdw5REA0VodDcvqNUMHiBZSDvfpYYuRF3v7Y=")
+                .unwrap()
+                .try_into()
+                .unwrap(),
+        );
+        assert!(certificate_pin.matches(&certificate));
+
+        assert!(!CertificatePin::spki_sha256([0; 32]).matches(&certificate));
+        assert!(!spki_pin.matches(&certificate[..100]));
+
+        "sha1/gx+OiTbww9lfof+ZV5qq8PSHH+c+3346NuP72/CGbFI="
+            .parse::<CertificatePin>()
+            .expect_err("should not have been a valid pin");
+        "sha256/gx+OiTbww9lfof+ZV5qq8PSHH"
+            .parse::<CertificatePin>()
+            .expect_err("should not have been a valid pin");
+    }
+
+    #[test]
+    fn pin_mismatch_errors() {
+        let err = rustls::Error::InvalidCertificate(CertificateError::Other(Arc::new(
+            CertificatePinMismatch,
+        )));
+        let err = io::Error::new(io::ErrorKind::InvalidData, err);
+        assert!(is_pin_mismatch(&err));
+
+        let err = io::Error::new(io::ErrorKind::InvalidData, rustls::Error::DecryptError);
+        assert!(!is_pin_mismatch(&err));
+    }
+}
@@ -1,16 +1,27 @@
 use std::io;
+use std::time::Duration;
 
 use thiserror::Error;
 
 use near_jsonrpc_primitives::errors::{RpcError, RpcErrorKind, RpcRequestValidationErrorKind};
 use near_jsonrpc_primitives::message::{self, Message};
+use near_primitives::errors::TxExecutionError;
+use near_primitives::hash::CryptoHash;
+
+use super::methods::query::RpcQueryError;
+use super::methods::status::{RpcStatusError, RpcStatusResponse};
+use super::methods::tx::RpcTransactionError;
+use super::transaction::TransactionBuilderError;
+use super::transport::BoxError;
 
 #[derive(Debug, Error)]
 pub enum JsonRpcTransportSendError {
     #[error("error while serializing payload: [{0}]")]
     PayloadSerializeError(io::Error),
     #[error("error while sending payload: [{0}]")]
-    PayloadSendError(reqwest::Error),
+    PayloadSendError(BoxError),
+    #[error("error while authorizing the request: [{0}]")]
+    AuthorizationError(BoxError),
 }
 
 #[derive(Debug, Error)]
@@ -26,7 +37,7 @@ pub enum JsonRpcTransportRecvError {
     #[error("unexpected server response: [{0:?}]")]
     UnexpectedServerResponse(Message),
     #[error("error while reading response: [{0}]")]
-    PayloadRecvError(reqwest::Error),
+    PayloadRecvError(BoxError),
     #[error("error while parsing server response: [{0:?}]")]
     PayloadParseError(message::Broken),
     #[error(transparent)]
@@ -39,16 +50,34 @@ pub enum RpcTransportError {
     SendError(JsonRpcTransportSendError),
     #[error(transparent)]
     RecvError(JsonRpcTransportRecvError),
+    #[error("the request timed out")]
+    TimedOut,
+    #[error("the server's certificate doesn't match any of the pinned certificates")]
+    CertificatePinMismatch,
+    #[error("the circuit breaker for this endpoint is open")]
+    CircuitOpen,
+    /// The server is rate limiting this client, either with a `429 Too Many Requests`
+    /// response, or with a provider-specific error.
+    ///
+    /// `retry_after` is how long the server asked to wait for, with a `Retry-After` header.
+    #[error("this client is being rate limited by the server")]
+    RateLimited { retry_after: Option<Duration> },
+    /// All the slots for requests in flight are taken, see
+    /// [`JsonRpcClient::try_call`](crate::JsonRpcClient::try_call).
+    #[error("all the slots for requests in flight are taken")]
+    WouldBlock,
 }
 
 #[derive(Debug, Error)]
 pub enum JsonRpcServerResponseStatusError {
     #[error("this client is unauthorized")]
     Unauthorized,
+    /// No longer returned, rate limited calls fail with [`RpcTransportError::RateLimited`].
+    #[deprecated(note = "rate limited calls fail with `RpcTransportError::RateLimited`")]
     #[error("this client has exceeded the rate limit")]
     TooManyRequests,
     #[error("the server returned a non-OK (200) status code: [{status}]")]
-    Unexpected { status: reqwest::StatusCode },
+    Unexpected { status: http::StatusCode },
 }
 
 #[derive(Debug, Error)]
@@ -65,15 +94,101 @@ pub enum JsonRpcServerError<E> {
     ResponseStatusError(JsonRpcServerResponseStatusError),
 }
 
+/// A failed attempt at a call, see [`DeadlineExceededError`].
+#[derive(Debug)]
+pub struct CallAttempt<E> {
+    /// The error the attempt failed with.
+    pub error: JsonRpcError<E>,
+    /// How long after the start of the call the attempt failed.
+    pub elapsed: Duration,
+}
+
+/// The call didn't complete within its deadline.
+///
+/// See [`CallOptions::deadline`](crate::CallOptions::deadline) and
+/// [`RetryPolicy::deadline`](crate::retry::RetryPolicy::deadline).
+#[derive(Debug, Error)]
+#[error("the deadline for the call was exceeded after {} attempt(s)", .attempts.len())]
+pub struct DeadlineExceededError<E> {
+    /// The failed attempts made before the deadline, in order.
+    pub attempts: Vec<CallAttempt<E>>,
+}
+
+/// The code of a contract doesn't hash to the code hash of its account, see
+/// [`JsonRpcClient::view_code`](crate::JsonRpcClient::view_code).
+#[derive(Debug, Error)]
+#[error("the contract code hashes to {computed}, but the account has code hash {expected}")]
+pub struct CodeHashMismatchError {
+    /// The code hash of the account.
+    pub expected: CryptoHash,
+    /// The hash of the code the server returned.
+    pub computed: CryptoHash,
+}
+
+/// An error from viewing the code of a contract, see
+/// [`JsonRpcClient::view_code`](crate::JsonRpcClient::view_code).
+#[derive(Debug, Error)]
+pub enum ViewCodeError {
+    #[error(transparent)]
+    Call(JsonRpcError<RpcQueryError>),
+    #[error(transparent)]
+    Mismatch(CodeHashMismatchError),
+}
+
+/// An error from sending a transaction, see
+/// [`JsonRpcClient::send_transaction`](crate::JsonRpcClient::send_transaction).
+#[derive(Debug, Error)]
+pub enum SendTransactionError {
+    /// Viewing the access key of the signer failed.
+    #[error(transparent)]
+    AccessKey(JsonRpcError<RpcQueryError>),
+    #[error(transparent)]
+    Build(TransactionBuilderError),
+    #[error("error while signing the transaction: [{0}]")]
+    Sign(BoxError),
+    #[error(transparent)]
+    Commit(JsonRpcError<RpcTransactionError>),
+}
+
+/// An error from working with a multisig account, see [`Multisig`](crate::multisig::Multisig).
+#[derive(Debug, Error)]
+pub enum MultisigError {
+    /// Viewing the contract failed.
+    #[error(transparent)]
+    View(JsonRpcError<RpcQueryError>),
+    /// Sending a call on the contract failed.
+    #[error(transparent)]
+    Send(SendTransactionError),
+    /// A call on the contract failed on chain.
+    #[error("the call on the contract failed: [{0}]")]
+    Failure(TxExecutionError),
+    #[error("error while parsing the contract's result: [{0}]")]
+    Result(serde_json::Error),
+}
+
+/// An error from waiting for a node to sync, see
+/// [`JsonRpcClient::wait_for_sync`](crate::JsonRpcClient::wait_for_sync).
+#[derive(Debug, Error)]
+pub enum WaitForSyncError {
+    #[error(transparent)]
+    Call(JsonRpcError<RpcStatusError>),
+    /// The node didn't sync before the timeout, this holds the last status it reported.
+    #[error("the node didn't sync in time")]
+    Timeout(Box<RpcStatusResponse>),
+}
+
 #[derive(Debug, Error)]
 pub enum JsonRpcError<E> {
     #[error(transparent)]
     TransportError(RpcTransportError),
     #[error(transparent)]
     ServerError(JsonRpcServerError<E>),
+    #[error(transparent)]
+    DeadlineExceeded(DeadlineExceededError<E>),
 }
 
 impl<E> JsonRpcError<E> {
+    #[allow(clippy::result_large_err)]
     pub fn handler_error(self) -> Result<E, Self> {
         match self {
             Self::ServerError(JsonRpcServerError::HandlerError(err)) => Ok(err),
@@ -82,6 +197,108 @@ impl<E> JsonRpcError<E> {
     }
 }
 
+impl<E> JsonRpcError<E> {
+    /// Whether the error counts as a failure of the endpoint, see [`CircuitBreaker`](crate::circuit::CircuitBreaker).
+    pub(crate) fn is_endpoint_failure(&self) -> bool {
+        match self {
+            Self::TransportError(err) => matches!(
+                err,
+                RpcTransportError::SendError(JsonRpcTransportSendError::PayloadSendError(_))
+                    | RpcTransportError::RecvError(JsonRpcTransportRecvError::PayloadRecvError(_))
+                    | RpcTransportError::TimedOut
+            ),
+            Self::ServerError(JsonRpcServerError::ResponseStatusError(
+                JsonRpcServerResponseStatusError::Unexpected { status },
+            )) => status.is_server_error(),
+            _ => false,
+        }
+    }
+}
+
+impl<E: super::methods::RpcHandlerError> JsonRpcError<E> {
+    /// Whether the call failing with this error may succeed if retried.
+    ///
+    /// This is the case for connection failures, timeouts, rate limiting, `5xx` responses,
+    /// internal server errors, and handler errors reported as
+    /// [retryable](super::methods::RpcHandlerError::is_retryable).
+    ///
+    /// Note that this doesn't account for whether the method itself is safe to retry,
+    /// see [`RpcMethod::is_idempotent`](super::methods::RpcMethod::is_idempotent).
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::TransportError(err) => matches!(
+                err,
+                RpcTransportError::SendError(JsonRpcTransportSendError::PayloadSendError(_))
+                    | RpcTransportError::RecvError(JsonRpcTransportRecvError::PayloadRecvError(_))
+                    | RpcTransportError::TimedOut
+                    | RpcTransportError::RateLimited { .. }
+            ),
+            Self::ServerError(err) => match err {
+                JsonRpcServerError::HandlerError(err) => err.is_retryable(),
+                JsonRpcServerError::InternalError { .. } => true,
+                JsonRpcServerError::ResponseStatusError(err) => match err {
+                    JsonRpcServerResponseStatusError::Unexpected { status } => {
+                        status.is_server_error() && *status != http::StatusCode::NOT_IMPLEMENTED
+                    }
+                    JsonRpcServerResponseStatusError::Unauthorized => false,
+                    #[allow(deprecated)]
+                    JsonRpcServerResponseStatusError::TooManyRequests => true,
+                },
+                JsonRpcServerError::RequestValidationError(_)
+                | JsonRpcServerError::NonContextualError(_) => false,
+            },
+            Self::DeadlineExceeded(_) => false,
+        }
+    }
+}
+
+/// Error code some providers use for rate limiting, on top of, or instead of, a `429` status.
+const RATE_LIMIT_ERROR_CODE: i64 = -32005;
+
+fn is_rate_limit_message(message: &str) -> bool {
+    let message = message.to_lowercase();
+    message.contains("rate limit") || message.contains("too many requests")
+}
+
+/// Whether a JSON RPC error is a provider-specific way of rate limiting the client.
+pub(crate) fn is_rate_limit_error(err: &RpcError) -> bool {
+    err.code == RATE_LIMIT_ERROR_CODE || is_rate_limit_message(&err.message)
+}
+
+/// Detect responses rate limiting the client, from their status or body.
+pub(crate) fn rate_limit_error(
+    status: http::StatusCode,
+    headers: &http::HeaderMap,
+    body: &[u8],
+) -> Option<RpcTransportError> {
+    let rate_limited = status == http::StatusCode::TOO_MANY_REQUESTS
+        || serde_json::from_slice::<serde_json::Value>(body).map_or(false, |body| {
+            let error = &body["error"];
+            error["code"] == RATE_LIMIT_ERROR_CODE
+                || [&error["message"], error, &body["message"]]
+                    .iter()
+                    .any(|message| message.as_str().map_or(false, is_rate_limit_message))
+        });
+    if !rate_limited {
+        return None;
+    }
+    Some(RpcTransportError::RateLimited {
+        retry_after: retry_after(headers),
+    })
+}
+
+/// Parse the `Retry-After` header, only the delay in seconds form is supported.
+pub(crate) fn retry_after(headers: &http::HeaderMap) -> Option<Duration> {
+    headers
+        .get(http::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
 impl<E: super::methods::RpcHandlerError> From<RpcError> for JsonRpcError<E> {
     fn from(err: RpcError) -> Self {
         let mut handler_parse_error = None;
@@ -4,8 +4,8 @@
 
 use std::marker::PhantomData;
 
-pub use reqwest::header::HeaderValue;
-use reqwest::header::IntoHeaderName;
+pub use http::header::HeaderValue;
+use http::header::IntoHeaderName;
 
 use super::JsonRpcClient;
 
@@ -0,0 +1,209 @@
+//! Composable middleware around the transport.
+//!
+//! A [`Layer`] configured on the client, with [`JsonRpcClient::layer`](crate::JsonRpcClient::layer),
+//! sits between the client and its [`Transport`], seeing every request, with its serialized
+//! JSON RPC payload, on its way to the server, and every raw response on its way back. A layer
+//! can inspect or modify either, hand the request over to the [`Next`] layer any number of
+//! times, or not at all and respond by itself, which makes for logging, caching or custom
+//! retry layers, among others.
+//!
+//! Layers are called in the order they were added, the first one added sees the request
+//! first and the response last. They run for each attempt at a call, after credentials from
+//! the auth provider, if any, are attached.
+//!
+//! With the `tracing` feature flag, [`PayloadLogger`] logs every payload, with signed
+//! transactions and access keys redacted.
+//!
+//! ## Example
+//!
+//! ```
+//! use near_jsonrpc_client::layer::{Layer, Next};
+//! use near_jsonrpc_client::transport::{BoxFuture, TransportError, TransportRequest, TransportResponse};
+//! use near_jsonrpc_client::JsonRpcClient;
+//!
+//! /// Tags every request, and logs the status of every response.
+//! struct Logger;
+//!
+//! impl Layer for Logger {
+//!     fn handle<'a>(
+//!         &'a self,
+//!         mut request: TransportRequest,
+//!         next: Next<'a>,
+//!     ) -> BoxFuture<'a, Result<TransportResponse, TransportError>> {
+//!         Box::pin(async move {
+//!             request.headers.insert("x-app", "explorer".parse().unwrap());
+//!             let response = next.run(request).await?;
+//!             eprintln!("responded with {}", response.status);
+//!             Ok(response)
+//!         })
+//!     }
+//! }
+//!
+//! let client = JsonRpcClient::connect("https://rpc.testnet.near.org").layer(Logger);
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::transport::{BoxFuture, Transport, TransportError, TransportRequest, TransportResponse};
+
+#[cfg(feature = "tracing")]
+mod logging;
+#[cfg(feature = "tracing")]
+pub use self::logging::PayloadLogger;
+
+#[cfg(feature = "opentelemetry")]
+mod trace_context;
+#[cfg(feature = "opentelemetry")]
+pub(crate) use self::trace_context::TraceContext;
+
+/// The layers every client starts with, according to the enabled feature flags.
+pub(crate) fn defaults() -> Vec<Arc<dyn Layer>> {
+    vec![
+        #[cfg(feature = "opentelemetry")]
+        Arc::new(TraceContext),
+    ]
+}
+
+/// Middleware around the transport, see the [module-level documentation](self).
+pub trait Layer: Send + Sync + 'static {
+    /// Handle a request, usually by handing it over to the next layer.
+    fn handle<'a>(
+        &'a self,
+        request: TransportRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<TransportResponse, TransportError>>;
+}
+
+/// The rest of the layers, down to the transport, see [`Layer::handle`].
+#[derive(Clone, Copy)]
+pub struct Next<'a> {
+    layers: &'a [Arc<dyn Layer>],
+    transport: &'a dyn Transport,
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn new(layers: &'a [Arc<dyn Layer>], transport: &'a dyn Transport) -> Self {
+        Self { layers, transport }
+    }
+
+    /// Hand the request over to the next layer, or to the transport after the last one.
+    pub fn run(
+        self,
+        request: TransportRequest,
+    ) -> BoxFuture<'a, Result<TransportResponse, TransportError>> {
+        match self.layers.split_first() {
+            Some((layer, layers)) => layer.handle(request, Next { layers, ..self }),
+            None => self.transport.send(request),
+        }
+    }
+}
+
+impl fmt::Debug for Next<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Next")
+            .field("layers", &self.layers.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::methods;
+    use crate::transport::testing::{json_rpc_result, FnTransport};
+    use crate::JsonRpcClient;
+
+    /// Records the methods it sees, under a name.
+    struct Recorder(&'static str, Arc<Mutex<Vec<String>>>);
+
+    impl Layer for Recorder {
+        fn handle<'a>(
+            &'a self,
+            request: TransportRequest,
+            next: Next<'a>,
+        ) -> BoxFuture<'a, Result<TransportResponse, TransportError>> {
+            Box::pin(async move {
+                let payload: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+                let log = |event| self.1.lock().unwrap().push(format!("{} {}", self.0, event));
+                log(format!("request {}", payload["method"].as_str().unwrap()));
+                let response = next.run(request).await;
+                log(format!("response {}", response.as_ref().unwrap().status));
+                response
+            })
+        }
+    }
+
+    /// Caches results by method, responding by itself to methods it has seen before.
+    #[derive(Default)]
+    struct Cache(Mutex<HashMap<String, serde_json::Value>>);
+
+    impl Layer for Cache {
+        fn handle<'a>(
+            &'a self,
+            request: TransportRequest,
+            next: Next<'a>,
+        ) -> BoxFuture<'a, Result<TransportResponse, TransportError>> {
+            Box::pin(async move {
+                let payload: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+                let method = payload["method"].as_str().unwrap().to_string();
+                let cached = self.0.lock().unwrap().get(&method).cloned();
+                if let Some(result) = cached {
+                    return Ok(json_rpc_result(&payload["id"], result));
+                }
+                let response = next.run(request).await?;
+                let body: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+                self.0
+                    .lock()
+                    .unwrap()
+                    .insert(method, body["result"].clone());
+                Ok(response)
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn layers() {
+        let sent = Arc::new(Mutex::new(0));
+        // responds to every request with a `null` result, counting them
+        let transport = FnTransport::new({
+            let sent = sent.clone();
+            move |_, payload| {
+                *sent.lock().unwrap() += 1;
+                Ok(json_rpc_result(&payload["id"], serde_json::Value::Null))
+            }
+        });
+        let log = Arc::new(Mutex::new(vec![]));
+        let client = JsonRpcClient::with_transport(transport)
+            .connect("http://localhost:3030")
+            .layer(Recorder("outer", log.clone()))
+            .layer(Recorder("inner", log.clone()))
+            .layer(Cache::default());
+
+        client
+            .call(methods::health::RpcHealthRequest)
+            .await
+            .unwrap();
+        assert_eq!(*sent.lock().unwrap(), 1);
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "outer request health",
+                "inner request health",
+                "inner response 200 OK",
+                "outer response 200 OK",
+            ]
+        );
+
+        log.lock().unwrap().clear();
+        client
+            .call(methods::health::RpcHealthRequest)
+            .await
+            .unwrap();
+        assert_eq!(*sent.lock().unwrap(), 1, "expected the cache to respond");
+        assert_eq!(log.lock().unwrap().len(), 4);
+    }
+}
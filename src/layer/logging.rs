@@ -0,0 +1,210 @@
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+use super::{Layer, Next};
+use crate::retry::Instant;
+use crate::transport::{BoxFuture, TransportError, TransportRequest, TransportResponse};
+
+/// Methods whose params are a signed transaction.
+const SIGNED_TRANSACTION_METHODS: &[&str] =
+    &["broadcast_tx_async", "broadcast_tx_commit", "send_tx"];
+
+/// Fields redacted by default, wherever they appear, holding signatures or access keys.
+const REDACTED_FIELDS: &[&str] = &[
+    "signature",
+    "public_key",
+    "access_key",
+    "AccessKey",
+    "keys",
+    "permission",
+    "private_key",
+    "secret_key",
+];
+
+const REDACTED: &str = "[redacted]";
+
+/// A [`Layer`] logging every request and response payload, as `debug` events with the
+/// `tracing` crate, under the `near_jsonrpc_client::payload` target.
+///
+/// By default, signed transactions, signatures and access keys are redacted from the logged
+/// payloads, so that no signable material ends up in log aggregation. The requests and
+/// responses themselves are left untouched.
+///
+/// Available with the `tracing` feature flag.
+///
+/// ## Example
+///
+/// ```
+/// use near_jsonrpc_client::{layer::PayloadLogger, JsonRpcClient};
+///
+/// let client = JsonRpcClient::connect("https://rpc.testnet.near.org")
+///     .layer(PayloadLogger::new().redact_field("nonce"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct PayloadLogger {
+    redact: bool,
+    fields: HashSet<String>,
+}
+
+impl Default for PayloadLogger {
+    fn default() -> Self {
+        Self {
+            redact: true,
+            fields: REDACTED_FIELDS
+                .iter()
+                .map(|field| field.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl PayloadLogger {
+    /// Create a new logger, redacting signed transactions, signatures and access keys.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also redact the specified field, wherever it appears in a payload.
+    pub fn redact_field<F: Into<String>>(mut self, field: F) -> Self {
+        self.fields.insert(field.into());
+        self
+    }
+
+    /// Log payloads as they are, without redacting anything.
+    ///
+    /// Only use this where the logs can't leak, as they will hold signed transactions.
+    pub fn without_redaction(mut self) -> Self {
+        self.redact = false;
+        self
+    }
+
+    /// Render a payload for logging, redacting it as configured.
+    fn render(&self, body: &[u8], method: Option<&str>) -> String {
+        let mut payload: Value = match serde_json::from_slice(body) {
+            Ok(payload) => payload,
+            Err(_) => return String::from_utf8_lossy(body).into_owned(),
+        };
+        if self.redact {
+            if method.map_or(false, |method| SIGNED_TRANSACTION_METHODS.contains(&method)) {
+                payload["params"] = REDACTED.into();
+            }
+            self.redact_fields(&mut payload);
+        }
+        payload.to_string()
+    }
+
+    fn redact_fields(&self, value: &mut Value) {
+        match value {
+            Value::Object(fields) => {
+                for (name, value) in fields {
+                    if self.fields.contains(name) {
+                        *value = REDACTED.into();
+                    } else {
+                        self.redact_fields(value);
+                    }
+                }
+            }
+            Value::Array(values) => values
+                .iter_mut()
+                .for_each(|value| self.redact_fields(value)),
+            _ => {}
+        }
+    }
+}
+
+impl Layer for PayloadLogger {
+    fn handle<'a>(
+        &'a self,
+        request: TransportRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<TransportResponse, TransportError>> {
+        Box::pin(async move {
+            let method = serde_json::from_slice::<Value>(&request.body)
+                .ok()
+                .and_then(|payload| payload["method"].as_str().map(str::to_string));
+            tracing::debug!(
+                target: "near_jsonrpc_client::payload",
+                server_addr = %request.server_addr,
+                method = method.as_deref().unwrap_or_default(),
+                payload = %self.render(&request.body, method.as_deref()),
+                "request"
+            );
+            let start = Instant::now();
+            let response = next.run(request).await;
+            match response {
+                Ok(ref response) => tracing::debug!(
+                    target: "near_jsonrpc_client::payload",
+                    method = method.as_deref().unwrap_or_default(),
+                    status = response.status.as_u16(),
+                    latency_ms = start.elapsed().as_millis() as u64,
+                    payload = %self.render(&response.body, None),
+                    "response"
+                ),
+                Err(ref err) => tracing::debug!(
+                    target: "near_jsonrpc_client::payload",
+                    method = method.as_deref().unwrap_or_default(),
+                    latency_ms = start.elapsed().as_millis() as u64,
+                    error = %err,
+                    "no response"
+                ),
+            }
+            response
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn render(logger: &PayloadLogger, payload: Value, method: Option<&str>) -> Value {
+        serde_json::from_str(&logger.render(payload.to_string().as_bytes(), method)).unwrap()
+    }
+
+    #[test]
+    fn redaction() {
+        let logger = PayloadLogger::new();
+
+        let broadcast = json!({
+            "jsonrpc": "2.0",
+            "id": "1",
+            "method": "broadcast_tx_commit",
+            "params": ["DgAAAHNlbmRlci50ZXN0bmV0AOrmAai64SZOv9e/naX4W15pJx0GAap35wTT1T/DwcbbDwAAAAAAAAAQAAAAcmVjZWl2ZXIudGVzdG5ldNMnL7URB1cxPOu3G8jTqlEwlcasagIbKlAJlF5ywVFLAQAAAAMAAACh7czOG8LTAAAAAAAAAGQcOG03xVSFQFjoagOb4NBBqWhERnnz45LY4+52JgZhm1iQKz7qAdPByrGFDQhQ2Rv7puYA35kuR8yNolldxux0kAI="],
+        });
+        assert_eq!(
+            render(&logger, broadcast, Some("broadcast_tx_commit"))["params"],
+            REDACTED
+        );
+
+        let access_keys = json!({
+            "jsonrpc": "2.0",
+            "id": "1",
+            "result": {
+                "block_height": 1,
+                "keys": [{ "public_key": "ed25519:...", "access_key": { "nonce": 1 } }],
+            },
+        });
+        let rendered = render(&logger, access_keys, None);
+        assert_eq!(rendered["result"]["keys"], REDACTED);
+        assert_eq!(rendered["result"]["block_height"], 1);
+
+        let rendered = render(
+            &logger.clone().redact_field("nonce"),
+            json!({ "result": { "nonce": 7, "permission": "FullAccess" } }),
+            None,
+        );
+        assert_eq!(
+            rendered,
+            json!({ "result": { "nonce": REDACTED, "permission": REDACTED } })
+        );
+
+        let payload = json!({ "params": { "public_key": "ed25519:..." } });
+        assert_eq!(
+            render(&logger.without_redaction(), payload.clone(), None),
+            payload
+        );
+    }
+}
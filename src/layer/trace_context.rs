@@ -0,0 +1,156 @@
+use std::borrow::Cow;
+
+use opentelemetry::propagation::Injector;
+use opentelemetry::trace::{SpanKind, Status, TraceContextExt, Tracer};
+use opentelemetry::{global, Context, KeyValue};
+
+use super::{Layer, Next};
+use crate::transport::{BoxFuture, TransportError, TransportRequest, TransportResponse};
+
+/// Records every request as an OpenTelemetry client span, propagating its context to the
+/// server in the request headers, see the [crate-level documentation](crate#opentelemetry).
+#[derive(Debug)]
+pub(crate) struct TraceContext;
+
+struct HeaderInjector<'a>(&'a mut http::HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        // headers set explicitly on the call take precedence
+        if let (Ok(name), Ok(value)) = (
+            http::header::HeaderName::from_bytes(key.as_bytes()),
+            http::HeaderValue::from_str(&value),
+        ) {
+            self.0.entry(name).or_insert(value);
+        }
+    }
+}
+
+impl Layer for TraceContext {
+    fn handle<'a>(
+        &'a self,
+        mut request: TransportRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<TransportResponse, TransportError>> {
+        let method = serde_json::from_slice::<serde_json::Value>(&request.body)
+            .ok()
+            .and_then(|payload| payload["method"].as_str().map(str::to_string))
+            .unwrap_or_default();
+        let tracer = global::tracer("near-jsonrpc-client");
+        let span = tracer
+            .span_builder(Cow::Owned(method.clone()))
+            .with_kind(SpanKind::Client)
+            .with_attributes(vec![
+                KeyValue::new("rpc.system", "jsonrpc"),
+                KeyValue::new("rpc.method", method),
+                KeyValue::new("server.address", request.server_addr.clone()),
+            ])
+            .start(&tracer);
+        let cx = Context::current_with_span(span);
+        global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(&cx, &mut HeaderInjector(&mut request.headers))
+        });
+
+        Box::pin(async move {
+            let response = next.run(request).await;
+            let span = cx.span();
+            match response {
+                Ok(ref response) => {
+                    span.set_attribute(KeyValue::new(
+                        "http.response.status_code",
+                        i64::from(response.status.as_u16()),
+                    ));
+                    if !response.status.is_success() {
+                        span.set_status(Status::error(response.status.to_string()));
+                    }
+                }
+                Err(ref err) => span.set_status(Status::error(err.to_string())),
+            }
+            span.end();
+            response
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use opentelemetry::propagation::text_map_propagator::FieldIter;
+    use opentelemetry::propagation::{Extractor, TextMapPropagator};
+    use opentelemetry::trace::{SpanContext, SpanId, TraceFlags, TraceId, TraceState};
+
+    use super::*;
+    use crate::methods;
+    use crate::transport::testing::{json_rpc_result, FnTransport};
+    use crate::{CallOptions, JsonRpcClient};
+
+    /// Propagates the span context as a `traceparent`, standing in for the SDK's propagator.
+    #[derive(Debug)]
+    struct TraceParentPropagator;
+
+    impl TextMapPropagator for TraceParentPropagator {
+        fn inject_context(&self, cx: &Context, injector: &mut dyn Injector) {
+            let span = cx.span();
+            let span = span.span_context();
+            injector.set(
+                "traceparent",
+                format!("00-{}-{}-01", span.trace_id(), span.span_id()),
+            );
+        }
+
+        fn extract_with_context(&self, cx: &Context, _: &dyn Extractor) -> Context {
+            cx.clone()
+        }
+
+        fn fields(&self) -> FieldIter<'_> {
+            FieldIter::new(&[])
+        }
+    }
+
+    #[tokio::test]
+    async fn trace_context() {
+        global::set_text_map_propagator(TraceParentPropagator);
+
+        let headers = Arc::new(Mutex::new(vec![]));
+        // records the headers of every request
+        let transport = FnTransport::new({
+            let headers = headers.clone();
+            move |request, payload| {
+                headers.lock().unwrap().push(request.headers.clone());
+                Ok(json_rpc_result(&payload["id"], serde_json::Value::Null))
+            }
+        });
+        let client = JsonRpcClient::with_transport(transport).connect("http://localhost:3030");
+
+        // the call is made within a trace coming from upstream
+        let _guard = Context::current()
+            .with_remote_span_context(SpanContext::new(
+                TraceId::from_bytes(0x0af7651916cd43dd8448eb211c80319c_u128.to_be_bytes()),
+                SpanId::from_bytes(0xb7ad6b7169203331_u64.to_be_bytes()),
+                TraceFlags::SAMPLED,
+                true,
+                TraceState::default(),
+            ))
+            .attach();
+
+        client
+            .call(methods::health::RpcHealthRequest)
+            .await
+            .unwrap();
+        client
+            .call_with_options(
+                methods::health::RpcHealthRequest,
+                CallOptions::new().header("traceparent", "explicit".parse().unwrap()),
+            )
+            .await
+            .unwrap();
+
+        let headers = headers.lock().unwrap();
+        assert_eq!(
+            headers[0]["traceparent"],
+            "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01"
+        );
+        assert_eq!(headers[1]["traceparent"], "explicit");
+    }
+}
@@ -110,63 +110,319 @@
 //!    # Ok(())
 //!    # }
 //!    ```
+//!
+//!    Types that only implement `Deserialize` can be used without implementing `RpcHandlerResponse`,
+//!    by wrapping them in `methods::Raw`, as in `methods::any::<Raw<PartialGenesisConfig>>(..)`.
+//!    Wrap the error type too, as in `Result<Raw<T>, Raw<E>>`, for typed errors.
+//!
+//! ## Tracing
+//!
+//! With the `tracing` feature flag, every call is instrumented with an `rpc_call` span, recording
+//! the method name and the server address, nested within the caller's current span. Each attempt
+//! at the call, retries included, gets an `rpc_attempt` span of its own, recording the attempt
+//! index, the JSON RPC request id, the server address and, once it completes, its outcome: either
+//! `success` or the [class](metrics::ErrorClass) of the error it failed with. Both emit a `debug`
+//! event on completion, with their latency.
+//!
+//! With a [`JsonRpcMultiClient`], the calls to each endpoint tried are grouped under an
+//! `rpc_multi_call` span, with the attempts indexed across endpoints, in the order they started.
+//!
+//! ## OpenTelemetry
+//!
+//! With the `opentelemetry` feature flag, every attempt at a call is recorded as a client span
+//! with the [global tracer](https://docs.rs/opentelemetry/latest/opentelemetry/global/fn.tracer.html),
+//! as a child of the current OpenTelemetry context, and its context is injected in the request
+//! headers with the global propagator, such as `traceparent` and `tracestate` with the W3C trace
+//! context propagator. Headers set explicitly on the client or on the call take precedence.
+//!
+//! The context is thread-local, so calls should be made with it attached, e.g. with
+//! `FutureExt::with_context` from the `opentelemetry` crate.
 
-use std::{fmt, sync::Arc};
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
+#[cfg(any(
+    feature = "reqwest-transport",
+    feature = "hyper-transport",
+    feature = "isahc-transport",
+    all(feature = "unix-socket", unix)
+))]
 use lazy_static::lazy_static;
 
 use near_jsonrpc_primitives::message::{from_slice, Message};
 
 #[cfg(feature = "auth")]
 pub mod auth;
+#[cfg(all(feature = "blocking", target_arch = "wasm32"))]
+compile_error!("the `blocking` feature flag is not supported on `wasm32` targets");
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod capture;
+pub mod circuit;
+pub mod connector;
 pub mod errors;
 pub mod header;
+pub mod layer;
 pub mod methods;
+pub mod metrics;
+pub mod multi;
+pub mod multisig;
+pub mod nonce;
+pub mod rate_limit;
+pub mod request_id;
+pub mod retry;
+pub mod transaction;
+pub mod transport;
 
+pub use connector::JsonRpcClientConnector;
 use errors::*;
+pub use multi::JsonRpcMultiClient;
+use transport::{Transport, TransportError, TransportRequest, TransportResponse};
 
 pub const NEAR_MAINNET_RPC_URL: &str = "https://rpc.mainnet.near.org";
 pub const NEAR_TESTNET_RPC_URL: &str = "https://rpc.testnet.near.org";
 pub const NEAR_MAINNET_ARCHIVAL_RPC_URL: &str = "https://archival-rpc.mainnet.near.org";
 pub const NEAR_TESTNET_ARCHIVAL_RPC_URL: &str = "https://archival-rpc.testnet.near.org";
 
+#[cfg(any(
+    feature = "reqwest-transport",
+    feature = "hyper-transport",
+    feature = "isahc-transport"
+))]
 lazy_static! {
     static ref DEFAULT_CONNECTOR: JsonRpcClientConnector = JsonRpcClient::new_client();
 }
 
-/// NEAR JSON RPC client connector.
-#[derive(Clone)]
-pub struct JsonRpcClientConnector {
-    client: reqwest::Client,
+#[cfg(all(feature = "unix-socket", unix))]
+lazy_static! {
+    static ref UNIX_SOCKET_CONNECTOR: JsonRpcClientConnector =
+        JsonRpcClient::with_transport(transport::UnixSocketTransport::new());
 }
 
-impl JsonRpcClientConnector {
-    /// Return a JsonRpcClient that connects to the specified server.
-    pub fn connect<U: AsUrl>(&self, server_addr: U) -> JsonRpcClient {
-        JsonRpcClient {
-            inner: Arc::new(JsonRpcInnerClient {
-                server_addr: server_addr.to_string(),
-                client: self.client.clone(),
-            }),
-            headers: reqwest::header::HeaderMap::new(),
+struct JsonRpcInnerClient {
+    server_addr: String,
+    transport: Arc<dyn Transport>,
+    /// Consecutive failures to send a request, see [`JsonRpcClient::refresh_connections`].
+    connect_failures: AtomicU32,
+}
+
+/// Run a call, or an attempt at one, within the specified span, emitting its outcome and latency.
+#[cfg(feature = "tracing")]
+async fn traced<T, E>(
+    span: tracing::Span,
+    future: impl std::future::Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    use tracing::Instrument;
+
+    let start = retry::Instant::now();
+    let result = future.instrument(span.clone()).await;
+    span.in_scope(|| {
+        tracing::debug!(
+            latency_ms = start.elapsed().as_millis() as u64,
+            success = result.is_ok(),
+            "completed"
+        )
+    });
+    result
+}
+
+fn transport_error(err: TransportError) -> RpcTransportError {
+    match err {
+        TransportError::SendError(err) => {
+            RpcTransportError::SendError(JsonRpcTransportSendError::PayloadSendError(err))
+        }
+        TransportError::RecvError(err) => {
+            RpcTransportError::RecvError(JsonRpcTransportRecvError::PayloadRecvError(err))
         }
+        TransportError::TimedOut => RpcTransportError::TimedOut,
+        TransportError::CertificatePinMismatch => RpcTransportError::CertificatePinMismatch,
     }
 }
 
-struct JsonRpcInnerClient {
-    server_addr: String,
-    client: reqwest::Client,
+/// The error of a query responding with a kind other than the one requested.
+fn unexpected_query_response<E>(
+    kind: near_jsonrpc_primitives::types::query::QueryResponseKind,
+) -> JsonRpcError<E> {
+    JsonRpcError::TransportError(RpcTransportError::RecvError(
+        JsonRpcTransportRecvError::ResponseParseError(
+            JsonRpcTransportHandlerResponseError::ResultParseError(serde::de::Error::custom(
+                format_args!("unexpected query response: {:?}", kind),
+            )),
+        ),
+    ))
 }
 
+/// How many times in a row sending a request has to fail before the transport is refreshed.
+const CONNECT_FAILURES_BEFORE_REFRESH: u32 = 3;
+
+/// How many times [`JsonRpcClient::send_transaction`] signs and sends a transaction, if the
+/// node rejects it for its nonce or block hash.
+const SEND_TRANSACTION_ATTEMPTS: u32 = 3;
+
 #[derive(Clone)]
 /// A NEAR JSON RPC Client.
 pub struct JsonRpcClient {
     inner: Arc<JsonRpcInnerClient>,
-    headers: reqwest::header::HeaderMap,
+    headers: http::HeaderMap,
+    #[cfg(feature = "auth")]
+    auth_provider: Option<Arc<dyn auth::AuthProvider>>,
+    retry_policy: Option<retry::RetryPolicy>,
+    circuit_breaker: Option<circuit::CircuitBreaker>,
+    rate_limiter: Option<rate_limit::RateLimiter>,
+    in_flight: Option<Arc<async_lock::Semaphore>>,
+    archival: Option<Arc<JsonRpcClient>>,
+    metrics: Option<Arc<dyn metrics::MetricsSink>>,
+    layers: Vec<Arc<dyn layer::Layer>>,
+    id_generator: Option<Arc<dyn request_id::IdGenerator>>,
+    slow_calls: Option<metrics::SlowCallPolicy>,
 }
 
 pub type MethodCallResult<T, E> = Result<T, JsonRpcError<E>>;
 
+/// Metadata about a completed RPC call, see [`JsonRpcClient::call_with_meta`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct CallMeta {
+    /// The URL that served the response, after following any redirects.
+    pub url: String,
+    /// How long the call waited for a slot before being sent, see [`JsonRpcClient::max_in_flight`].
+    pub queue_time: Duration,
+    /// The HTTP status of the response.
+    pub status: http::StatusCode,
+    /// The headers of the response, such as the rate limiting or billing headers some
+    /// providers send.
+    pub headers: http::HeaderMap,
+    /// The address of the server that was called, which is the archival server for calls
+    /// served by the [archival fallback](JsonRpcClient::with_archival_fallback).
+    pub server_addr: String,
+    /// How many attempts it took the server to serve the call, retries included.
+    pub attempts: u32,
+    /// How long the call took, from start to finish.
+    pub latency: Duration,
+    /// The JSON RPC request id the call was sent with, see the [`request_id`] module.
+    pub request_id: String,
+}
+
+/// Options for a single RPC call, see [`JsonRpcClient::call_with_options`].
+#[derive(Clone, Debug, Default)]
+pub struct CallOptions {
+    /// How long to wait for the call to complete before giving up.
+    ///
+    /// This is ignored on `wasm32` targets.
+    pub timeout: Option<Duration>,
+    /// Extra headers sent with this call only.
+    ///
+    /// These take precedence over the client's headers.
+    pub headers: http::HeaderMap,
+    /// How long to spend on the call as a whole, across all its attempts.
+    ///
+    /// With a [`JsonRpcMultiClient`], this also spans hedges and failover attempts.
+    ///
+    /// This takes precedence over the deadline of the client's retry policy.
+    ///
+    /// On `wasm32` targets, attempts already in flight aren't interrupted.
+    pub deadline: Option<Duration>,
+    /// How to retry this call, in place of the client's retry policy.
+    pub retry_policy: Option<retry::RetryPolicy>,
+    /// The JSON RPC request id to send this call with, in place of a generated one.
+    pub request_id: Option<String>,
+    /// Whether to keep this call from falling back to the client's
+    /// [archival server](JsonRpcClient::with_archival_fallback).
+    pub no_archival_fallback: bool,
+}
+
+impl CallOptions {
+    /// Create a new set of call options, with no timeout, no deadline and no extra headers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Give up on the call if it doesn't complete within the specified duration.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Fail the call with [`JsonRpcError::DeadlineExceeded`] if it, including any retries,
+    /// doesn't complete within the specified duration.
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Retry this call according to the specified policy, instead of the client's.
+    ///
+    /// Use a policy with a single attempt to disable retries for this call.
+    pub fn retry_policy(mut self, policy: retry::RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Send this call with the specified JSON RPC request id, for correlating it with the
+    /// server's logs.
+    ///
+    /// See the [`request_id`] module for more details.
+    pub fn request_id<I: Into<String>>(mut self, id: I) -> Self {
+        self.request_id = Some(id.into());
+        self
+    }
+
+    /// Don't send this call to the client's
+    /// [archival server](JsonRpcClient::with_archival_fallback), even if the data it asks for
+    /// seems to have been garbage collected.
+    ///
+    /// Like for a transaction that was just sent, which the server doesn't know about because
+    /// it hasn't been included yet, and which the archival server wouldn't know about either.
+    pub fn no_archival_fallback(mut self) -> Self {
+        self.no_archival_fallback = true;
+        self
+    }
+
+    /// These options, with the deadline set to what's left until the specified instant,
+    /// or `None` if it has passed.
+    pub(crate) fn within(&self, deadline: Option<retry::Instant>) -> Option<Self> {
+        let deadline = match deadline {
+            Some(deadline) => deadline,
+            None => return Some(self.clone()),
+        };
+        let remaining = deadline.saturating_duration_since(retry::Instant::now());
+        if remaining == Duration::ZERO {
+            return None;
+        }
+        Some(Self {
+            deadline: Some(remaining),
+            ..self.clone()
+        })
+    }
+
+    /// Add a header to be sent with this call only.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use near_jsonrpc_client::{header::HeaderValue, CallOptions};
+    ///
+    /// let options = CallOptions::new()
+    ///     .header("x-tenant-id", HeaderValue::from_static("acme"))
+    ///     .header("traceparent", HeaderValue::from_static("00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01"));
+    /// ```
+    pub fn header<K: http::header::IntoHeaderName>(
+        mut self,
+        name: K,
+        value: http::HeaderValue,
+    ) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+}
+
 impl JsonRpcClient {
     /// Connect to a JSON RPC server using the default connector.
     ///
@@ -185,59 +441,1114 @@ impl JsonRpcClient {
     /// let status_request = methods::status::RpcStatusRequest;
     /// let server_status = mainnet_client.call(status_request).await?;
     ///
-    /// println!("{:?}", server_status);
-    /// # Ok(())
-    /// # }
-    /// ```
-    pub fn connect<U: AsUrl>(server_addr: U) -> JsonRpcClient {
-        DEFAULT_CONNECTOR.connect(server_addr)
+    /// println!("{:?}", server_status);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(any(
+        feature = "reqwest-transport",
+        feature = "hyper-transport",
+        feature = "isahc-transport"
+    ))]
+    pub fn connect<U: AsUrl>(server_addr: U) -> JsonRpcClient {
+        DEFAULT_CONNECTOR.connect(server_addr)
+    }
+
+    /// Connect to a JSON RPC server listening on a unix domain socket.
+    ///
+    /// This is useful for talking to a neard instance running on the same host,
+    /// without going through the network stack.
+    ///
+    /// Available with the `unix-socket` feature flag, on unix platforms.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use near_jsonrpc_client::{methods, JsonRpcClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = JsonRpcClient::connect_unix("/run/neard/rpc.sock");
+    ///
+    /// assert_eq!(client.server_addr(), "unix:/run/neard/rpc.sock");
+    ///
+    /// let server_status = client.call(methods::status::RpcStatusRequest).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(all(feature = "unix-socket", unix))]
+    pub fn connect_unix<P: AsRef<std::path::Path>>(socket_path: P) -> JsonRpcClient {
+        UNIX_SOCKET_CONNECTOR.connect(connector::unix_socket_url(socket_path.as_ref()))
+    }
+
+    /// Get the server address the client connects to.
+    pub fn server_addr(&self) -> &str {
+        &self.inner.server_addr
+    }
+
+    /// RPC method executor for the client.
+    pub async fn call<M>(&self, method: M) -> MethodCallResult<M::Response, M::Error>
+    where
+        M: methods::RpcMethod,
+    {
+        self.call_with_options(method, CallOptions::default()).await
+    }
+
+    /// RPC method executor for the client, failing fast instead of queueing the call.
+    ///
+    /// With [`max_in_flight`](JsonRpcClient::max_in_flight) set, calls made while all
+    /// the slots are taken fail with [`RpcTransportError::WouldBlock`], without being sent,
+    /// so that callers can shed load instead. Retries of a call that got a slot still
+    /// queue for one.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use near_jsonrpc_client::{errors::{JsonRpcError, RpcTransportError}, methods, JsonRpcClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = JsonRpcClient::connect("https://rpc.testnet.near.org").max_in_flight(16);
+    ///
+    /// match client.try_call(methods::status::RpcStatusRequest).await {
+    ///     Ok(server_status) => println!("{:?}", server_status),
+    ///     Err(JsonRpcError::TransportError(RpcTransportError::WouldBlock)) => {
+    ///         println!("too busy, try again later")
+    ///     }
+    ///     Err(err) => return Err(err.into()),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn try_call<M>(&self, method: M) -> MethodCallResult<M::Response, M::Error>
+    where
+        M: methods::RpcMethod,
+    {
+        let slot = match self.in_flight {
+            Some(ref in_flight) => Some(
+                in_flight
+                    .try_acquire_arc()
+                    .ok_or(JsonRpcError::TransportError(RpcTransportError::WouldBlock))?,
+            ),
+            None => None,
+        };
+        self.execute_in(
+            &method,
+            CallOptions::default(),
+            slot,
+            &metrics::AttemptLog::default(),
+        )
+        .await
+        .map(|(response, _)| response)
+    }
+
+    /// RPC method executor for the client, with options applying to this call only.
+    ///
+    /// Calls exceeding their timeout fail with [`RpcTransportError::TimedOut`].
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use near_jsonrpc_client::{methods, CallOptions, JsonRpcClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = JsonRpcClient::connect("https://rpc.testnet.near.org");
+    ///
+    /// let server_status = client
+    ///     .call_with_options(
+    ///         methods::status::RpcStatusRequest,
+    ///         CallOptions::new().timeout(Duration::from_secs(5)),
+    ///     )
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn call_with_options<M>(
+        &self,
+        method: M,
+        options: CallOptions,
+    ) -> MethodCallResult<M::Response, M::Error>
+    where
+        M: methods::RpcMethod,
+    {
+        self.execute(&method, options)
+            .await
+            .map(|(response, _)| response)
+    }
+
+    /// RPC method executor for the client, also returning metadata about the call.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use near_jsonrpc_client::{methods, JsonRpcClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = JsonRpcClient::connect("https://rpc.testnet.near.org");
+    ///
+    /// let (server_status, meta) = client
+    ///     .call_with_meta(methods::status::RpcStatusRequest)
+    ///     .await?;
+    ///
+    /// println!("served by {} in {:?}", meta.url, meta.latency);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn call_with_meta<M>(
+        &self,
+        method: M,
+    ) -> MethodCallResult<(M::Response, CallMeta), M::Error>
+    where
+        M: methods::RpcMethod,
+    {
+        self.execute(&method, CallOptions::default()).await
+    }
+
+    /// Commit a transaction with `broadcast_tx_commit`, retrying it without risking double
+    /// submission.
+    ///
+    /// `broadcast_tx_commit` isn't safe to retry blindly: a call timing out may still have
+    /// reached the server, and the transaction may already be executing. Instead, when the call
+    /// fails with a [retryable](errors::JsonRpcError::is_retryable) error, the status of the
+    /// transaction is checked with `tx`, by its hash. The transaction is only broadcast again
+    /// if the server doesn't know about it, otherwise the outcome found is returned.
+    ///
+    /// Attempts and delays follow the client's [retry policy](JsonRpcClient::retry_policy),
+    /// without one, this makes a single attempt.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use near_jsonrpc_client::{methods, retry::RetryPolicy, JsonRpcClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let signed_transaction: methods::broadcast_tx_commit::SignedTransaction = unimplemented!();
+    /// let client = JsonRpcClient::connect("https://rpc.testnet.near.org").retry_policy(RetryPolicy::new());
+    ///
+    /// let outcome = client.commit_transaction(signed_transaction).await?;
+    ///
+    /// println!("{:?}", outcome.status);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn commit_transaction(
+        &self,
+        signed_transaction: methods::broadcast_tx_commit::SignedTransaction,
+    ) -> MethodCallResult<
+        methods::broadcast_tx_commit::RpcBroadcastTxCommitResponse,
+        methods::broadcast_tx_commit::RpcTransactionError,
+    > {
+        let status_request = methods::tx::RpcTransactionStatusRequest {
+            transaction_info: methods::tx::TransactionInfo::TransactionId {
+                hash: signed_transaction.get_hash(),
+                account_id: signed_transaction.transaction.signer_id.clone(),
+            },
+        };
+        let request =
+            methods::broadcast_tx_commit::RpcBroadcastTxCommitRequest { signed_transaction };
+
+        let max_attempts = self
+            .retry_policy
+            .as_ref()
+            .map_or(1, |policy| policy.get_max_attempts());
+        // the deadline bounds the whole exchange, not each call separately
+        let deadline = self
+            .retry_policy
+            .as_ref()
+            .and_then(|policy| policy.get_deadline())
+            .and_then(|deadline| retry::Instant::now().checked_add(deadline));
+        let deadline_exceeded =
+            || JsonRpcError::DeadlineExceeded(DeadlineExceededError { attempts: vec![] });
+
+        let mut attempt = 1;
+        loop {
+            let options = CallOptions::default()
+                .within(deadline)
+                .ok_or_else(deadline_exceeded)?;
+            let err = match self.call_with_options(&request, options).await {
+                Err(err) if err.is_retryable() && attempt < max_attempts => err,
+                result => return result,
+            };
+
+            // the transaction may have gone through, look it up before sending it again
+            let options = match CallOptions::default()
+                .no_archival_fallback()
+                .within(deadline)
+            {
+                Some(options) => options,
+                None => return Err(err),
+            };
+            match self.call_with_options(&status_request, options).await {
+                Err(JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+                    methods::tx::RpcTransactionError::UnknownTransaction { .. },
+                ))) => {}
+                Ok(outcome) => return Ok(outcome),
+                Err(_) => return Err(err),
+            }
+
+            if let Some(ref policy) = self.retry_policy {
+                retry::sleep(policy.backoff(attempt)).await;
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Commit a transaction like [`commit_transaction`](Self::commit_transaction), but if the
+    /// server times out waiting for it to execute, keep waiting for its outcome, for up to
+    /// `max_wait`.
+    ///
+    /// Commits time out with a [`TimeoutError`](methods::tx::RpcTransactionError::TimeoutError)
+    /// once the server has been waiting on the transaction for a while, though it's still
+    /// executing. See [`wait_for_transaction`](Self::wait_for_transaction).
+    pub async fn commit_transaction_and_wait(
+        &self,
+        signed_transaction: methods::broadcast_tx_commit::SignedTransaction,
+        max_wait: Duration,
+    ) -> MethodCallResult<
+        methods::broadcast_tx_commit::RpcBroadcastTxCommitResponse,
+        methods::broadcast_tx_commit::RpcTransactionError,
+    > {
+        let transaction_info = methods::tx::TransactionInfo::TransactionId {
+            hash: signed_transaction.get_hash(),
+            account_id: signed_transaction.transaction.signer_id.clone(),
+        };
+        match self.commit_transaction(signed_transaction).await {
+            Err(JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+                methods::tx::RpcTransactionError::TimeoutError,
+            ))) => self.wait_for_transaction(transaction_info, max_wait).await,
+            result => result,
+        }
+    }
+
+    /// Send a transaction, signed by `signer`, with the specified actions on `receiver_id`,
+    /// returning its outcome.
+    ///
+    /// The nonce and the block hash of the transaction come from viewing the access key of
+    /// the signer, as of the final block. The transaction is then
+    /// [committed](Self::commit_transaction), following the client's retry policy.
+    ///
+    /// If the node rejects the transaction for its nonce, like when the access key was used
+    /// concurrently, or for its block hash having expired, it's signed and sent again with a
+    /// new nonce and block hash, up to 3 times in all, see
+    /// [`send_transaction_with_attempts`](Self::send_transaction_with_attempts).
+    ///
+    /// Any [`TransactionSigner`](transaction::TransactionSigner) can sign, like an
+    /// [`InMemorySigner`](near_crypto::InMemorySigner), or one with a key held in a KMS or on
+    /// a hardware wallet.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use near_jsonrpc_client::JsonRpcClient;
+    /// use near_primitives::transaction::{Action, TransferAction};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let signer: near_crypto::InMemorySigner = unimplemented!();
+    /// let client = JsonRpcClient::connect("https://rpc.testnet.near.org");
+    ///
+    /// let outcome = client
+    ///     .send_transaction(
+    ///         &signer,
+    ///         "miraclx.testnet".parse()?,
+    ///         vec![Action::Transfer(TransferAction {
+    ///             deposit: 10u128.pow(24), // 1 NEAR
+    ///         })],
+    ///     )
+    ///     .await?;
+    ///
+    /// println!("{:?}", outcome.status);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_transaction<S: transaction::TransactionSigner + ?Sized>(
+        &self,
+        signer: &S,
+        receiver_id: near_primitives::types::AccountId,
+        actions: Vec<near_primitives::transaction::Action>,
+    ) -> Result<methods::broadcast_tx_commit::RpcBroadcastTxCommitResponse, SendTransactionError>
+    {
+        self.send_transaction_with_attempts(signer, receiver_id, actions, SEND_TRANSACTION_ATTEMPTS)
+            .await
+            .map(|sent| sent.outcome)
+    }
+
+    /// Send a transaction like [`send_transaction`](Self::send_transaction), making up to
+    /// `max_attempts` attempts, returning its outcome along with the attempts it took.
+    ///
+    /// An attempt is only made again if the node rejected the transaction for its nonce or
+    /// for its block hash having expired. The access key of the signer is then viewed again,
+    /// and the transaction signed with a nonce higher than the one the node reported.
+    ///
+    /// A transaction resent while [committing](Self::commit_transaction) it is rejected for
+    /// its nonce, or for its block hash having expired, if an earlier copy of it went through
+    /// after all, so on either rejection, the transaction is looked up with `tx` first, and its
+    /// outcome returned if it's known. It's only signed again once the node doesn't know about
+    /// it, so none of the actions execute twice.
+    pub async fn send_transaction_with_attempts<S: transaction::TransactionSigner + ?Sized>(
+        &self,
+        signer: &S,
+        receiver_id: near_primitives::types::AccountId,
+        actions: Vec<near_primitives::transaction::Action>,
+        max_attempts: u32,
+    ) -> Result<transaction::SentTransaction, SendTransactionError> {
+        use near_primitives::errors::InvalidTxError;
+
+        let mut attempts = 0;
+        // the nonce the node reported for the access key, which may be ahead of the final block
+        let mut ak_nonce = None;
+        loop {
+            attempts += 1;
+            let signed_transaction = self
+                .sign_for_sending(signer, receiver_id.clone(), actions.clone(), ak_nonce)
+                .await?;
+            let hash = signed_transaction.get_hash();
+            let err = match self.commit_transaction(signed_transaction).await {
+                Ok(outcome) => return Ok(transaction::SentTransaction { outcome, attempts }),
+                Err(err) => err,
+            };
+            let rejection = match &err {
+                JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+                    methods::tx::RpcTransactionError::InvalidTransaction { context },
+                )) => context,
+                _ => return Err(SendTransactionError::Commit(err)),
+            };
+            let nonce = match rejection {
+                InvalidTxError::InvalidNonce {
+                    ak_nonce: nonce, ..
+                } => Some(*nonce),
+                InvalidTxError::Expired => None,
+                _ => return Err(SendTransactionError::Commit(err)),
+            };
+            let status = self
+                .call_with_options(
+                    methods::tx::RpcTransactionStatusRequest {
+                        transaction_info: methods::tx::TransactionInfo::TransactionId {
+                            hash,
+                            account_id: signer.account_id().clone(),
+                        },
+                    },
+                    CallOptions::new().no_archival_fallback(),
+                )
+                .await;
+            match status {
+                Ok(outcome) => return Ok(transaction::SentTransaction { outcome, attempts }),
+                Err(JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+                    methods::tx::RpcTransactionError::UnknownTransaction { .. },
+                ))) => {}
+                // the transaction may have gone through, it's not safe to sign again
+                Err(_) => return Err(SendTransactionError::Commit(err)),
+            }
+            ak_nonce = nonce.or(ak_nonce);
+            if attempts >= max_attempts {
+                return Err(SendTransactionError::Commit(err));
+            }
+        }
+    }
+
+    /// Sign a transaction to send, with the nonce and block hash from viewing the signer's
+    /// access key, keeping the nonce above `ak_nonce`, if any.
+    async fn sign_for_sending<S: transaction::TransactionSigner + ?Sized>(
+        &self,
+        signer: &S,
+        receiver_id: near_primitives::types::AccountId,
+        actions: Vec<near_primitives::transaction::Action>,
+        ak_nonce: Option<near_primitives::types::Nonce>,
+    ) -> Result<methods::broadcast_tx_commit::SignedTransaction, SendTransactionError> {
+        use near_jsonrpc_primitives::types::query::QueryResponseKind;
+
+        let response = self
+            .call(methods::query::RpcQueryRequest {
+                block_reference: near_primitives::types::Finality::Final.into(),
+                request: near_primitives::views::QueryRequest::ViewAccessKey {
+                    account_id: signer.account_id().clone(),
+                    public_key: signer.public_key().clone(),
+                },
+            })
+            .await
+            .map_err(SendTransactionError::AccessKey)?;
+        let access_key = match response.kind {
+            QueryResponseKind::AccessKey(access_key) => access_key,
+            kind => {
+                return Err(SendTransactionError::AccessKey(unexpected_query_response(
+                    kind,
+                )))
+            }
+        };
+
+        let transaction = actions
+            .into_iter()
+            .fold(
+                transaction::TransactionBuilder::new(signer.account_id().clone(), receiver_id),
+                transaction::TransactionBuilder::action,
+            )
+            .public_key(signer.public_key().clone())
+            .nonce(access_key.nonce.max(ak_nonce.unwrap_or(0)) + 1)
+            .block_hash(response.block_hash)
+            .build()
+            .map_err(SendTransactionError::Build)?;
+
+        transaction::sign(transaction, signer)
+            .await
+            .map_err(SendTransactionError::Sign)
+    }
+
+    /// Wait for an already submitted transaction to execute, returning its outcome.
+    ///
+    /// The transaction's status is polled with `tx` for up to `max_wait`, for as long as the
+    /// server times out waiting for it, doesn't know about it yet, or fails with a
+    /// [retryable](errors::JsonRpcError::is_retryable) error. The last error is returned if
+    /// the transaction hasn't executed within `max_wait`.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use near_jsonrpc_client::{methods, JsonRpcClient};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let signed_transaction: methods::broadcast_tx_async::SignedTransaction = unimplemented!();
+    /// let client = JsonRpcClient::connect("https://rpc.testnet.near.org");
+    ///
+    /// let transaction_info = methods::tx::TransactionInfo::TransactionId {
+    ///     hash: signed_transaction.get_hash(),
+    ///     account_id: signed_transaction.transaction.signer_id.clone(),
+    /// };
+    /// client
+    ///     .call(methods::broadcast_tx_async::RpcBroadcastTxAsyncRequest { signed_transaction })
+    ///     .await?;
+    ///
+    /// let outcome = client
+    ///     .wait_for_transaction(transaction_info, Duration::from_secs(60))
+    ///     .await?;
+    ///
+    /// println!("{:?}", outcome.status);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn wait_for_transaction(
+        &self,
+        transaction_info: methods::tx::TransactionInfo,
+        max_wait: Duration,
+    ) -> MethodCallResult<methods::tx::RpcTransactionStatusResponse, methods::tx::RpcTransactionError>
+    {
+        /// How long to wait for before polling again, unless the server already waited.
+        const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+        let request = methods::tx::RpcTransactionStatusRequest { transaction_info };
+        let deadline = retry::Instant::now().checked_add(max_wait);
+        loop {
+            // the server not knowing about the transaction yet isn't a sign of it being pruned
+            let options = match CallOptions::default()
+                .no_archival_fallback()
+                .within(deadline)
+            {
+                Some(options) => options,
+                None => {
+                    return Err(JsonRpcError::DeadlineExceeded(DeadlineExceededError {
+                        attempts: vec![],
+                    }))
+                }
+            };
+            let err = match self.call_with_options(&request, options).await {
+                Ok(outcome) => return Ok(outcome),
+                Err(err) => err,
+            };
+            let delay = match err {
+                JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+                    methods::tx::RpcTransactionError::TimeoutError,
+                )) => Duration::ZERO,
+                JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+                    methods::tx::RpcTransactionError::UnknownTransaction { .. },
+                )) => POLL_INTERVAL,
+                ref err if err.is_retryable() => POLL_INTERVAL,
+                err => return Err(err),
+            };
+            match deadline {
+                Some(deadline) if retry::Instant::now() + delay >= deadline => return Err(err),
+                _ => retry::sleep(delay).await,
+            }
+        }
+    }
+
+    /// Wait for the node to be synced, polling its `status` every `poll_interval`.
+    ///
+    /// The node is synced once it no longer reports that it's syncing, and its latest block is
+    /// at most `max_block_age` old. Until then, retryable errors are ignored, like those of a
+    /// node still starting up. If the node isn't synced after `timeout`, this fails with the
+    /// last error, or with the last status it reported.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use near_jsonrpc_client::JsonRpcClient;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = JsonRpcClient::connect("http://localhost:3030");
+    ///
+    /// let status = client
+    ///     .wait_for_sync(
+    ///         Duration::from_secs(1),
+    ///         Duration::from_secs(300),
+    ///         Duration::from_secs(30),
+    ///     )
+    ///     .await?;
+    ///
+    /// println!("synced at height {}", status.sync_info.latest_block_height);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn wait_for_sync(
+        &self,
+        poll_interval: Duration,
+        timeout: Duration,
+        max_block_age: Duration,
+    ) -> Result<methods::status::RpcStatusResponse, WaitForSyncError> {
+        let deadline = retry::Instant::now().checked_add(timeout);
+        loop {
+            let result = match CallOptions::default().within(deadline) {
+                Some(options) => {
+                    self.call_with_options(methods::status::RpcStatusRequest, options)
+                        .await
+                }
+                None => Err(JsonRpcError::DeadlineExceeded(DeadlineExceededError {
+                    attempts: vec![],
+                })),
+            };
+            let timed_out = match deadline {
+                Some(deadline) => retry::Instant::now() + poll_interval >= deadline,
+                None => false,
+            };
+            match result {
+                Ok(status) => {
+                    let block_age = chrono::Utc::now()
+                        .signed_duration_since(status.sync_info.latest_block_time)
+                        .to_std()
+                        .unwrap_or(Duration::ZERO);
+                    if !status.sync_info.syncing && block_age <= max_block_age {
+                        return Ok(status);
+                    }
+                    if timed_out {
+                        return Err(WaitForSyncError::Timeout(Box::new(status)));
+                    }
+                }
+                Err(err) if err.is_retryable() && !timed_out => {}
+                Err(err) => return Err(WaitForSyncError::Call(err)),
+            }
+            retry::sleep(poll_interval).await;
+        }
+    }
+
+    /// View the code of a contract, checking that it hashes to the code hash of its account.
+    ///
+    /// The account and its code are both viewed as of the block `block_reference` resolves
+    /// to, so the check holds even as the contract is redeployed.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use near_jsonrpc_client::JsonRpcClient;
+    /// use near_primitives::types::Finality;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = JsonRpcClient::connect("https://rpc.testnet.near.org");
+    ///
+    /// let contract = client
+    ///     .view_code("guest-book.testnet".parse()?, Finality::Final)
+    ///     .await?;
+    ///
+    /// std::fs::write("guest_book.wasm", &contract.code)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn view_code(
+        &self,
+        account_id: near_primitives::types::AccountId,
+        block_reference: impl Into<near_primitives::types::BlockReference>,
+    ) -> Result<near_primitives::views::ContractCodeView, ViewCodeError> {
+        use near_jsonrpc_primitives::types::query::QueryResponseKind;
+        use near_primitives::views::QueryRequest;
+
+        let query = |block_reference, request| async move {
+            let response = self
+                .call(methods::query::RpcQueryRequest {
+                    block_reference,
+                    request,
+                })
+                .await
+                .map_err(ViewCodeError::Call)?;
+            Ok((response.kind, response.block_hash))
+        };
+        let unexpected = |kind| ViewCodeError::Call(unexpected_query_response(kind));
+
+        let (account, block_hash) = match query(
+            block_reference.into(),
+            QueryRequest::ViewAccount {
+                account_id: account_id.clone(),
+            },
+        )
+        .await?
+        {
+            (QueryResponseKind::ViewAccount(account), block_hash) => (account, block_hash),
+            (kind, _) => return Err(unexpected(kind)),
+        };
+        let code = match query(
+            near_primitives::types::BlockId::Hash(block_hash).into(),
+            QueryRequest::ViewCode { account_id },
+        )
+        .await?
+        {
+            (QueryResponseKind::ViewCode(code), _) => code,
+            (kind, _) => return Err(unexpected(kind)),
+        };
+
+        let computed = near_primitives::hash::hash(&code.code);
+        if computed != account.code_hash {
+            return Err(ViewCodeError::Mismatch(CodeHashMismatchError {
+                expected: account.code_hash,
+                computed,
+            }));
+        }
+        Ok(code)
+    }
+
+    /// Fetch the gas price of each block in a range of heights, with up to `concurrency` calls
+    /// in flight at a time.
+    ///
+    /// The prices are returned in order of height. Heights without a block, like skipped ones,
+    /// are left out, and so are the heights of garbage collected blocks, unless the client has
+    /// an [archival fallback](Self::with_archival_fallback).
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use near_jsonrpc_client::JsonRpcClient;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = JsonRpcClient::connect("https://archival-rpc.mainnet.near.org");
+    ///
+    /// let prices = client.gas_price_history(83975000..83975100, 8).await?;
+    ///
+    /// for (height, gas_price) in prices {
+    ///     println!("{}: {}", height, gas_price);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn gas_price_history(
+        &self,
+        heights: std::ops::Range<near_primitives::types::BlockHeight>,
+        concurrency: usize,
+    ) -> MethodCallResult<
+        Vec<(
+            near_primitives::types::BlockHeight,
+            near_primitives::types::Balance,
+        )>,
+        methods::gas_price::RpcGasPriceError,
+    > {
+        use futures_util::stream::{self, StreamExt, TryStreamExt};
+
+        stream::iter(heights)
+            .map(|height| async move {
+                let request = methods::gas_price::RpcGasPriceRequest {
+                    block_id: Some(near_primitives::types::BlockId::Height(height)),
+                };
+                match self.call(request).await {
+                    Ok(view) => Ok(Some((height, view.gas_price))),
+                    Err(JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+                        methods::gas_price::RpcGasPriceError::UnknownBlock { .. },
+                    ))) => Ok(None),
+                    Err(err) => Err(err),
+                }
+            })
+            .buffered(concurrency.max(1))
+            .try_filter_map(|price| async move { Ok(price) })
+            .try_collect()
+            .await
+    }
+
+    /// Iterate over the records of the genesis state, requesting them `page_size` at a time.
+    ///
+    /// See [`EXPERIMENTAL_genesis_records`](methods::EXPERIMENTAL_genesis_records).
+    pub fn genesis_records(
+        &self,
+        page_size: usize,
+    ) -> methods::EXPERIMENTAL_genesis_records::GenesisRecords<'_> {
+        methods::EXPERIMENTAL_genesis_records::GenesisRecords::new(self, page_size)
+    }
+
+    /// Send a transaction with `broadcast_tx_async`, returning a handle to follow up on it.
+    ///
+    /// See [`broadcast_tx_async`](methods::broadcast_tx_async).
+    pub async fn broadcast_transaction(
+        &self,
+        signed_transaction: methods::broadcast_tx_async::SignedTransaction,
+    ) -> MethodCallResult<
+        methods::broadcast_tx_async::PendingTransaction<'_>,
+        methods::broadcast_tx_async::RpcBroadcastTxAsyncError,
+    > {
+        let pending =
+            methods::broadcast_tx_async::PendingTransaction::new(self, &signed_transaction);
+        self.call(methods::broadcast_tx_async::RpcBroadcastTxAsyncRequest { signed_transaction })
+            .await?;
+        Ok(pending)
     }
 
-    /// Get the server address the client connects to.
-    pub fn server_addr(&self) -> &str {
-        &self.inner.server_addr
+    /// Execute a call, borrowing the method, so it can be reused across endpoints.
+    pub(crate) async fn execute<M>(
+        &self,
+        method: &M,
+        options: CallOptions,
+    ) -> MethodCallResult<(M::Response, CallMeta), M::Error>
+    where
+        M: methods::RpcMethod,
+    {
+        let log = metrics::AttemptLog::default();
+        let result = self.execute_logged(method, options, &log).await;
+        log.report(self.metrics.as_deref(), method.method_name());
+        result
     }
 
-    /// RPC method executor for the client.
-    pub async fn call<M>(&self, method: M) -> MethodCallResult<M::Response, M::Error>
+    /// Execute a call, recording its attempts in the specified log.
+    pub(crate) async fn execute_logged<M>(
+        &self,
+        method: &M,
+        options: CallOptions,
+        log: &metrics::AttemptLog,
+    ) -> MethodCallResult<(M::Response, CallMeta), M::Error>
+    where
+        M: methods::RpcMethod,
+    {
+        let start = retry::Instant::now();
+        let call = async {
+            self.execute_in(method, options, None, log)
+                .await
+                .map(|(response, meta)| {
+                    let latency = start.elapsed();
+                    (response, CallMeta { latency, ..meta })
+                })
+        };
+        #[cfg(feature = "tracing")]
+        let call = traced(
+            tracing::debug_span!(
+                "rpc_call",
+                method = method.method_name(),
+                server_addr = %self.inner.server_addr,
+            ),
+            call,
+        );
+        let result = match self.metrics {
+            Some(ref sink) => metrics::metered(&**sink, method.method_name(), call).await,
+            None => call.await,
+        };
+        if let Some(ref policy) = self.slow_calls {
+            policy.check(method, &self.inner.server_addr, start.elapsed());
+        }
+        result
+    }
+
+    /// Execute a call, with the first attempt taking the specified slot, if any.
+    async fn execute_in<M>(
+        &self,
+        method: &M,
+        options: CallOptions,
+        slot: Option<async_lock::SemaphoreGuardArc>,
+        log: &metrics::AttemptLog,
+    ) -> MethodCallResult<(M::Response, CallMeta), M::Error>
+    where
+        M: methods::RpcMethod,
+    {
+        // boxed, so that nesting calls doesn't pile the responses of every layer on the stack
+        match (
+            Box::pin(self.dispatch_or_alias(method, options.clone(), slot, log)).await,
+            &self.archival,
+        ) {
+            (
+                Err(JsonRpcError::ServerError(JsonRpcServerError::HandlerError(err))),
+                Some(archival),
+            ) if !options.no_archival_fallback && methods::RpcHandlerError::is_pruned(&err) => {
+                Box::pin(archival.dispatch_or_alias(method, options, None, log)).await
+            }
+            (result, _) => result,
+        }
+    }
+
+    /// Execute a call against this client's server, calling the method by its alias if the
+    /// server doesn't know the method by its name.
+    async fn dispatch_or_alias<M>(
+        &self,
+        method: &M,
+        options: CallOptions,
+        slot: Option<async_lock::SemaphoreGuardArc>,
+        log: &metrics::AttemptLog,
+    ) -> MethodCallResult<(M::Response, CallMeta), M::Error>
+    where
+        M: methods::RpcMethod,
+    {
+        match Box::pin(self.dispatch(method, options.clone(), slot, log)).await {
+            Err(JsonRpcError::ServerError(JsonRpcServerError::RequestValidationError(
+                near_jsonrpc_primitives::errors::RpcRequestValidationErrorKind::MethodNotFound {
+                    ..
+                },
+            ))) if method.alias().is_some() => {
+                Box::pin(self.dispatch(&methods::Aliased(method), options, None, log)).await
+            }
+            result => result,
+        }
+    }
+
+    /// Execute a call against this client's server, with retries.
+    async fn dispatch<M>(
+        &self,
+        method: &M,
+        options: CallOptions,
+        mut slot: Option<async_lock::SemaphoreGuardArc>,
+        log: &metrics::AttemptLog,
+    ) -> MethodCallResult<(M::Response, CallMeta), M::Error>
     where
         M: methods::RpcMethod,
     {
-        let request_payload = methods::to_json(&method).map_err(|err| {
+        let mut request_payload = methods::to_json(method).map_err(|err| {
             JsonRpcError::TransportError(RpcTransportError::SendError(
                 JsonRpcTransportSendError::PayloadSerializeError(err),
             ))
         })?;
 
+        let id = match (options.request_id, &self.id_generator) {
+            (Some(id), _) => id,
+            (None, Some(generator)) => generator.generate(),
+            (None, None) => request_payload["id"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+        };
+        request_payload["id"] = id.clone().into();
+
         let request_payload = serde_json::to_vec(&request_payload).map_err(|err| {
             JsonRpcError::TransportError(RpcTransportError::SendError(
                 JsonRpcTransportSendError::PayloadSerializeError(err.into()),
             ))
         })?;
 
-        let request = self
-            .inner
-            .client
-            .post(&self.inner.server_addr)
-            .headers(self.headers.clone())
-            .body(request_payload);
+        let mut headers = self.headers.clone();
+        headers.extend(options.headers);
+        headers
+            .entry(http::header::CONTENT_TYPE)
+            .or_insert_with(|| http::HeaderValue::from_static("application/json"));
 
-        let response = request.send().await.map_err(|err| {
-            JsonRpcError::TransportError(RpcTransportError::SendError(
-                JsonRpcTransportSendError::PayloadSendError(err),
-            ))
-        })?;
-        match response.status() {
-            reqwest::StatusCode::OK => {}
+        let request = TransportRequest {
+            server_addr: self.inner.server_addr.clone(),
+            headers,
+            body: request_payload,
+            timeout: options.timeout,
+        };
+
+        let retry_policy = options.retry_policy.as_ref().or(self.retry_policy.as_ref());
+
+        let start = retry::Instant::now();
+        let deadline = options
+            .deadline
+            .or_else(|| retry_policy?.get_deadline())
+            .and_then(|deadline| start.checked_add(deadline));
+
+        let mut attempts = vec![];
+        let mut attempt = 1;
+        loop {
+            let mut request = request.clone();
+            if let Some(deadline) = deadline {
+                let remaining = deadline.saturating_duration_since(retry::Instant::now());
+                request.timeout = Some(request.timeout.map_or(remaining, |t| t.min(remaining)));
+            }
+
+            let index = log.next_index();
+            let attempt_start = retry::Instant::now();
+            let result = self.attempt::<M>(request, slot.take());
+            #[cfg(feature = "tracing")]
+            let span = tracing::debug_span!(
+                "rpc_attempt",
+                attempt = index,
+                %id,
+                server_addr = %self.inner.server_addr,
+                outcome = tracing::field::Empty,
+            );
+            #[cfg(feature = "tracing")]
+            let result = traced(span.clone(), result);
+            let result = result.await;
+            let error = result.as_ref().err().map(metrics::ErrorClass::of);
+            #[cfg(feature = "tracing")]
+            span.record(
+                "outcome",
+                error
+                    .as_ref()
+                    .map_or("success", metrics::ErrorClass::as_str),
+            );
+            log.record(metrics::Attempt {
+                index,
+                server_addr: self.inner.server_addr.clone(),
+                error,
+                latency: attempt_start.elapsed(),
+            });
+            let err = match result {
+                Err(err) if err.is_retryable() => err,
+                result => {
+                    return result.map(|(response, meta)| {
+                        (
+                            response,
+                            CallMeta {
+                                attempts: attempt,
+                                request_id: id,
+                                ..meta
+                            },
+                        )
+                    })
+                }
+            };
+
+            // rate limited requests weren't processed, so they are safe to retry either way
+            let retry_after = match err {
+                JsonRpcError::TransportError(RpcTransportError::RateLimited { retry_after }) => {
+                    Some(retry_after)
+                }
+                _ => None,
+            };
+            let backoff = retry_policy
+                .filter(|_| method.is_idempotent() || retry_after.is_some())
+                .filter(|policy| attempt < policy.get_max_attempts())
+                .and_then(|policy| {
+                    let backoff = policy.backoff(attempt);
+                    match retry_after.flatten() {
+                        // waiting for longer than the policy allows is left to the caller
+                        Some(delay) if delay > policy.get_max_delay() => None,
+                        Some(delay) => Some(delay.max(backoff)),
+                        None => Some(backoff),
+                    }
+                });
+
+            // give up early, instead of sleeping past the deadline
+            let now = retry::Instant::now();
+            let exceeded = deadline.map_or(false, |deadline| {
+                now.checked_add(backoff.unwrap_or_default())
+                    .map_or(true, |next| next >= deadline)
+            });
+
+            match backoff {
+                _ if exceeded => {
+                    attempts.push(CallAttempt {
+                        error: err,
+                        elapsed: now.saturating_duration_since(start),
+                    });
+                    return Err(JsonRpcError::DeadlineExceeded(DeadlineExceededError {
+                        attempts,
+                    }));
+                }
+                None => return Err(err),
+                Some(backoff) => {
+                    if deadline.is_some() {
+                        attempts.push(CallAttempt {
+                            error: err,
+                            elapsed: now.saturating_duration_since(start),
+                        });
+                    }
+                    retry::sleep(backoff).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Make a single attempt at a call, unless the endpoint's circuit is open.
+    async fn attempt<M>(
+        &self,
+        request: TransportRequest,
+        slot: Option<async_lock::SemaphoreGuardArc>,
+    ) -> MethodCallResult<(M::Response, CallMeta), M::Error>
+    where
+        M: methods::RpcMethod,
+    {
+        let permit = match self.circuit_breaker {
+            Some(ref breaker) => Some(
+                breaker
+                    .acquire(&self.inner.server_addr)
+                    .ok_or(JsonRpcError::TransportError(RpcTransportError::CircuitOpen))?,
+            ),
+            None => None,
+        };
+        let queued_at = retry::Instant::now();
+        let _slot = match (slot, &self.in_flight) {
+            (Some(slot), _) => Some(slot),
+            (None, Some(in_flight)) => Some(in_flight.acquire_arc().await),
+            (None, None) => None,
+        };
+        let queue_time = queued_at.elapsed();
+        if let Some(ref limiter) = self.rate_limiter {
+            limiter.acquire().await;
+        }
+
+        let result = self
+            .exchange::<M>(request)
+            .await
+            .map(|(response, meta)| (response, CallMeta { queue_time, ..meta }));
+        if let Some(permit) = permit {
+            permit.record(!matches!(result, Err(ref err) if err.is_endpoint_failure()));
+        }
+        result
+    }
+
+    /// Exchange a request for a response, and parse it.
+    async fn exchange<M>(
+        &self,
+        request: TransportRequest,
+    ) -> MethodCallResult<(M::Response, CallMeta), M::Error>
+    where
+        M: methods::RpcMethod,
+    {
+        #[cfg(feature = "auth")]
+        let response = match self.auth_provider {
+            Some(ref provider) => self.send_authorized(&**provider, request).await?,
+            None => self.send(request).await?,
+        };
+        #[cfg(not(feature = "auth"))]
+        let response = self.send(request).await?;
+
+        let meta = CallMeta {
+            url: response
+                .url
+                .clone()
+                .unwrap_or_else(|| self.inner.server_addr.clone()),
+            queue_time: Duration::ZERO,
+            status: response.status,
+            headers: response.headers.clone(),
+            server_addr: self.inner.server_addr.clone(),
+            attempts: 1,
+            latency: Duration::ZERO,
+            request_id: String::new(),
+        };
+
+        match response.status {
+            http::StatusCode::OK => {}
             non_ok_status => {
+                if let Some(err) =
+                    errors::rate_limit_error(non_ok_status, &response.headers, &response.body)
+                {
+                    return Err(JsonRpcError::TransportError(err));
+                }
                 return Err(JsonRpcError::ServerError(
                     JsonRpcServerError::ResponseStatusError(match non_ok_status {
-                        reqwest::StatusCode::UNAUTHORIZED => {
+                        http::StatusCode::UNAUTHORIZED => {
                             JsonRpcServerResponseStatusError::Unauthorized
                         }
-                        reqwest::StatusCode::TOO_MANY_REQUESTS => {
-                            JsonRpcServerResponseStatusError::TooManyRequests
-                        }
                         unexpected => {
                             JsonRpcServerResponseStatusError::Unexpected { status: unexpected }
                         }
@@ -245,19 +1556,24 @@ impl JsonRpcClient {
                 ));
             }
         }
-        let response_payload = response.bytes().await.map_err(|err| {
-            JsonRpcError::TransportError(RpcTransportError::RecvError(
-                JsonRpcTransportRecvError::PayloadRecvError(err),
-            ))
-        })?;
-        let response_message = from_slice(&response_payload).map_err(|err| {
+        let response_message = from_slice(&response.body).map_err(|err| {
             JsonRpcError::TransportError(RpcTransportError::RecvError(
                 JsonRpcTransportRecvError::PayloadParseError(err),
             ))
         })?;
 
-        if let Message::Response(response) = response_message {
-            return M::parse_handler_response(response.result?)
+        if let Message::Response(message) = response_message {
+            let result = match message.result {
+                Err(ref err) if errors::is_rate_limit_error(err) => {
+                    return Err(JsonRpcError::TransportError(
+                        RpcTransportError::RateLimited {
+                            retry_after: errors::retry_after(&response.headers),
+                        },
+                    ))
+                }
+                result => result?,
+            };
+            return M::parse_handler_response(result)
                 .map_err(|err| {
                     JsonRpcError::TransportError(RpcTransportError::RecvError(
                         JsonRpcTransportRecvError::ResponseParseError(
@@ -265,6 +1581,7 @@ impl JsonRpcClient {
                         ),
                     ))
                 })?
+                .map(|response| (response, meta))
                 .map_err(|err| JsonRpcError::ServerError(JsonRpcServerError::HandlerError(err)));
         }
         Err(JsonRpcError::TransportError(RpcTransportError::RecvError(
@@ -272,6 +1589,56 @@ impl JsonRpcClient {
         )))
     }
 
+    /// Hand the request over to the transport.
+    async fn send<E>(
+        &self,
+        request: TransportRequest,
+    ) -> Result<TransportResponse, JsonRpcError<E>> {
+        let response = layer::Next::new(&self.layers, &*self.inner.transport)
+            .run(request)
+            .await;
+        if let Err(TransportError::SendError(_)) = response {
+            let failures = self.inner.connect_failures.fetch_add(1, Ordering::Relaxed) + 1;
+            if failures >= CONNECT_FAILURES_BEFORE_REFRESH {
+                self.refresh_connections();
+            }
+        } else {
+            self.inner.connect_failures.store(0, Ordering::Relaxed);
+        }
+        response.map_err(|err| JsonRpcError::TransportError(transport_error(err)))
+    }
+
+    /// Send the request with credentials from the auth provider, retrying once with
+    /// fresh credentials if the server rejects them.
+    #[cfg(feature = "auth")]
+    async fn send_authorized<E>(
+        &self,
+        provider: &dyn auth::AuthProvider,
+        request: TransportRequest,
+    ) -> Result<TransportResponse, JsonRpcError<E>> {
+        let mut retried = false;
+        loop {
+            let authorization = provider.authorization().await.map_err(|err| {
+                JsonRpcError::TransportError(RpcTransportError::SendError(
+                    JsonRpcTransportSendError::AuthorizationError(err),
+                ))
+            })?;
+
+            let mut request = request.clone();
+            request
+                .headers
+                .insert(http::header::AUTHORIZATION, authorization.clone());
+
+            let response = self.send(request).await?;
+            if response.status == http::StatusCode::UNAUTHORIZED && !retried {
+                provider.invalidate(&authorization);
+                retried = true;
+                continue;
+            }
+            return Ok(response);
+        }
+    }
+
     /// Add a header to this request.
     ///
     /// Depending on the header specified, this method either returns back
@@ -305,13 +1672,186 @@ impl JsonRpcClient {
         D::apply(self, entry)
     }
 
+    /// Consult an [`AuthProvider`](auth::AuthProvider) for the `Authorization` header
+    /// before each request.
+    ///
+    /// Available with the `auth` feature flag.
+    #[cfg(feature = "auth")]
+    pub fn auth_provider<P: auth::AuthProvider>(mut self, provider: P) -> Self {
+        self.auth_provider = Some(Arc::new(provider));
+        self
+    }
+
+    /// Automatically retry calls failing with transient errors, according to the specified policy.
+    ///
+    /// See the [`retry`] module for more details.
+    pub fn retry_policy(mut self, policy: retry::RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Fail fast when the server keeps failing, according to the specified circuit breaker.
+    ///
+    /// See the [`circuit`] module for more details.
+    pub fn circuit_breaker(mut self, breaker: circuit::CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(breaker);
+        self
+    }
+
+    /// Limit the rate of requests sent to the server, according to the specified rate limiter.
+    ///
+    /// See the [`rate_limit`] module for more details.
+    pub fn rate_limiter(mut self, limiter: rate_limit::RateLimiter) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Bound the number of requests in flight at once, queueing the rest until a slot frees up.
+    ///
+    /// Values below 1 are treated as 1. The limit is shared with clones of the client, but not
+    /// with the [archival fallback](JsonRpcClient::with_archival_fallback). How long a call
+    /// waited for a slot is reported in [`CallMeta::queue_time`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use near_jsonrpc_client::JsonRpcClient;
+    ///
+    /// let client = JsonRpcClient::connect("https://rpc.testnet.near.org").max_in_flight(16);
+    /// ```
+    pub fn max_in_flight(mut self, limit: usize) -> Self {
+        self.in_flight = Some(Arc::new(async_lock::Semaphore::new(limit.max(1))));
+        self
+    }
+
+    /// Report the calls made by the client to the specified metrics sink.
+    ///
+    /// See the [`metrics`] module for more details.
+    pub fn metrics_sink<S: metrics::MetricsSink>(mut self, sink: S) -> Self {
+        self.metrics = Some(Arc::new(sink));
+        self
+    }
+
+    /// Report calls taking longer than the thresholds of the specified policy.
+    ///
+    /// See the [`metrics`] module for more details.
+    pub fn slow_calls(mut self, policy: metrics::SlowCallPolicy) -> Self {
+        self.slow_calls = Some(policy);
+        self
+    }
+
+    /// Generate the JSON RPC request ids of calls with the specified generator, instead of
+    /// using random UUIDs.
+    ///
+    /// See the [`request_id`] module for more details.
+    pub fn id_generator<G: request_id::IdGenerator>(mut self, generator: G) -> Self {
+        self.id_generator = Some(Arc::new(generator));
+        self
+    }
+
+    /// Add a layer of middleware around the transport, inside the layers already added.
+    ///
+    /// See the [`layer`] module for more details.
+    pub fn layer<L: layer::Layer>(mut self, layer: L) -> Self {
+        self.layers.push(Arc::new(layer));
+        self
+    }
+
+    /// Send calls failing because the server has garbage collected the requested data
+    /// to an archival server instead.
+    ///
+    /// This applies to errors reported as [pruned](methods::RpcHandlerError::is_pruned),
+    /// like unknown blocks, chunks or transactions, from methods such as `block`, `chunk`,
+    /// `query`, `tx` and `EXPERIMENTAL_changes`. Calls made with
+    /// [`no_archival_fallback`](CallOptions::no_archival_fallback) aren't, like the `tx` polls
+    /// looking for transactions the client just sent.
+    ///
+    /// The archival server is called through the same transport, with the headers and
+    /// policies the client has when this is called.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use near_jsonrpc_client::{JsonRpcClient, NEAR_MAINNET_ARCHIVAL_RPC_URL, NEAR_MAINNET_RPC_URL};
+    ///
+    /// let client = JsonRpcClient::connect(NEAR_MAINNET_RPC_URL)
+    ///     .with_archival_fallback(NEAR_MAINNET_ARCHIVAL_RPC_URL);
+    /// ```
+    pub fn with_archival_fallback<U: AsUrl>(mut self, server_addr: U) -> Self {
+        let archival = JsonRpcClient {
+            inner: Arc::new(JsonRpcInnerClient {
+                server_addr: server_addr.to_string(),
+                transport: self.inner.transport.clone(),
+                connect_failures: AtomicU32::new(0),
+            }),
+            in_flight: None,
+            archival: None,
+            ..self.clone()
+        };
+        self.archival = Some(Arc::new(archival));
+        self
+    }
+
+    /// Establish a connection to the server ahead of the first call, so that it doesn't
+    /// incur the latency of resolving the server's address, and of the TCP and TLS handshakes.
+    ///
+    /// The connection is kept in the transport's pool for the calls that follow, see
+    /// [`Transport::warm_up`]. To also check that the server is healthy, and get credentials
+    /// from the auth provider, if any, use
+    /// [`warm_up_with_health`](Self::warm_up_with_health).
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use near_jsonrpc_client::JsonRpcClient;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = JsonRpcClient::connect("https://rpc.testnet.near.org");
+    ///
+    /// client.warm_up().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn warm_up(&self) -> Result<(), RpcTransportError> {
+        self.inner
+            .transport
+            .warm_up(&self.inner.server_addr)
+            .await
+            .map_err(transport_error)
+    }
+
+    /// Establish a connection to the server like [`warm_up`](Self::warm_up), then make a
+    /// `health` call over it.
+    pub async fn warm_up_with_health(
+        &self,
+    ) -> MethodCallResult<(), methods::health::RpcStatusError> {
+        self.warm_up().await.map_err(JsonRpcError::TransportError)?;
+        self.call(methods::health::RpcHealthRequest).await?;
+        Ok(())
+    }
+
+    /// Drop the transport's pooled connections, so that the next requests connect anew,
+    /// resolving the server's address again.
+    ///
+    /// This is done automatically after sending a request fails 3 times in a row, and is
+    /// useful when the server has moved to another address but stale connections are still
+    /// being reused. Only the connections to this client's server are dropped, other clients
+    /// of the same connector keep theirs, unless they connect to the same server. Transports
+    /// that don't pool connections, or can't drop them, ignore this, see
+    /// [`Transport::refresh`].
+    pub fn refresh_connections(&self) {
+        self.inner.connect_failures.store(0, Ordering::Relaxed);
+        self.inner.transport.refresh(self.server_addr());
+    }
+
     /// Get a shared reference to the headers.
-    pub fn headers(&self) -> &reqwest::header::HeaderMap {
+    pub fn headers(&self) -> &http::HeaderMap {
         &self.headers
     }
 
     /// Get an exclusive reference to the headers.
-    pub fn headers_mut(&mut self) -> &mut reqwest::header::HeaderMap {
+    pub fn headers_mut(&mut self) -> &mut http::HeaderMap {
         &mut self.headers
     }
 
@@ -323,6 +1863,10 @@ impl JsonRpcClient {
     /// However, if for some reason you still need to manually create a new connector, you can do so.
     /// Just remember to properly **reuse** it as much as possible.
     ///
+    /// The connector is backed by the `reqwest` transport, or, without the
+    /// `reqwest-transport` feature flag, the `hyper` or the `isahc` transport,
+    /// in that order of preference.
+    ///
     /// ## Example
     ///
     /// ```
@@ -332,19 +1876,24 @@ impl JsonRpcClient {
     /// let mainnet_client = client_connector.connect("https://rpc.mainnet.near.org");
     /// let testnet_client = client_connector.connect("https://rpc.testnet.near.org");
     /// ```
+    #[cfg(any(
+        feature = "reqwest-transport",
+        feature = "hyper-transport",
+        feature = "isahc-transport"
+    ))]
     pub fn new_client() -> JsonRpcClientConnector {
-        let mut headers = reqwest::header::HeaderMap::with_capacity(2);
-        headers.insert(
-            reqwest::header::CONTENT_TYPE,
-            reqwest::header::HeaderValue::from_static("application/json"),
-        );
+        #[cfg(feature = "reqwest-transport")]
+        let transport = transport::ReqwestTransport::new();
+        #[cfg(all(not(feature = "reqwest-transport"), feature = "hyper-transport"))]
+        let transport = transport::HyperTransport::new();
+        #[cfg(all(
+            not(feature = "reqwest-transport"),
+            not(feature = "hyper-transport"),
+            feature = "isahc-transport"
+        ))]
+        let transport = transport::IsahcTransport::new();
 
-        JsonRpcClientConnector {
-            client: reqwest::Client::builder()
-                .default_headers(headers)
-                .build()
-                .unwrap(),
-        }
+        JsonRpcClient::with_transport(transport)
     }
 
     /// Create a new client constructor using a custom web client.
@@ -366,8 +1915,44 @@ impl JsonRpcClient {
     /// # Ok(())
     /// # }
     /// ```
+    #[cfg(feature = "reqwest-transport")]
     pub fn with(client: reqwest::Client) -> JsonRpcClientConnector {
-        JsonRpcClientConnector { client }
+        JsonRpcClient::with_transport(transport::ReqwestTransport::with_client(client))
+    }
+
+    /// Create a new client constructor using a middleware-wrapped `reqwest::Client`.
+    ///
+    /// This lets applications that already maintain a shared, instrumented HTTP client
+    /// reuse it, instead of having two connection pools.
+    ///
+    /// Available with the `middleware` feature flag.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use near_jsonrpc_client::JsonRpcClient;
+    ///
+    /// let web_client = reqwest_middleware::ClientBuilder::new(reqwest::Client::new()).build();
+    ///
+    /// let testnet_client =
+    ///     JsonRpcClient::with_middleware(web_client).connect("https://rpc.testnet.near.org");
+    /// ```
+    #[cfg(feature = "middleware")]
+    pub fn with_middleware(
+        client: reqwest_middleware::ClientWithMiddleware,
+    ) -> JsonRpcClientConnector {
+        JsonRpcClient::with_transport(transport::ReqwestMiddlewareTransport::with_client(client))
+    }
+
+    /// Create a new client constructor using a custom [`Transport`].
+    ///
+    /// This is useful if you want to bring your own HTTP stack, or a test double.
+    ///
+    /// See the [`transport`] module for more details.
+    pub fn with_transport<T: Transport>(transport: T) -> JsonRpcClientConnector {
+        JsonRpcClientConnector {
+            transport: Arc::new(transport),
+        }
     }
 }
 
@@ -376,8 +1961,22 @@ impl fmt::Debug for JsonRpcClient {
         let mut builder = f.debug_struct("JsonRpcClient");
         builder.field("server_addr", &self.inner.server_addr);
         builder.field("headers", &self.headers);
-        builder.field("client", &self.inner.client);
-        builder.finish()
+        builder.field("retry_policy", &self.retry_policy);
+        builder.field("circuit_breaker", &self.circuit_breaker);
+        builder.field("rate_limiter", &self.rate_limiter);
+        builder.field("in_flight", &self.in_flight);
+        builder.field("metrics", &self.metrics.is_some());
+        builder.field("layers", &self.layers.len());
+        builder.field("id_generator", &self.id_generator.is_some());
+        builder.field("slow_calls", &self.slow_calls);
+        builder.field(
+            "archival_fallback",
+            &self
+                .archival
+                .as_ref()
+                .map(|archival| archival.server_addr()),
+        );
+        builder.finish_non_exhaustive()
     }
 }
 
@@ -399,17 +1998,325 @@ impl private::Sealed for &str {}
 
 impl AsUrl for &str {}
 
+#[cfg(feature = "reqwest-transport")]
 impl private::Sealed for reqwest::Url {}
 
+#[cfg(feature = "reqwest-transport")]
 impl AsUrl for reqwest::Url {}
 
 #[cfg(test)]
 mod tests {
     use crate::{methods, JsonRpcClient};
 
-    const RPC_SERVER_ADDR: &'static str = "https://archival-rpc.mainnet.near.org";
+    #[cfg(feature = "reqwest-transport")]
+    const RPC_SERVER_ADDR: &str = "https://archival-rpc.mainnet.near.org";
+
+    #[tokio::test]
+    #[cfg(feature = "reqwest-transport")]
+    async fn call_timeout() -> Result<(), Box<dyn std::error::Error>> {
+        use std::time::Duration;
+
+        use crate::errors::{JsonRpcError, RpcTransportError};
+        use crate::CallOptions;
+
+        // accepts connections into its backlog, but never responds
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let client = JsonRpcClient::connect(format!("http://{}", listener.local_addr()?));
+
+        let status = client
+            .call_with_options(
+                methods::status::RpcStatusRequest,
+                CallOptions::new().timeout(Duration::from_millis(100)),
+            )
+            .await;
+
+        assert!(
+            matches!(
+                status,
+                Err(JsonRpcError::TransportError(RpcTransportError::TimedOut))
+            ),
+            "expected a timeout error, found [{:?}]",
+            status
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn archival_fallback() {
+        use std::sync::{Arc, Mutex};
+
+        use crate::errors::{JsonRpcError, JsonRpcServerError};
+        use crate::transport::testing::{json_rpc_handler_error, json_rpc_result, FnTransport};
+
+        let served_by = Arc::new(Mutex::new(vec![]));
+        // the regular node has garbage collected every block, the archival node has them all
+        let transport = FnTransport::new({
+            let served_by = served_by.clone();
+            move |request, payload| {
+                served_by.lock().unwrap().push(request.server_addr.clone());
+                Ok(if request.server_addr.contains("archival") {
+                    json_rpc_result(
+                        &payload["id"],
+                        serde_json::json!({
+                            "block_hash": near_primitives::hash::CryptoHash::default(),
+                            "changes": [],
+                        }),
+                    )
+                } else {
+                    json_rpc_handler_error(
+                        &payload["id"],
+                        serde_json::json!({
+                            "name": "UNKNOWN_BLOCK",
+                            "info": { "error_message": "DB Not Found Error" },
+                        }),
+                        "DB Not Found Error".into(),
+                    )
+                })
+            }
+        });
+        let request = methods::EXPERIMENTAL_changes_in_block::RpcStateChangesInBlockRequest {
+            block_reference: near_primitives::types::BlockId::Height(1).into(),
+        };
+
+        let client = JsonRpcClient::with_transport(transport).connect("http://localhost:3030");
+        let response = client.call(&request).await;
+        assert!(
+            matches!(
+                response,
+                Err(JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+                    methods::EXPERIMENTAL_changes_in_block::RpcStateChangesError::UnknownBlock { .. }
+                )))
+            ),
+            "expected an unknown block error, found [{:?}]",
+            response
+        );
+
+        let client = client.with_archival_fallback("http://archival.localhost:3030");
+        let response = client.call(&request).await;
+        assert!(
+            response.is_ok(),
+            "expected the archival node to serve the call, found [{:?}]",
+            response
+        );
+        assert_eq!(
+            *served_by.lock().unwrap(),
+            vec![
+                "http://localhost:3030",
+                "http://localhost:3030",
+                "http://archival.localhost:3030"
+            ]
+        );
+
+        served_by.lock().unwrap().clear();
+        let response = client
+            .call_with_options(&request, crate::CallOptions::new().no_archival_fallback())
+            .await;
+        assert!(response.is_err());
+        assert_eq!(*served_by.lock().unwrap(), ["http://localhost:3030"]);
+    }
+
+    #[tokio::test]
+    async fn commit_transaction() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        use near_primitives::transaction::{SignedTransaction, Transaction};
+        use near_primitives::views::{FinalExecutionOutcomeView, FinalExecutionStatus};
+
+        use crate::retry::RetryPolicy;
+        use crate::transport::testing::{
+            json_rpc_handler_error, json_rpc_result, transaction_outcome, FnTransport,
+        };
+        use crate::transport::{Transport, TransportError};
+
+        /// Times out on the first broadcast, whether or not the transaction went through,
+        /// then responds with `server_timeouts` server-side timeouts.
+        fn timing_out_transport(
+            outcome: FinalExecutionOutcomeView,
+            executed: bool,
+            broadcasts: Arc<AtomicUsize>,
+            server_timeouts: usize,
+        ) -> impl Transport {
+            let server_timeouts = AtomicUsize::new(server_timeouts);
+            FnTransport::new(move |_, payload| {
+                let server_timeout = server_timeouts
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                    .is_ok();
+                Ok(match payload["method"].as_str() {
+                    Some("broadcast_tx_commit" | "tx") if server_timeout => json_rpc_handler_error(
+                        &payload["id"],
+                        serde_json::to_value(methods::tx::RpcTransactionError::TimeoutError)
+                            .unwrap(),
+                        serde_json::Value::Null,
+                    ),
+                    Some("broadcast_tx_commit") => {
+                        if broadcasts.fetch_add(1, Ordering::SeqCst) == 0 {
+                            return Err(TransportError::TimedOut);
+                        }
+                        json_rpc_result(&payload["id"], serde_json::to_value(&outcome).unwrap())
+                    }
+                    Some("tx") if executed => {
+                        json_rpc_result(&payload["id"], serde_json::to_value(&outcome).unwrap())
+                    }
+                    Some("tx") => json_rpc_handler_error(
+                        &payload["id"],
+                        serde_json::json!({
+                            "name": "UNKNOWN_TRANSACTION",
+                            "info": {
+                                "requested_transaction_hash": near_primitives::hash::CryptoHash::default(),
+                            },
+                        }),
+                        serde_json::Value::Null,
+                    ),
+                    method => panic!("unexpected method [{:?}]", method),
+                })
+            })
+        }
+
+        let signed_transaction = SignedTransaction::new(
+            near_crypto::Signature::empty(near_crypto::KeyType::ED25519),
+            Transaction {
+                signer_id: "alice.near".parse().unwrap(),
+                public_key: near_crypto::PublicKey::empty(near_crypto::KeyType::ED25519),
+                nonce: 1,
+                receiver_id: "bob.near".parse().unwrap(),
+                block_hash: Default::default(),
+                actions: vec![],
+            },
+        );
+        let outcome = transaction_outcome(
+            &signed_transaction,
+            FinalExecutionStatus::SuccessValue(String::new()),
+        );
+
+        for executed in [true, false] {
+            let broadcasts = Arc::new(AtomicUsize::new(0));
+            let transport = timing_out_transport(outcome.clone(), executed, broadcasts.clone(), 0);
+            let client = JsonRpcClient::with_transport(transport)
+                .connect("http://localhost:3030")
+                .retry_policy(RetryPolicy::new().base_delay(Duration::from_millis(1)));
+
+            let response = client.commit_transaction(signed_transaction.clone()).await;
+
+            assert!(
+                matches!(response, Ok(ref outcome) if outcome.transaction.hash == signed_transaction.get_hash()),
+                "expected the outcome of the transaction, found [{:?}]",
+                response
+            );
+            // only broadcast again if the transaction didn't go through
+            assert_eq!(
+                broadcasts.load(Ordering::SeqCst),
+                if executed { 1 } else { 2 }
+            );
+        }
+
+        // the server times out on the commit, and on the first status poll
+        let broadcasts = Arc::new(AtomicUsize::new(1));
+        let transport = timing_out_transport(outcome, true, broadcasts.clone(), 2);
+        let client = JsonRpcClient::with_transport(transport).connect("http://localhost:3030");
+
+        let response = client
+            .commit_transaction_and_wait(signed_transaction.clone(), Duration::from_secs(5))
+            .await;
+
+        assert!(
+            matches!(response, Ok(ref outcome) if outcome.transaction.hash == signed_transaction.get_hash()),
+            "expected the outcome of the transaction, found [{:?}]",
+            response
+        );
+        assert_eq!(broadcasts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn max_in_flight() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        use crate::errors::{JsonRpcError, RpcTransportError};
+        use crate::transport::testing::json_rpc_result;
+        use crate::transport::{
+            BoxFuture, Transport, TransportError, TransportRequest, TransportResponse,
+        };
+
+        /// Responds after a delay, recording the highest number of concurrent requests.
+        #[derive(Clone, Default)]
+        struct SlowTransport {
+            in_flight: Arc<AtomicUsize>,
+            peak: Arc<AtomicUsize>,
+        }
+
+        impl Transport for SlowTransport {
+            fn send(
+                &self,
+                request: TransportRequest,
+            ) -> BoxFuture<'_, Result<TransportResponse, TransportError>> {
+                Box::pin(async move {
+                    let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    self.peak.fetch_max(in_flight, Ordering::SeqCst);
+                    futures_timer::Delay::new(Duration::from_millis(20)).await;
+                    self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+                    let payload: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+                    Ok(json_rpc_result(&payload["id"], serde_json::Value::Null))
+                })
+            }
+        }
+
+        let transport = SlowTransport::default();
+        let peak = transport.peak.clone();
+        let client = JsonRpcClient::with_transport(transport)
+            .connect("http://localhost:3030")
+            .max_in_flight(2);
+
+        let metas = futures_util::future::join_all(
+            (0..6).map(|_| client.call_with_meta(methods::health::RpcHealthRequest)),
+        )
+        .await;
+
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        let mut queue_times: Vec<_> = metas
+            .into_iter()
+            .map(|result| result.unwrap().1.queue_time)
+            .collect();
+        queue_times.sort();
+        // two calls go straight through, the last two wait for two rounds
+        assert!(queue_times[..2]
+            .iter()
+            .all(|time| *time < Duration::from_millis(20)));
+        assert!(queue_times[4..]
+            .iter()
+            .all(|time| *time >= Duration::from_millis(35)));
+
+        // with all the slots taken, try_call fails fast
+        let client = client.max_in_flight(1);
+        let (response, try_response) =
+            futures_util::future::join(client.call(methods::health::RpcHealthRequest), async {
+                futures_timer::Delay::new(Duration::from_millis(5)).await;
+                client.try_call(methods::health::RpcHealthRequest).await
+            })
+            .await;
+        assert!(response.is_ok());
+        assert!(
+            matches!(
+                try_response,
+                Err(JsonRpcError::TransportError(RpcTransportError::WouldBlock))
+            ),
+            "expected a would block error, found [{:?}]",
+            try_response
+        );
+        let response = client.try_call(methods::health::RpcHealthRequest).await;
+        assert!(
+            response.is_ok(),
+            "expected a free slot, found [{:?}]",
+            response
+        );
+    }
 
     #[tokio::test]
+    #[cfg(feature = "reqwest-transport")]
     async fn chk_status_testnet() {
         let client = JsonRpcClient::connect(RPC_SERVER_ADDR);
 
@@ -423,7 +2330,7 @@ mod tests {
     }
 
     #[tokio::test]
-    #[cfg(feature = "any")]
+    #[cfg(all(feature = "any", feature = "reqwest-transport"))]
     async fn any_typed_ok() -> Result<(), Box<dyn std::error::Error>> {
         let client = JsonRpcClient::connect(RPC_SERVER_ADDR);
 
@@ -452,7 +2359,7 @@ mod tests {
     }
 
     #[tokio::test]
-    #[cfg(feature = "any")]
+    #[cfg(all(feature = "any", feature = "reqwest-transport"))]
     async fn any_typed_err() -> Result<(), Box<dyn std::error::Error>> {
         let client = JsonRpcClient::connect(RPC_SERVER_ADDR);
 
@@ -484,7 +2391,7 @@ mod tests {
     }
 
     #[tokio::test]
-    #[cfg(feature = "any")]
+    #[cfg(all(feature = "any", feature = "reqwest-transport"))]
     async fn any_untyped_ok() {
         let client = JsonRpcClient::connect(RPC_SERVER_ADDR);
 
@@ -514,7 +2421,7 @@ mod tests {
     }
 
     #[tokio::test]
-    #[cfg(feature = "any")]
+    #[cfg(all(feature = "any", feature = "reqwest-transport"))]
     async fn any_untyped_err() {
         let client = JsonRpcClient::connect(RPC_SERVER_ADDR);
 
@@ -545,4 +2452,1104 @@ mod tests {
             tx_error
         );
     }
+
+    #[tokio::test]
+    async fn refresh_connections() {
+        use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        use crate::transport::testing::json_rpc_result;
+        use crate::transport::{
+            BoxFuture, Transport, TransportError, TransportRequest, TransportResponse,
+        };
+
+        /// Fails to connect while `failing` is set, counting refreshes.
+        #[derive(Clone, Default)]
+        struct UnreachableTransport {
+            failing: Arc<AtomicBool>,
+            refreshes: Arc<AtomicUsize>,
+        }
+
+        impl Transport for UnreachableTransport {
+            fn send(
+                &self,
+                request: TransportRequest,
+            ) -> BoxFuture<'_, Result<TransportResponse, TransportError>> {
+                Box::pin(async move {
+                    if self.failing.load(Ordering::SeqCst) {
+                        return Err(TransportError::SendError("connection refused".into()));
+                    }
+                    let payload: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+                    Ok(json_rpc_result(&payload["id"], serde_json::Value::Null))
+                })
+            }
+
+            fn refresh(&self, server_addr: &str) {
+                assert_eq!(server_addr, "http://localhost:3030");
+                self.refreshes.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let transport = UnreachableTransport::default();
+        let (failing, refreshes) = (transport.failing.clone(), transport.refreshes.clone());
+        let client = JsonRpcClient::with_transport(transport).connect("http://localhost:3030");
+
+        failing.store(true, Ordering::SeqCst);
+        for _ in 0..2 {
+            assert!(client
+                .call(methods::health::RpcHealthRequest)
+                .await
+                .is_err());
+        }
+        assert_eq!(refreshes.load(Ordering::SeqCst), 0);
+
+        // a success in between starts over
+        failing.store(false, Ordering::SeqCst);
+        assert!(client.call(methods::health::RpcHealthRequest).await.is_ok());
+        failing.store(true, Ordering::SeqCst);
+        for _ in 0..2 {
+            assert!(client
+                .call(methods::health::RpcHealthRequest)
+                .await
+                .is_err());
+        }
+        assert_eq!(refreshes.load(Ordering::SeqCst), 0);
+
+        assert!(client
+            .call(methods::health::RpcHealthRequest)
+            .await
+            .is_err());
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+
+        client.refresh_connections();
+        assert_eq!(refreshes.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn warm_up() {
+        use std::sync::{Arc, Mutex};
+
+        use crate::transport::testing::json_rpc_result;
+        use crate::transport::{
+            BoxFuture, Transport, TransportError, TransportRequest, TransportResponse,
+        };
+
+        /// Records connections and calls.
+        #[derive(Clone, Default)]
+        struct RecordingTransport(Arc<Mutex<Vec<String>>>);
+
+        impl Transport for RecordingTransport {
+            fn send(
+                &self,
+                request: TransportRequest,
+            ) -> BoxFuture<'_, Result<TransportResponse, TransportError>> {
+                Box::pin(async move {
+                    let payload: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+                    self.0
+                        .lock()
+                        .unwrap()
+                        .push(payload["method"].as_str().unwrap().to_string());
+                    Ok(json_rpc_result(&payload["id"], serde_json::Value::Null))
+                })
+            }
+
+            fn warm_up<'a>(
+                &'a self,
+                server_addr: &'a str,
+            ) -> BoxFuture<'a, Result<(), TransportError>> {
+                Box::pin(async move {
+                    self.0
+                        .lock()
+                        .unwrap()
+                        .push(format!("connect {}", server_addr));
+                    Ok(())
+                })
+            }
+        }
+
+        let transport = RecordingTransport::default();
+        let log = transport.0.clone();
+        let client = JsonRpcClient::with_transport(transport).connect("http://localhost:3030");
+
+        client.warm_up().await.unwrap();
+        assert_eq!(*log.lock().unwrap(), ["connect http://localhost:3030"]);
+
+        client.warm_up_with_health().await.unwrap();
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "connect http://localhost:3030",
+                "connect http://localhost:3030",
+                "health"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn call_meta() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::time::Duration;
+
+        use crate::transport::testing::{json_rpc_result, FnTransport};
+        use crate::transport::TransportError;
+
+        let requests = AtomicUsize::new(0);
+        // fails to connect on the first request, then responds with a billing header
+        let transport = FnTransport::new(move |_, payload| {
+            if requests.fetch_add(1, Ordering::SeqCst) == 0 {
+                return Err(TransportError::SendError("connection refused".into()));
+            }
+            let mut headers = http::HeaderMap::new();
+            headers.insert("x-credits-used", http::HeaderValue::from_static("1"));
+            Ok(json_rpc_result(&payload["id"], serde_json::Value::Null).with_headers(headers))
+        });
+
+        let client = JsonRpcClient::with_transport(transport)
+            .connect("http://localhost:3030")
+            .retry_policy(
+                crate::retry::RetryPolicy::new()
+                    .base_delay(Duration::from_millis(5))
+                    .jitter(false),
+            );
+
+        let (_, meta) = client
+            .call_with_meta(methods::health::RpcHealthRequest)
+            .await
+            .unwrap();
+        assert_eq!(meta.status, http::StatusCode::OK);
+        assert_eq!(meta.headers["x-credits-used"], "1");
+        assert_eq!(meta.server_addr, "http://localhost:3030");
+        assert_eq!(meta.attempts, 2);
+        assert!(
+            meta.latency >= Duration::from_millis(5),
+            "expected the latency to include the backoff, found [{:?}]",
+            meta.latency
+        );
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    async fn tracing_spans() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+
+        use crate::retry::RetryPolicy;
+        use crate::transport::testing::{json_rpc_result, FnTransport};
+        use crate::transport::TransportResponse;
+
+        /// Records the spans created, and the fields recorded on them, in order.
+        #[derive(Clone, Default)]
+        struct Recorder {
+            next_id: Arc<AtomicU64>,
+            spans: Arc<Mutex<Vec<(&'static tracing::Metadata<'static>, Fields)>>>,
+        }
+
+        #[derive(Debug, Default, PartialEq)]
+        struct Fields(Vec<(&'static str, String)>);
+
+        impl Visit for Fields {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                self.0.push((field.name(), format!("{:?}", value)));
+            }
+
+            fn record_str(&mut self, field: &Field, value: &str) {
+                self.0.push((field.name(), value.to_string()));
+            }
+        }
+
+        impl tracing::Subscriber for Recorder {
+            fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, span: &Attributes<'_>) -> Id {
+                let mut fields = Fields::default();
+                span.record(&mut fields);
+                self.spans.lock().unwrap().push((span.metadata(), fields));
+                Id::from_u64(self.next_id.fetch_add(1, Ordering::SeqCst) + 1)
+            }
+            fn record(&self, span: &Id, values: &Record<'_>) {
+                let mut spans = self.spans.lock().unwrap();
+                values.record(&mut spans[span.into_u64() as usize - 1].1);
+            }
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, _: &tracing::Event<'_>) {}
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let requests = AtomicU64::new(0);
+        // fails the first request with a `503 Service Unavailable`
+        let transport = FnTransport::new(move |_, payload| {
+            if requests.fetch_add(1, Ordering::SeqCst) == 0 {
+                return Ok(TransportResponse::new(
+                    http::StatusCode::SERVICE_UNAVAILABLE,
+                    vec![],
+                ));
+            }
+            Ok(json_rpc_result(&payload["id"], serde_json::Value::Null))
+        });
+
+        let recorder = Recorder::default();
+        let client = JsonRpcClient::with_transport(transport)
+            .connect("http://localhost:3030")
+            .retry_policy(RetryPolicy::new().base_delay(Duration::ZERO));
+
+        let _guard = tracing::subscriber::set_default(recorder.clone());
+        client
+            .call(methods::health::RpcHealthRequest)
+            .await
+            .unwrap();
+
+        let spans = recorder.spans.lock().unwrap();
+        let names: Vec<_> = spans.iter().map(|(metadata, _)| metadata.name()).collect();
+        assert_eq!(names, ["rpc_call", "rpc_attempt", "rpc_attempt"]);
+        assert_eq!(
+            spans[0].1,
+            Fields(vec![
+                ("method", "health".to_string()),
+                ("server_addr", "http://localhost:3030".to_string())
+            ])
+        );
+        let Fields(ref fields) = spans[2].1;
+        assert_eq!(fields[0], ("attempt", "2".to_string()));
+        assert_eq!(fields[1].0, "id");
+        // retries are sent with the same id
+        assert_eq!(spans[1].1 .0[1], fields[1]);
+        assert_eq!(
+            fields[2],
+            ("server_addr", "http://localhost:3030".to_string())
+        );
+        assert_eq!(spans[1].1 .0[3], ("outcome", "status".to_string()));
+        assert_eq!(fields[3], ("outcome", "success".to_string()));
+    }
+
+    #[tokio::test]
+    async fn view_code() {
+        use near_primitives::hash::{hash, CryptoHash};
+        use near_primitives::types::Finality;
+
+        use crate::errors::ViewCodeError;
+        use crate::transport::testing::{json_rpc_result, FnTransport};
+        use crate::transport::Transport;
+
+        /// Serves an account with the specified code hash, and `\0asm` as its code.
+        fn contract_transport(code_hash: CryptoHash) -> impl Transport {
+            FnTransport::new(move |_, payload| {
+                let result = match payload["params"]["request_type"].as_str() {
+                    Some("view_account") => serde_json::json!({
+                        "amount": "0",
+                        "locked": "0",
+                        "code_hash": code_hash,
+                        "storage_usage": 0,
+                    }),
+                    Some("view_code") => {
+                        // the code is viewed at the block the account was viewed at
+                        assert_eq!(
+                            payload["params"]["block_id"],
+                            "11111111111111111111111111111111"
+                        );
+                        serde_json::json!({
+                            "code_base64": "AGFzbQ==",
+                            "hash": hash(b"\0asm"),
+                        })
+                    }
+                    other => panic!("unexpected query: {:?}", other),
+                };
+                let mut result = result.as_object().unwrap().clone();
+                result.insert("block_height".to_string(), 1.into());
+                result.insert(
+                    "block_hash".to_string(),
+                    serde_json::json!(CryptoHash::default()),
+                );
+                Ok(json_rpc_result(&payload["id"], result.into()))
+            })
+        }
+
+        let client = JsonRpcClient::with_transport(contract_transport(hash(b"\0asm")))
+            .connect("http://localhost:3030");
+        let contract = client
+            .view_code("guest-book.testnet".parse().unwrap(), Finality::Final)
+            .await
+            .unwrap();
+        assert_eq!(contract.code, b"\0asm");
+
+        let client = JsonRpcClient::with_transport(contract_transport(hash(b"another contract")))
+            .connect("http://localhost:3030");
+        match client
+            .view_code("guest-book.testnet".parse().unwrap(), Finality::Final)
+            .await
+        {
+            Err(ViewCodeError::Mismatch(mismatch)) => {
+                assert_eq!(mismatch.expected, hash(b"another contract"));
+                assert_eq!(mismatch.computed, hash(b"\0asm"));
+            }
+            result => panic!("expected a code hash mismatch, found [{:?}]", result),
+        }
+    }
+
+    #[tokio::test]
+    async fn gas_price_history() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        use crate::transport::testing::{json_rpc_handler_error, json_rpc_result};
+        use crate::transport::{
+            BoxFuture, Transport, TransportError, TransportRequest, TransportResponse,
+        };
+
+        /// Prices each block at a hundred times its height, but has no block at height 3.
+        #[derive(Clone, Default)]
+        struct GasPriceTransport {
+            in_flight: Arc<AtomicUsize>,
+            max_in_flight: Arc<AtomicUsize>,
+        }
+
+        impl Transport for GasPriceTransport {
+            fn send(
+                &self,
+                request: TransportRequest,
+            ) -> BoxFuture<'_, Result<TransportResponse, TransportError>> {
+                Box::pin(async move {
+                    let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+                    futures_timer::Delay::new(Duration::from_millis(10)).await;
+                    self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+                    let payload: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+                    let height = payload["params"][0].as_u64().unwrap();
+                    Ok(if height == 3 {
+                        json_rpc_handler_error(
+                            &payload["id"],
+                            serde_json::json!({
+                                "name": "UNKNOWN_BLOCK",
+                                "info": { "error_message": "DB Not Found Error" },
+                            }),
+                            "DB Not Found Error".into(),
+                        )
+                    } else {
+                        json_rpc_result(
+                            &payload["id"],
+                            serde_json::json!({ "gas_price": (height * 100).to_string() }),
+                        )
+                    })
+                })
+            }
+        }
+
+        let transport = GasPriceTransport::default();
+        let max_in_flight = transport.max_in_flight.clone();
+        let client = JsonRpcClient::with_transport(transport).connect("http://localhost:3030");
+
+        let prices = client.gas_price_history(1..7, 2).await.unwrap();
+        assert_eq!(prices, [(1, 100), (2, 200), (4, 400), (5, 500), (6, 600)]);
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn genesis_records() {
+        use std::sync::{Arc, Mutex};
+
+        use crate::methods::EXPERIMENTAL_genesis_records::{Pagination, StateRecord};
+        use crate::transport::testing::{json_rpc_result, FnTransport};
+
+        let pages = Arc::new(Mutex::new(vec![]));
+        // serves five contract records, recording the pages requested
+        let transport = FnTransport::new({
+            let pages = pages.clone();
+            move |_, payload| {
+                let pagination = &payload["params"]["pagination"];
+                let (offset, limit) = (
+                    pagination["offset"].as_u64().unwrap() as usize,
+                    pagination["limit"].as_u64().unwrap() as usize,
+                );
+                pages.lock().unwrap().push(Pagination { offset, limit });
+                let records: Vec<_> = (offset..(offset + limit).min(5))
+                    .map(|i| {
+                        serde_json::json!({
+                            "Contract": { "account_id": format!("contract{}.near", i), "code": "" }
+                        })
+                    })
+                    .collect();
+                Ok(json_rpc_result(
+                    &payload["id"],
+                    serde_json::json!({ "pagination": pagination, "records": records }),
+                ))
+            }
+        });
+        let client = JsonRpcClient::with_transport(transport).connect("http://localhost:3030");
+
+        let mut records = client.genesis_records(2);
+        let mut account_ids = vec![];
+        while let Some(record) = records.next().await {
+            match record.unwrap() {
+                StateRecord::Contract { account_id, .. } => {
+                    account_ids.push(account_id.to_string())
+                }
+                record => panic!("unexpected record: {:?}", record),
+            }
+        }
+        assert_eq!(
+            account_ids,
+            [
+                "contract0.near",
+                "contract1.near",
+                "contract2.near",
+                "contract3.near",
+                "contract4.near"
+            ]
+        );
+        assert_eq!(
+            *pages.lock().unwrap(),
+            [
+                Pagination {
+                    offset: 0,
+                    limit: 2
+                },
+                Pagination {
+                    offset: 2,
+                    limit: 2
+                },
+                Pagination {
+                    offset: 4,
+                    limit: 2
+                }
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn broadcast_transaction() {
+        use std::sync::{Arc, Mutex};
+
+        use near_primitives::transaction::{SignedTransaction, Transaction};
+
+        use crate::errors::{JsonRpcError, JsonRpcServerError};
+        use crate::methods::send_tx::TxExecutionStatus;
+        use crate::transport::testing::{json_rpc_handler_error, json_rpc_result, FnTransport};
+
+        let calls = Arc::new(Mutex::new(vec![]));
+        // acknowledges broadcasts, and reports transactions as included, recording the calls
+        let transport = FnTransport::new({
+            let calls = calls.clone();
+            move |_, payload| {
+                let method = payload["method"].as_str().unwrap().to_string();
+                let response = match (method.as_str(), payload["params"].is_array()) {
+                    ("broadcast_tx_async", _) => json_rpc_result(
+                        &payload["id"],
+                        serde_json::json!(near_primitives::hash::CryptoHash::default()),
+                    ),
+                    ("tx", true) => json_rpc_handler_error(
+                        &payload["id"],
+                        serde_json::json!({
+                            "name": "UNKNOWN_TRANSACTION",
+                            "info": {
+                                "requested_transaction_hash": near_primitives::hash::CryptoHash::default(),
+                            },
+                        }),
+                        serde_json::Value::Null,
+                    ),
+                    ("tx", false) => json_rpc_result(
+                        &payload["id"],
+                        serde_json::json!({ "final_execution_status": "INCLUDED" }),
+                    ),
+                    (method, _) => panic!("unexpected method [{:?}]", method),
+                };
+                calls
+                    .lock()
+                    .unwrap()
+                    .push((method, payload["params"].clone()));
+                Ok(response)
+            }
+        });
+        let client = JsonRpcClient::with_transport(transport).connect("http://localhost:3030");
+
+        let signed_transaction = SignedTransaction::new(
+            near_crypto::Signature::empty(near_crypto::KeyType::ED25519),
+            Transaction {
+                signer_id: "alice.near".parse().unwrap(),
+                public_key: near_crypto::PublicKey::empty(near_crypto::KeyType::ED25519),
+                nonce: 1,
+                receiver_id: "bob.near".parse().unwrap(),
+                block_hash: Default::default(),
+                actions: vec![],
+            },
+        );
+        let hash = signed_transaction.get_hash();
+
+        let pending = client
+            .broadcast_transaction(signed_transaction)
+            .await
+            .unwrap();
+        assert_eq!(pending.hash(), hash);
+        assert_eq!(pending.signer_id().as_ref(), "alice.near");
+
+        match pending.status().await {
+            Err(JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+                methods::tx::RpcTransactionError::UnknownTransaction { .. },
+            ))) => {}
+            res => panic!("expected an UnknownTransaction error, found [{:?}]", res),
+        }
+
+        let response = pending
+            .wait_until(TxExecutionStatus::Included)
+            .await
+            .unwrap();
+        assert!(response.final_execution_outcome.is_none());
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[0].0, "broadcast_tx_async");
+        assert_eq!(
+            calls[1],
+            ("tx".to_string(), serde_json::json!([hash, "alice.near"]))
+        );
+        assert_eq!(
+            calls[2],
+            (
+                "tx".to_string(),
+                serde_json::json!({
+                    "tx_hash": hash,
+                    "sender_account_id": "alice.near",
+                    "wait_until": "INCLUDED",
+                })
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn wait_for_sync() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        use crate::errors::{JsonRpcError, WaitForSyncError};
+        use crate::transport::testing::{json_rpc_result, FnTransport};
+        use crate::transport::TransportError;
+
+        // goes through the steps, staying on the last one: `None` fails to connect, and
+        // `Some((syncing, block_age))` reports a status
+        let connect = |steps: Vec<Option<(bool, chrono::Duration)>>| {
+            let polls = Arc::new(AtomicUsize::new(0));
+            let transport = FnTransport::new({
+                let polls = polls.clone();
+                move |_, payload| {
+                    let poll = polls.fetch_add(1, Ordering::SeqCst);
+                    let (syncing, block_age) = match steps[poll.min(steps.len() - 1)] {
+                        Some(step) => step,
+                        None => return Err(TransportError::SendError("connection refused".into())),
+                    };
+                    Ok(json_rpc_result(
+                        &payload["id"],
+                        serde_json::json!({
+                            "version": { "version": "1.0.0", "build": "test" },
+                            "chain_id": "localnet",
+                            "protocol_version": 1,
+                            "latest_protocol_version": 1,
+                            "validators": [],
+                            "sync_info": {
+                                "latest_block_hash": "11111111111111111111111111111111",
+                                "latest_block_height": 100,
+                                "latest_state_root": "11111111111111111111111111111111",
+                                "latest_block_time": chrono::Utc::now() - block_age,
+                                "syncing": syncing,
+                                "earliest_block_hash": null,
+                                "earliest_block_height": null,
+                                "earliest_block_time": null,
+                            },
+                            "validator_account_id": null,
+                        }),
+                    ))
+                }
+            });
+            let client = JsonRpcClient::with_transport(transport).connect("http://localhost:3030");
+            (client, polls)
+        };
+
+        let (client, polls) = connect(vec![
+            None,
+            Some((true, chrono::Duration::zero())),
+            Some((false, chrono::Duration::minutes(10))),
+            Some((false, chrono::Duration::zero())),
+        ]);
+        let status = client
+            .wait_for_sync(
+                Duration::from_millis(1),
+                Duration::from_secs(10),
+                Duration::from_secs(60),
+            )
+            .await
+            .unwrap();
+        assert!(!status.sync_info.syncing);
+        assert_eq!(polls.load(Ordering::SeqCst), 4);
+
+        let (client, _) = connect(vec![Some((false, chrono::Duration::minutes(10)))]);
+        match client
+            .wait_for_sync(
+                Duration::from_millis(10),
+                Duration::from_millis(50),
+                Duration::from_secs(60),
+            )
+            .await
+        {
+            Err(WaitForSyncError::Timeout(status)) => assert!(!status.sync_info.syncing),
+            res => panic!("expected a timeout, found [{:?}]", res),
+        }
+
+        let (client, _) = connect(vec![None]);
+        match client
+            .wait_for_sync(
+                Duration::from_millis(10),
+                Duration::from_millis(50),
+                Duration::from_secs(60),
+            )
+            .await
+        {
+            Err(WaitForSyncError::Call(JsonRpcError::TransportError(_))) => {}
+            res => panic!("expected a transport error, found [{:?}]", res),
+        }
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "any")]
+    async fn any_raw() {
+        use crate::methods::Raw;
+        use crate::transport::testing::{json_rpc_handler_error, json_rpc_result, FnTransport};
+
+        // responds to `EXPERIMENTAL_foo` with its params, and fails any other method
+        let transport = FnTransport::new(|_, payload| {
+            Ok(match payload["method"].as_str() {
+                Some("EXPERIMENTAL_foo") => {
+                    json_rpc_result(&payload["id"], payload["params"].clone())
+                }
+                _ => json_rpc_handler_error(
+                    &payload["id"],
+                    serde_json::json!({ "name": "NO_FOO", "info": { "bar": 1 } }),
+                    serde_json::Value::Null,
+                ),
+            })
+        });
+
+        #[derive(Debug, serde::Deserialize)]
+        struct FooResponse {
+            foo: u64,
+        }
+
+        #[derive(Debug, serde::Deserialize)]
+        #[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+        enum FooError {
+            NoFoo { bar: u64 },
+        }
+
+        let client = JsonRpcClient::with_transport(transport).connect("http://localhost:3030");
+
+        let Raw(response) = client
+            .call(methods::any::<Raw<FooResponse>>(
+                "EXPERIMENTAL_foo",
+                serde_json::json!({ "foo": 42 }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.foo, 42);
+
+        let err = client
+            .call(methods::any::<Raw<FooResponse>>(
+                "EXPERIMENTAL_bar",
+                serde_json::json!(null),
+            ))
+            .await
+            .unwrap_err()
+            .handler_error()
+            .unwrap();
+        assert_eq!(err["name"], "NO_FOO");
+
+        let err = client
+            .call(methods::any::<Result<Raw<FooResponse>, Raw<FooError>>>(
+                "EXPERIMENTAL_bar",
+                serde_json::json!(null),
+            ))
+            .await
+            .unwrap_err()
+            .handler_error()
+            .unwrap();
+        assert!(matches!(err, Raw(FooError::NoFoo { bar: 1 })));
+    }
+
+    #[tokio::test]
+    async fn method_aliases() {
+        use std::sync::{Arc, Mutex};
+
+        use near_jsonrpc_primitives::errors::RpcRequestValidationErrorKind;
+        use near_primitives::types::Finality;
+
+        use crate::errors::{JsonRpcError, JsonRpcServerError};
+        use crate::transport::testing::{json_rpc_error, json_rpc_result, FnTransport};
+
+        let methods = Arc::new(Mutex::new(vec![]));
+        // only knows the stable `changes` method, recording the methods called
+        let transport = FnTransport::new({
+            let methods = methods.clone();
+            move |_, payload| {
+                let method = payload["method"].as_str().unwrap().to_string();
+                let response = match method.as_str() {
+                    "changes" => json_rpc_result(
+                        &payload["id"],
+                        serde_json::json!({
+                            "block_hash": "11111111111111111111111111111111",
+                            "changes": [],
+                        }),
+                    ),
+                    method => json_rpc_error(
+                        &payload["id"],
+                        serde_json::json!({
+                            "name": "REQUEST_VALIDATION_ERROR",
+                            "cause": {
+                                "name": "METHOD_NOT_FOUND",
+                                "info": { "method_name": method },
+                            },
+                            "code": -32601,
+                            "message": "Method not found",
+                            "data": method,
+                        }),
+                    ),
+                };
+                methods.lock().unwrap().push(method);
+                Ok(response)
+            }
+        });
+        let client = JsonRpcClient::with_transport(transport).connect("http://localhost:3030");
+
+        let response = client
+            .call(methods::EXPERIMENTAL_changes::account_changes(
+                Finality::Final,
+                ["guest-book.testnet".parse().unwrap()],
+            ))
+            .await
+            .unwrap();
+        assert!(response.changes.is_empty());
+        assert_eq!(
+            *methods.lock().unwrap(),
+            ["EXPERIMENTAL_changes", "changes"]
+        );
+
+        // the alias is unknown too
+        methods.lock().unwrap().clear();
+        match client
+            .call(
+                methods::EXPERIMENTAL_changes_in_block::RpcStateChangesInBlockRequest {
+                    block_reference: Finality::Final.into(),
+                },
+            )
+            .await
+        {
+            Err(JsonRpcError::ServerError(JsonRpcServerError::RequestValidationError(
+                RpcRequestValidationErrorKind::MethodNotFound { method_name },
+            ))) => assert_eq!(method_name, "block_effects"),
+            res => panic!("expected a method not found error, found [{:?}]", res),
+        }
+        assert_eq!(
+            *methods.lock().unwrap(),
+            ["EXPERIMENTAL_changes_in_block", "block_effects"]
+        );
+
+        // methods without an alias are only called once
+        methods.lock().unwrap().clear();
+        client
+            .call(methods::status::RpcStatusRequest)
+            .await
+            .unwrap_err();
+        assert_eq!(*methods.lock().unwrap(), ["status"]);
+    }
+
+    #[tokio::test]
+    async fn send_transaction() {
+        use near_primitives::transaction::{Action, TransferAction};
+        use near_primitives::views::FinalExecutionStatus;
+
+        use crate::errors::SendTransactionError;
+        use crate::transaction::TransactionBuilderError;
+        use crate::transport::testing::{
+            broadcast_transaction, json_rpc_result, transaction_outcome, FnTransport,
+        };
+        use crate::transport::BoxFuture;
+
+        // serves an access key with nonce 5, and commits transactions
+        let transport = FnTransport::new(|_, payload| {
+            let result = match payload["method"].as_str() {
+                Some("query") => {
+                    assert_eq!(payload["params"]["request_type"], "view_access_key");
+                    serde_json::json!({
+                        "nonce": 5,
+                        "permission": "FullAccess",
+                        "block_height": 100,
+                        "block_hash": "9FtHUFBQsZ2MG77K3x3MJ9wjX3UT8zE1TczCrhZEcG8U",
+                    })
+                }
+                Some("broadcast_tx_commit") => serde_json::to_value(transaction_outcome(
+                    &broadcast_transaction(&payload),
+                    FinalExecutionStatus::SuccessValue(String::new()),
+                ))
+                .unwrap(),
+                method => panic!("unexpected method [{:?}]", method),
+            };
+            Ok(json_rpc_result(&payload["id"], result))
+        });
+
+        let client = JsonRpcClient::with_transport(transport).connect("http://localhost:3030");
+        let signer = near_crypto::InMemorySigner::from_seed(
+            "alice.near".parse().unwrap(),
+            near_crypto::KeyType::ED25519,
+            "alice.near",
+        );
+
+        let outcome = client
+            .send_transaction(
+                &signer,
+                "bob.near".parse().unwrap(),
+                vec![Action::Transfer(TransferAction { deposit: 1 })],
+            )
+            .await
+            .unwrap();
+        assert_eq!(outcome.transaction.signer_id, signer.account_id);
+        assert_eq!(outcome.transaction.public_key, signer.public_key);
+        assert_eq!(outcome.transaction.nonce, 6);
+        assert_eq!(outcome.transaction.receiver_id.as_ref(), "bob.near");
+
+        match client
+            .send_transaction(&signer, "bob.near".parse().unwrap(), vec![])
+            .await
+        {
+            Err(SendTransactionError::Build(TransactionBuilderError::NoActions)) => {}
+            res => panic!("expected a missing actions error, found [{:?}]", res),
+        }
+
+        /// Fails to sign, like a hardware wallet on which signing was rejected.
+        struct RejectingSigner(near_crypto::InMemorySigner);
+
+        impl crate::transaction::TransactionSigner for RejectingSigner {
+            fn account_id(&self) -> &near_primitives::types::AccountId {
+                &self.0.account_id
+            }
+
+            fn public_key(&self) -> &near_crypto::PublicKey {
+                &self.0.public_key
+            }
+
+            fn sign<'a>(
+                &'a self,
+                _message: &'a [u8],
+            ) -> BoxFuture<'a, Result<near_crypto::Signature, crate::transport::BoxError>>
+            {
+                Box::pin(async { Err("rejected on the device".into()) })
+            }
+        }
+
+        let signer: Box<dyn crate::transaction::TransactionSigner> =
+            Box::new(RejectingSigner(signer));
+        match client
+            .send_transaction(
+                &*signer,
+                "bob.near".parse().unwrap(),
+                vec![Action::Transfer(TransferAction { deposit: 1 })],
+            )
+            .await
+        {
+            Err(SendTransactionError::Sign(err)) => {
+                assert_eq!(err.to_string(), "rejected on the device")
+            }
+            res => panic!("expected a signing error, found [{:?}]", res),
+        }
+    }
+
+    #[tokio::test]
+    async fn send_transaction_attempts() {
+        use std::sync::{Arc, Mutex};
+
+        use near_primitives::errors::InvalidTxError;
+        use near_primitives::transaction::{Action, TransferAction};
+        use near_primitives::views::FinalExecutionStatus;
+
+        use crate::errors::{JsonRpcError, JsonRpcServerError, SendTransactionError};
+        use crate::methods::tx::RpcTransactionError;
+        use crate::transport::testing::{
+            broadcast_transaction, json_rpc_handler_error, json_rpc_result, transaction_outcome,
+            FnTransport,
+        };
+        use crate::transport::Transport;
+
+        /// Serves an access key with nonce 5 as of the final block, while the key was used up
+        /// to nonce 8, rejecting the first transaction for its nonce, and the second for its
+        /// block hash having expired, recording the nonces sent. None of the rejected
+        /// transactions are known to the node.
+        fn rejecting_transport() -> (impl Transport, Arc<Mutex<Vec<u64>>>) {
+            let nonces = Arc::new(Mutex::new(vec![]));
+            let transport = FnTransport::new({
+                let nonces = nonces.clone();
+                move |_, payload| {
+                    Ok(match payload["method"].as_str() {
+                        Some("query") => json_rpc_result(
+                            &payload["id"],
+                            serde_json::json!({
+                                "nonce": 5,
+                                "permission": "FullAccess",
+                                "block_height": 100,
+                                "block_hash": "9FtHUFBQsZ2MG77K3x3MJ9wjX3UT8zE1TczCrhZEcG8U",
+                            }),
+                        ),
+                        Some("broadcast_tx_commit") => {
+                            let signed_transaction = broadcast_transaction(&payload);
+                            let mut nonces = nonces.lock().unwrap();
+                            nonces.push(signed_transaction.transaction.nonce);
+                            let rejection = match nonces.len() {
+                                1 => serde_json::json!({
+                                    "InvalidNonce": { "tx_nonce": 6, "ak_nonce": 8 },
+                                }),
+                                2 => serde_json::json!("Expired"),
+                                _ => {
+                                    return Ok(json_rpc_result(
+                                        &payload["id"],
+                                        serde_json::to_value(transaction_outcome(
+                                            &signed_transaction,
+                                            FinalExecutionStatus::SuccessValue(String::new()),
+                                        ))
+                                        .unwrap(),
+                                    ))
+                                }
+                            };
+                            json_rpc_handler_error(
+                                &payload["id"],
+                                serde_json::json!({ "name": "INVALID_TRANSACTION", "info": {} }),
+                                serde_json::json!({
+                                    "TxExecutionError": { "InvalidTxError": rejection },
+                                }),
+                            )
+                        }
+                        Some("tx") => json_rpc_handler_error(
+                            &payload["id"],
+                            serde_json::json!({
+                                "name": "UNKNOWN_TRANSACTION",
+                                "info": { "requested_transaction_hash": payload["params"][0] },
+                            }),
+                            serde_json::Value::Null,
+                        ),
+                        method => panic!("unexpected method [{:?}]", method),
+                    })
+                }
+            });
+            (transport, nonces)
+        }
+
+        let signer = near_crypto::InMemorySigner::from_seed(
+            "alice.near".parse().unwrap(),
+            near_crypto::KeyType::ED25519,
+            "alice.near",
+        );
+        let actions = || vec![Action::Transfer(TransferAction { deposit: 1 })];
+
+        let (transport, nonces) = rejecting_transport();
+        let client = JsonRpcClient::with_transport(transport).connect("http://localhost:3030");
+        let outcome = client
+            .send_transaction(&signer, "bob.near".parse().unwrap(), actions())
+            .await
+            .unwrap();
+        assert_eq!(outcome.transaction.nonce, 9);
+        assert_eq!(*nonces.lock().unwrap(), [6, 9, 9]);
+
+        let (transport, nonces) = rejecting_transport();
+        let client = JsonRpcClient::with_transport(transport).connect("http://localhost:3030");
+        match client
+            .send_transaction_with_attempts(&signer, "bob.near".parse().unwrap(), actions(), 2)
+            .await
+        {
+            Err(SendTransactionError::Commit(JsonRpcError::ServerError(
+                JsonRpcServerError::HandlerError(RpcTransactionError::InvalidTransaction {
+                    context: InvalidTxError::Expired,
+                }),
+            ))) => {}
+            res => panic!("expected an expired transaction error, found [{:?}]", res),
+        }
+        assert_eq!(*nonces.lock().unwrap(), [6, 9]);
+
+        let sent = client
+            .send_transaction_with_attempts(&signer, "bob.near".parse().unwrap(), actions(), 2)
+            .await
+            .unwrap();
+        assert_eq!(sent.attempts, 1);
+
+        // the commit times out, and the node doesn't know about the transaction yet, so it's
+        // resent, but by then the first copy went through, and the copy is rejected for its
+        // nonce, or for its block hash having expired
+        for rejection in [
+            serde_json::json!({ "InvalidNonce": { "tx_nonce": 6, "ak_nonce": 6 } }),
+            serde_json::json!("Expired"),
+        ] {
+            let broadcasts = Arc::new(Mutex::new(vec![]));
+            let transport = FnTransport::new({
+                let broadcasts = broadcasts.clone();
+                move |_, payload| {
+                    let mut broadcasts = broadcasts.lock().unwrap();
+                    Ok(match payload["method"].as_str() {
+                        Some("query") => json_rpc_result(
+                            &payload["id"],
+                            serde_json::json!({
+                                "nonce": 5,
+                                "permission": "FullAccess",
+                                "block_height": 100,
+                                "block_hash": "9FtHUFBQsZ2MG77K3x3MJ9wjX3UT8zE1TczCrhZEcG8U",
+                            }),
+                        ),
+                        Some("broadcast_tx_commit") => {
+                            broadcasts.push(broadcast_transaction(&payload));
+                            if broadcasts.len() == 1 {
+                                return Err(crate::transport::TransportError::TimedOut);
+                            }
+                            json_rpc_handler_error(
+                                &payload["id"],
+                                serde_json::json!({ "name": "INVALID_TRANSACTION", "info": {} }),
+                                serde_json::json!({
+                                    "TxExecutionError": { "InvalidTxError": rejection },
+                                }),
+                            )
+                        }
+                        Some("tx") if broadcasts.len() == 1 => json_rpc_handler_error(
+                            &payload["id"],
+                            serde_json::json!({
+                                "name": "UNKNOWN_TRANSACTION",
+                                "info": { "requested_transaction_hash": payload["params"][0] },
+                            }),
+                            serde_json::Value::Null,
+                        ),
+                        Some("tx") => json_rpc_result(
+                            &payload["id"],
+                            serde_json::to_value(transaction_outcome(
+                                &broadcasts[0],
+                                FinalExecutionStatus::SuccessValue(String::new()),
+                            ))
+                            .unwrap(),
+                        ),
+                        method => panic!("unexpected method [{:?}]", method),
+                    })
+                }
+            });
+            let client = JsonRpcClient::with_transport(transport)
+                .connect("http://localhost:3030")
+                .retry_policy(
+                    crate::retry::RetryPolicy::new().base_delay(std::time::Duration::ZERO),
+                );
+            let sent = client
+                .send_transaction_with_attempts(&signer, "bob.near".parse().unwrap(), actions(), 3)
+                .await
+                .unwrap();
+            assert_eq!(sent.attempts, 1);
+            assert_eq!(sent.outcome.transaction.nonce, 6);
+            // the transaction is never signed again
+            let broadcasts = broadcasts.lock().unwrap();
+            assert_eq!(broadcasts.len(), 2);
+            assert_eq!(broadcasts[0], broadcasts[1]);
+        }
+    }
 }
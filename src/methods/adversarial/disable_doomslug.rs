@@ -14,6 +14,10 @@ impl RpcMethod for RpcAdversarialDisableDoomslugRequest {
     fn params(&self) -> Result<serde_json::Value, io::Error> {
         Ok(json!(null))
     }
+
+    fn is_idempotent(&self) -> bool {
+        false
+    }
 }
 
 impl private::Sealed for RpcAdversarialDisableDoomslugRequest {}
@@ -17,6 +17,10 @@ impl RpcMethod for RpcAdversarialProduceBlocksRequest {
     fn params(&self) -> Result<serde_json::Value, io::Error> {
         Ok(json!([self.num_blocks, self.only_valid]))
     }
+
+    fn is_idempotent(&self) -> bool {
+        false
+    }
 }
 
 impl private::Sealed for RpcAdversarialProduceBlocksRequest {}
@@ -2,6 +2,48 @@ use super::*;
 
 use std::marker::PhantomData;
 
+/// A response or a handler error deserialized as is, for types that don't implement
+/// [`RpcHandlerResponse`] or [`RpcHandlerError`].
+///
+/// This is handy for methods newer than this crate, whose responses only need to be
+/// [`Deserialize`]. The error defaults to a [`serde_json::Value`], unless both are specified.
+///
+/// ```
+/// use near_jsonrpc_client::methods::{self, Raw};
+/// use serde::Deserialize;
+/// use serde_json::json;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct FooResponse {
+///     foo: u64,
+/// }
+///
+/// #[derive(Debug, Deserialize)]
+/// #[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+/// enum FooError {
+///     NoFoo,
+/// }
+///
+/// let request = methods::any::<Raw<FooResponse>>("EXPERIMENTAL_foo", json!({ "bar": 1 }));
+///
+/// // with a typed error
+/// let request =
+///     methods::any::<Result<Raw<FooResponse>, Raw<FooError>>>("EXPERIMENTAL_foo", json!(null));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct Raw<T>(pub T);
+
+impl<T> Raw<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: serde::de::DeserializeOwned> RpcHandlerResponse for Raw<T> {}
+
+impl<T: serde::de::DeserializeOwned> RpcHandlerError for Raw<T> {}
+
 pub fn request<T: AnyRequestResult>(
     method_name: &str,
     params: serde_json::Value,
@@ -42,6 +84,14 @@ where
     fn params(&self) -> Result<serde_json::Value, io::Error> {
         Ok(self.params.clone())
     }
+
+    fn is_idempotent(&self) -> bool {
+        !(matches!(
+            self.method.as_str(),
+            "broadcast_tx_async" | "broadcast_tx_commit"
+        ) || self.method.starts_with("sandbox_")
+            || self.method.starts_with("adv_"))
+    }
 }
 
 pub trait AnyRequestResult {
@@ -54,6 +104,11 @@ impl<T, E> AnyRequestResult for Result<T, E> {
     type Error = E;
 }
 
+impl<T> AnyRequestResult for Raw<T> {
+    type Response = Raw<T>;
+    type Error = serde_json::Value;
+}
+
 impl<T: RpcMethod> AnyRequestResult for T {
     type Response = T::Response;
     type Error = T::Error;
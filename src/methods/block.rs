@@ -3,6 +3,7 @@
 //! Blocks can be referenced using either;
 //! - a [block ID](https://docs.near.org/docs/api/rpc#using-block_id-param) (block height or block hash) for querying historical blocks
 //! - or a [finality specifier](https://docs.near.org/docs/api/rpc#using-finality-param) (“final” or “optimistic”) for latest blocks.
+//! - or a [`SyncCheckpoint`] (“genesis” or “earliest_available”) for the first blocks.
 //!
 //! ## Examples
 //!
@@ -62,19 +63,60 @@
 //!           block_reference: BlockReference::Finality(Finality::None)
 //!       };
 //!       ```
+//!
+//! - Query the first blocks.
+//!
+//!     - `SyncCheckpoint::Genesis`: Get the genesis block.
+//!
+//!       ```
+//!       # use near_jsonrpc_client::methods;
+//!       use near_jsonrpc_client::methods::block::SyncCheckpoint;
+//!       use near_primitives::types::BlockReference;
+//!
+//!       let request = methods::block::RpcBlockRequest {
+//!           block_reference: BlockReference::SyncCheckpoint(SyncCheckpoint::Genesis)
+//!       };
+//!       ```
+//!
+//!     - `SyncCheckpoint::EarliestAvailable`: Get the earliest block the node still has.
+//!
+//!       Non-archival nodes garbage collect old blocks, this is the first one they kept.
+//!
+//!       ```
+//!       # use near_jsonrpc_client::methods;
+//!       use near_jsonrpc_client::methods::block::SyncCheckpoint;
+//!       use near_primitives::types::BlockReference;
+//!
+//!       let request = methods::block::RpcBlockRequest {
+//!           block_reference: BlockReference::SyncCheckpoint(SyncCheckpoint::EarliestAvailable)
+//!       };
+//!       ```
 use super::*;
 
 pub use near_jsonrpc_primitives::types::blocks::RpcBlockError;
 pub use near_jsonrpc_primitives::types::blocks::RpcBlockRequest;
+pub use near_primitives::types::SyncCheckpoint;
 
 pub type RpcBlockResponse = near_primitives::views::BlockView;
 
 impl RpcHandlerResponse for RpcBlockResponse {}
 
 impl RpcHandlerError for RpcBlockError {
+    fn variant_name(&self) -> Option<String> {
+        common::variant_name(self)
+    }
+
     fn parse_raw_error(value: serde_json::Value) -> Option<Result<Self, serde_json::Error>> {
         common::parse_unknown_block!(value => Self)
     }
+
+    fn is_retryable(&self) -> bool {
+        matches!(self, Self::NotSyncedYet | Self::InternalError { .. })
+    }
+
+    fn is_pruned(&self) -> bool {
+        matches!(self, Self::UnknownBlock { .. })
+    }
 }
 
 impl RpcMethod for RpcBlockRequest {
@@ -91,3 +133,29 @@ impl RpcMethod for RpcBlockRequest {
 }
 
 impl private::Sealed for RpcBlockRequest {}
+
+#[cfg(test)]
+mod tests {
+    use near_primitives::types::BlockReference;
+
+    use super::*;
+
+    #[test]
+    fn sync_checkpoints() {
+        let params = |sync_checkpoint| {
+            RpcBlockRequest {
+                block_reference: BlockReference::SyncCheckpoint(sync_checkpoint),
+            }
+            .params()
+            .unwrap()
+        };
+        assert_eq!(
+            params(SyncCheckpoint::Genesis),
+            json!({ "sync_checkpoint": "genesis" })
+        );
+        assert_eq!(
+            params(SyncCheckpoint::EarliestAvailable),
+            json!({ "sync_checkpoint": "earliest_available" })
+        );
+    }
+}
@@ -1,7 +1,40 @@
+//! Sends a signed transaction, returning its hash without waiting for it to execute.
+//!
+//! To follow up on the transaction, [`JsonRpcClient::broadcast_transaction`] sends it the same
+//! way, but returns a [`PendingTransaction`], which keeps track of the transaction hash and of
+//! its signer to look up its status.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use near_jsonrpc_client::{methods, JsonRpcClient};
+//! use near_jsonrpc_client::methods::send_tx::TxExecutionStatus;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! # let signed_transaction: methods::broadcast_tx_async::SignedTransaction = unimplemented!();
+//! let client = JsonRpcClient::connect("https://rpc.testnet.near.org");
+//!
+//! let pending = client.broadcast_transaction(signed_transaction).await?;
+//! println!("sent transaction {}", pending.hash());
+//!
+//! let response = pending.wait_until(TxExecutionStatus::Included).await?;
+//! println!("{:?}", response.final_execution_status);
+//!
+//! let response = pending.wait_final().await?;
+//! println!("{:?}", response.final_execution_outcome);
+//! # Ok(())
+//! # }
+//! ```
 use super::*;
 
 pub use near_primitives::transaction::SignedTransaction;
 
+use super::send_tx::{RpcTransactionResponse, TxExecutionStatus};
+use super::tx::{RpcTransactionError, RpcTransactionStatusRequest, RpcTransactionStatusResponse};
+use crate::errors::JsonRpcError;
+use crate::{CallOptions, JsonRpcClient};
+
 pub type RpcBroadcastTxAsyncResponse = near_primitives::hash::CryptoHash;
 
 #[derive(Debug)]
@@ -40,6 +73,121 @@ impl RpcMethod for RpcBroadcastTxAsyncRequest {
             &self.signed_transaction
         )?]))
     }
+
+    fn is_idempotent(&self) -> bool {
+        false
+    }
+
+    fn transaction_hash(&self) -> Option<near_primitives::hash::CryptoHash> {
+        Some(self.signed_transaction.get_hash())
+    }
 }
 
 impl private::Sealed for RpcBroadcastTxAsyncRequest {}
+
+/// A transaction sent with [`JsonRpcClient::broadcast_transaction`], to follow up on.
+#[derive(Debug)]
+pub struct PendingTransaction<'a> {
+    client: &'a JsonRpcClient,
+    hash: near_primitives::hash::CryptoHash,
+    signer_id: near_primitives::types::AccountId,
+}
+
+impl<'a> PendingTransaction<'a> {
+    pub(crate) fn new(client: &'a JsonRpcClient, signed_transaction: &SignedTransaction) -> Self {
+        Self {
+            client,
+            hash: signed_transaction.get_hash(),
+            signer_id: signed_transaction.transaction.signer_id.clone(),
+        }
+    }
+
+    /// The hash of the transaction.
+    pub fn hash(&self) -> near_primitives::hash::CryptoHash {
+        self.hash
+    }
+
+    /// The account that signed the transaction.
+    pub fn signer_id(&self) -> &near_primitives::types::AccountId {
+        &self.signer_id
+    }
+
+    /// The status of the transaction, with `tx`.
+    ///
+    /// This fails with an [`UnknownTransaction`](RpcTransactionError::UnknownTransaction) error
+    /// until the transaction is included in a block, and waits for it to execute after.
+    pub async fn status(
+        &self,
+    ) -> Result<RpcTransactionStatusResponse, JsonRpcError<RpcTransactionError>> {
+        self.client
+            .call_with_options(
+                RpcTransactionStatusRequest {
+                    transaction_info: super::tx::TransactionInfo::TransactionId {
+                        hash: self.hash,
+                        account_id: self.signer_id.clone(),
+                    },
+                },
+                CallOptions::new().no_archival_fallback(),
+            )
+            .await
+    }
+
+    /// Wait for the transaction to reach the specified execution status.
+    ///
+    /// Only newer nodes can wait for a specific status, like for
+    /// [`send_tx`](crate::methods::send_tx).
+    pub async fn wait_until(
+        &self,
+        wait_until: TxExecutionStatus,
+    ) -> Result<RpcTransactionResponse, JsonRpcError<RpcTransactionError>> {
+        self.client
+            .call_with_options(
+                TxWaitRequest {
+                    hash: self.hash,
+                    signer_id: self.signer_id.clone(),
+                    wait_until,
+                },
+                CallOptions::new().no_archival_fallback(),
+            )
+            .await
+    }
+
+    /// Wait for the transaction, all its receipts, and the blocks they were executed in, to be
+    /// final.
+    pub async fn wait_final(
+        &self,
+    ) -> Result<RpcTransactionResponse, JsonRpcError<RpcTransactionError>> {
+        self.wait_until(TxExecutionStatus::Final).await
+    }
+}
+
+/// A `tx` call waiting for an execution status.
+#[derive(Debug)]
+struct TxWaitRequest {
+    hash: near_primitives::hash::CryptoHash,
+    signer_id: near_primitives::types::AccountId,
+    wait_until: TxExecutionStatus,
+}
+
+impl RpcMethod for TxWaitRequest {
+    type Response = RpcTransactionResponse;
+    type Error = RpcTransactionError;
+
+    fn method_name(&self) -> &str {
+        "tx"
+    }
+
+    fn params(&self) -> Result<serde_json::Value, io::Error> {
+        Ok(json!({
+            "tx_hash": self.hash,
+            "sender_account_id": self.signer_id,
+            "wait_until": self.wait_until,
+        }))
+    }
+
+    fn transaction_hash(&self) -> Option<near_primitives::hash::CryptoHash> {
+        Some(self.hash)
+    }
+}
+
+impl private::Sealed for TxWaitRequest {}
@@ -33,6 +33,14 @@ impl RpcMethod for RpcBroadcastTxCommitRequest {
             &self.signed_transaction
         )?]))
     }
+
+    fn is_idempotent(&self) -> bool {
+        false
+    }
+
+    fn transaction_hash(&self) -> Option<near_primitives::hash::CryptoHash> {
+        Some(self.signed_transaction.get_hash())
+    }
 }
 
 impl private::Sealed for RpcBroadcastTxCommitRequest {}
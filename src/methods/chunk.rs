@@ -1,6 +1,7 @@
 //! Returns details of a specific chunk.
 //!
-//! You can use the [`block`](crate::methods::block) RPC method to get a valid chunk hash.
+//! You can use the [`block`](crate::methods::block) RPC method to get a valid chunk hash, or
+//! build the request straight from the chunk headers of a block with [`from_chunk_header`].
 //!
 //! ## Examples
 //!
@@ -12,7 +13,6 @@
 //!
 //!       ```
 //!       use near_jsonrpc_client::{methods, JsonRpcClient};
-//!       use near_jsonrpc_primitives::types::chunks;
 //!       use near_primitives::types::BlockId;
 //!
 //!       # #[tokio::main]
@@ -20,7 +20,7 @@
 //!       let client = JsonRpcClient::connect("https://archival-rpc.mainnet.near.org");
 //!
 //!       let request = methods::chunk::RpcChunkRequest {
-//!           chunk_reference: chunks::ChunkReference::BlockShardId {
+//!           chunk_reference: methods::chunk::ChunkReference::BlockShardId {
 //!               block_id: BlockId::Hash("6atGq4TUTZerVHU9qWoYfzXNBg3K4C4cca15TE6KfuBr".parse()?),
 //!               shard_id: 0,
 //!           }
@@ -40,7 +40,6 @@
 //!
 //!       ```
 //!       use near_jsonrpc_client::{methods, JsonRpcClient};
-//!       use near_jsonrpc_primitives::types::chunks;
 //!       use near_primitives::types::BlockId;
 //!
 //!       # #[tokio::main]
@@ -48,7 +47,7 @@
 //!       let client = JsonRpcClient::connect("https://archival-rpc.mainnet.near.org");
 //!
 //!       let request = methods::chunk::RpcChunkRequest {
-//!           chunk_reference: chunks::ChunkReference::BlockShardId {
+//!           chunk_reference: methods::chunk::ChunkReference::BlockShardId {
 //!               block_id: BlockId::Height(61512623),
 //!               shard_id: 3,
 //!           }
@@ -69,14 +68,13 @@
 //!
 //!   ```
 //!   use near_jsonrpc_client::{methods, JsonRpcClient};
-//!   use near_jsonrpc_primitives::types::chunks;
 //!
 //!   # #[tokio::main]
 //!   # async fn main() -> Result<(), Box<dyn std::error::Error>> {
 //!   let client = JsonRpcClient::connect("https://archival-rpc.mainnet.near.org");
 //!
 //!   let request = methods::chunk::RpcChunkRequest{
-//!       chunk_reference: chunks::ChunkReference::ChunkHash {
+//!       chunk_reference: methods::chunk::ChunkReference::ChunkHash {
 //!           chunk_id: "6GTgCQ5genLEEiPspEvdZEJooBzgWRrUnur9eGSdeTTD".parse()?,
 //!       }
 //!   };
@@ -92,16 +90,53 @@
 //!   ```
 use super::*;
 
-pub use near_jsonrpc_primitives::types::chunks::{RpcChunkError, RpcChunkRequest};
+pub use near_jsonrpc_primitives::types::chunks::{ChunkReference, RpcChunkError, RpcChunkRequest};
+
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::{BlockId, ShardId};
+use near_primitives::views::ChunkHeaderView;
 
 pub type RpcChunkResponse = near_primitives::views::ChunkView;
 
+/// Request a chunk by its hash.
+pub fn by_chunk_hash(chunk_id: CryptoHash) -> RpcChunkRequest {
+    RpcChunkRequest {
+        chunk_reference: ChunkReference::ChunkHash { chunk_id },
+    }
+}
+
+/// Request the chunk of a shard in a block.
+pub fn by_block_shard_id(block_id: BlockId, shard_id: ShardId) -> RpcChunkRequest {
+    RpcChunkRequest {
+        chunk_reference: ChunkReference::BlockShardId { block_id, shard_id },
+    }
+}
+
+/// Request the chunk of a chunk header, like the ones in [`BlockView::chunks`].
+///
+/// [`BlockView::chunks`]: near_primitives::views::BlockView::chunks
+pub fn from_chunk_header(header: &ChunkHeaderView) -> RpcChunkRequest {
+    by_chunk_hash(header.chunk_hash)
+}
+
 impl RpcHandlerResponse for RpcChunkResponse {}
 
 impl RpcHandlerError for RpcChunkError {
+    fn variant_name(&self) -> Option<String> {
+        common::variant_name(self)
+    }
+
     fn parse_raw_error(value: serde_json::Value) -> Option<Result<Self, serde_json::Error>> {
         common::parse_unknown_block!(value => Self)
     }
+
+    fn is_retryable(&self) -> bool {
+        matches!(self, Self::InternalError { .. })
+    }
+
+    fn is_pruned(&self) -> bool {
+        matches!(self, Self::UnknownBlock { .. } | Self::UnknownChunk { .. })
+    }
 }
 
 impl RpcMethod for RpcChunkRequest {
@@ -118,3 +153,28 @@ impl RpcMethod for RpcChunkRequest {
 }
 
 impl private::Sealed for RpcChunkRequest {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunk_references() {
+        assert_eq!(
+            by_chunk_hash(CryptoHash::default()).params().unwrap(),
+            json!({ "chunk_id": "11111111111111111111111111111111" })
+        );
+        assert_eq!(
+            by_block_shard_id(BlockId::Height(61512623), 3)
+                .params()
+                .unwrap(),
+            json!({ "block_id": 61512623, "shard_id": 3 })
+        );
+        assert_eq!(
+            by_block_shard_id(BlockId::Hash(CryptoHash::default()), 0)
+                .params()
+                .unwrap(),
+            json!({ "block_id": "11111111111111111111111111111111", "shard_id": 0 })
+        );
+    }
+}
@@ -1,8 +1,145 @@
+//! Returns the state changes of a kind, for specific accounts, in a block.
+//!
+//! Requests can be built for each kind of state changes with the constructors of this module,
+//! like [`data_changes`], which takes the key prefix as raw bytes and takes care of encoding it.
+//!
+//! Newer nodes serve this method under its stable name, `changes`, which calls fall back to
+//! when the node doesn't know the experimental one.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use near_jsonrpc_client::{methods, JsonRpcClient};
+//! use near_primitives::types::Finality;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JsonRpcClient::connect("https://rpc.testnet.near.org");
+//!
+//! let request = methods::EXPERIMENTAL_changes::data_changes(
+//!     Finality::Final,
+//!     ["guest-book.testnet".parse()?],
+//!     b"STATE",
+//! );
+//!
+//! let response = client.call(request).await?;
+//!
+//! println!("{} changes in block {}", response.changes.len(), response.block_hash);
+//! # Ok(())
+//! # }
+//! ```
 use super::*;
 
 pub use near_jsonrpc_primitives::types::changes::{
     RpcStateChangesError, RpcStateChangesInBlockByTypeRequest, RpcStateChangesInBlockResponse,
 };
+pub use near_primitives::views::StateChangesRequestView;
+
+use near_crypto::PublicKey;
+use near_primitives::types::{AccountId, AccountWithPublicKey, BlockReference};
+
+fn request(
+    block_reference: impl Into<BlockReference>,
+    state_changes_request: StateChangesRequestView,
+) -> RpcStateChangesInBlockByTypeRequest {
+    RpcStateChangesInBlockByTypeRequest {
+        block_reference: block_reference.into(),
+        state_changes_request,
+    }
+}
+
+/// Request the changes to the specified accounts, like their balance or storage usage.
+pub fn account_changes<I>(
+    block_reference: impl Into<BlockReference>,
+    account_ids: I,
+) -> RpcStateChangesInBlockByTypeRequest
+where
+    I: IntoIterator<Item = AccountId>,
+{
+    request(
+        block_reference,
+        StateChangesRequestView::AccountChanges {
+            account_ids: account_ids.into_iter().collect(),
+        },
+    )
+}
+
+/// Request the changes to specific access keys, each of an account.
+pub fn single_access_key_changes<I>(
+    block_reference: impl Into<BlockReference>,
+    keys: I,
+) -> RpcStateChangesInBlockByTypeRequest
+where
+    I: IntoIterator<Item = (AccountId, PublicKey)>,
+{
+    request(
+        block_reference,
+        StateChangesRequestView::SingleAccessKeyChanges {
+            keys: keys
+                .into_iter()
+                .map(|(account_id, public_key)| AccountWithPublicKey {
+                    account_id,
+                    public_key,
+                })
+                .collect(),
+        },
+    )
+}
+
+/// Request the changes to all the access keys of the specified accounts.
+pub fn all_access_key_changes<I>(
+    block_reference: impl Into<BlockReference>,
+    account_ids: I,
+) -> RpcStateChangesInBlockByTypeRequest
+where
+    I: IntoIterator<Item = AccountId>,
+{
+    request(
+        block_reference,
+        StateChangesRequestView::AllAccessKeyChanges {
+            account_ids: account_ids.into_iter().collect(),
+        },
+    )
+}
+
+/// Request the changes to the contract code of the specified accounts.
+pub fn contract_code_changes<I>(
+    block_reference: impl Into<BlockReference>,
+    account_ids: I,
+) -> RpcStateChangesInBlockByTypeRequest
+where
+    I: IntoIterator<Item = AccountId>,
+{
+    request(
+        block_reference,
+        StateChangesRequestView::ContractCodeChanges {
+            account_ids: account_ids.into_iter().collect(),
+        },
+    )
+}
+
+/// Request the changes to the contract data of the specified accounts, under keys starting
+/// with the specified prefix.
+///
+/// The prefix is raw bytes, it is base64 encoded when the request is sent. An empty prefix
+/// matches every key.
+pub fn data_changes<I, P>(
+    block_reference: impl Into<BlockReference>,
+    account_ids: I,
+    key_prefix: P,
+) -> RpcStateChangesInBlockByTypeRequest
+where
+    I: IntoIterator<Item = AccountId>,
+    P: AsRef<[u8]>,
+{
+    request(
+        block_reference,
+        StateChangesRequestView::DataChanges {
+            account_ids: account_ids.into_iter().collect(),
+            key_prefix: key_prefix.as_ref().to_vec().into(),
+        },
+    )
+}
 
 impl RpcHandlerResponse for RpcStateChangesInBlockResponse {}
 
@@ -17,6 +154,62 @@ impl RpcMethod for RpcStateChangesInBlockByTypeRequest {
     fn params(&self) -> Result<serde_json::Value, io::Error> {
         Ok(json!(self))
     }
+
+    fn alias(&self) -> Option<&str> {
+        Some("changes")
+    }
 }
 
 impl private::Sealed for RpcStateChangesInBlockByTypeRequest {}
+
+#[cfg(test)]
+mod tests {
+    use near_primitives::types::{BlockId, Finality};
+
+    use super::*;
+
+    #[test]
+    fn constructors() {
+        let account_id: AccountId = "guest-book.testnet".parse().unwrap();
+
+        assert_eq!(
+            account_changes(BlockId::Height(42), [account_id.clone()])
+                .params()
+                .unwrap(),
+            json!({
+                "block_id": 42,
+                "changes_type": "account_changes",
+                "account_ids": ["guest-book.testnet"],
+            })
+        );
+
+        let public_key: PublicKey = "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            single_access_key_changes(Finality::Final, [(account_id.clone(), public_key)])
+                .params()
+                .unwrap(),
+            json!({
+                "finality": "final",
+                "changes_type": "single_access_key_changes",
+                "keys": [{
+                    "account_id": "guest-book.testnet",
+                    "public_key": "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp",
+                }],
+            })
+        );
+
+        assert_eq!(
+            data_changes(Finality::Final, [account_id], b"STATE")
+                .params()
+                .unwrap(),
+            json!({
+                "finality": "final",
+                "changes_type": "data_changes",
+                "account_ids": ["guest-book.testnet"],
+                "key_prefix_base64": "U1RBVEU=",
+            })
+        );
+    }
+}
@@ -1,8 +1,43 @@
+//! Returns the kinds of state changes in a block, by account.
+//!
+//! The [changes](RpcStateChangesInBlockByTypeResponse::changes) are typed [`StateChangeKindView`]s,
+//! telling which accounts had their account, access keys, data or contract code touched, which
+//! the [`EXPERIMENTAL_changes`](super::EXPERIMENTAL_changes) method can then fetch the changes of.
+//!
+//! Newer nodes serve this method under its stable name, `block_effects`, which calls fall back
+//! to when the node doesn't know the experimental one.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use near_jsonrpc_client::{methods, JsonRpcClient};
+//! use near_jsonrpc_client::methods::EXPERIMENTAL_changes_in_block::StateChangeKindView;
+//! use near_primitives::types::{BlockReference, Finality};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JsonRpcClient::connect("https://rpc.testnet.near.org");
+//!
+//! let request = methods::EXPERIMENTAL_changes_in_block::RpcStateChangesInBlockRequest {
+//!     block_reference: BlockReference::Finality(Finality::Final),
+//! };
+//!
+//! let response = client.call(request).await?;
+//!
+//! for change in response.changes {
+//!     if let StateChangeKindView::ContractCodeTouched { account_id } = change {
+//!         println!("{} deployed a contract", account_id);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
 use super::*;
 
 pub use near_jsonrpc_primitives::types::changes::{
     RpcStateChangesError, RpcStateChangesInBlockByTypeResponse, RpcStateChangesInBlockRequest,
 };
+pub use near_primitives::views::StateChangeKindView;
 
 impl RpcHandlerResponse for RpcStateChangesInBlockByTypeResponse {}
 
@@ -17,6 +52,34 @@ impl RpcMethod for RpcStateChangesInBlockRequest {
     fn params(&self) -> Result<serde_json::Value, io::Error> {
         Ok(json!(self))
     }
+
+    fn alias(&self) -> Option<&str> {
+        Some("block_effects")
+    }
 }
 
 impl private::Sealed for RpcStateChangesInBlockRequest {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_changes() {
+        let response = RpcStateChangesInBlockByTypeResponse::parse(json!({
+            "block_hash": "11111111111111111111111111111111",
+            "changes": [
+                { "type": "account_touched", "account_id": "alice.testnet" },
+                { "type": "data_touched", "account_id": "guest-book.testnet" },
+            ],
+        }))
+        .unwrap();
+        assert!(matches!(
+            response.changes[..],
+            [
+                StateChangeKindView::AccountTouched { ref account_id },
+                StateChangeKindView::DataTouched { .. },
+            ] if account_id.as_ref() == "alice.testnet"
+        ));
+    }
+}
@@ -0,0 +1,113 @@
+//! Returns the configuration of the node's client.
+//!
+//! The configuration varies from one nearcore release to the next, so only a few long-standing
+//! fields are typed, the others being kept as JSON in [`RpcClientConfigResponse::other`].
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use near_jsonrpc_client::{methods, JsonRpcClient};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JsonRpcClient::connect("https://rpc.testnet.near.org");
+//!
+//! let config = client
+//!     .call(methods::EXPERIMENTAL_client_config::RpcClientConfigRequest)
+//!     .await?;
+//!
+//! println!(
+//!     "{} node on {}, running {}, gc_num_epochs_to_keep: {:?}",
+//!     if config.archive { "archival" } else { "rpc" },
+//!     config.chain_id,
+//!     config.version.version,
+//!     config.other.get("gc_num_epochs_to_keep"),
+//! );
+//! # Ok(())
+//! # }
+//! ```
+use super::*;
+
+use near_primitives::types::{AccountId, ShardId};
+
+#[derive(Debug)]
+pub struct RpcClientConfigRequest;
+
+#[derive(Debug, Deserialize)]
+pub struct RpcClientConfigResponse {
+    /// The version of nearcore the node is running.
+    pub version: near_primitives::version::Version,
+    /// The id of the chain the node is on.
+    pub chain_id: String,
+    /// Whether the node is an archival node, keeping the full history.
+    pub archive: bool,
+    /// The shards the node tracks.
+    #[serde(default)]
+    pub tracked_shards: Vec<ShardId>,
+    /// The accounts the node tracks the shards of.
+    #[serde(default)]
+    pub tracked_accounts: Vec<AccountId>,
+    /// The rest of the configuration.
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Error, serde::Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcClientConfigError {
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl RpcHandlerResponse for RpcClientConfigResponse {}
+
+impl RpcHandlerError for RpcClientConfigError {
+    fn variant_name(&self) -> Option<String> {
+        common::variant_name(self)
+    }
+
+    fn is_retryable(&self) -> bool {
+        matches!(self, Self::InternalError { .. })
+    }
+}
+
+impl RpcMethod for RpcClientConfigRequest {
+    type Response = RpcClientConfigResponse;
+    type Error = RpcClientConfigError;
+
+    fn method_name(&self) -> &str {
+        "EXPERIMENTAL_client_config"
+    }
+
+    fn params(&self) -> Result<serde_json::Value, io::Error> {
+        Ok(json!({}))
+    }
+}
+
+impl private::Sealed for RpcClientConfigRequest {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn semi_typed() {
+        let config = RpcClientConfigResponse::parse(json!({
+            "version": { "version": "1.36.0", "build": "crates-0.17.0" },
+            "chain_id": "testnet",
+            "archive": true,
+            "tracked_shards": [0],
+            "gc_num_epochs_to_keep": 5,
+        }))
+        .unwrap();
+        assert_eq!(config.version.version, "1.36.0");
+        assert_eq!(config.chain_id, "testnet");
+        assert!(config.archive);
+        assert_eq!(config.tracked_shards, [0]);
+        assert!(config.tracked_accounts.is_empty());
+        assert_eq!(
+            config.other,
+            *json!({ "gc_num_epochs_to_keep": 5 }).as_object().unwrap()
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! Returns the congestion level of a shard, as of a specific chunk.
+//!
+//! The congestion level ranges from `0.0`, for a shard with no backlog, to `1.0`, for a shard
+//! whose backlog is full, so relayers can throttle the transactions they submit to it.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use near_jsonrpc_client::{methods, JsonRpcClient};
+//! use near_jsonrpc_client::methods::EXPERIMENTAL_congestion_level::ChunkReference;
+//! use near_primitives::types::{BlockId, BlockReference, Finality};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JsonRpcClient::connect("https://rpc.testnet.near.org");
+//!
+//! let block = client
+//!     .call(methods::block::RpcBlockRequest {
+//!         block_reference: BlockReference::Finality(Finality::Final),
+//!     })
+//!     .await?;
+//!
+//! let request = methods::EXPERIMENTAL_congestion_level::RpcCongestionLevelRequest {
+//!     chunk_reference: ChunkReference::BlockShardId {
+//!         block_id: BlockId::Height(block.header.height),
+//!         shard_id: 0,
+//!     },
+//! };
+//!
+//! let response = client.call(request).await?;
+//!
+//! if response.congestion_level > 0.5 {
+//!     println!("shard 0 is congested, slowing down");
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use super::*;
+
+pub use near_jsonrpc_primitives::types::chunks::ChunkReference;
+
+#[derive(Debug, serde::Serialize)]
+pub struct RpcCongestionLevelRequest {
+    #[serde(flatten)]
+    pub chunk_reference: ChunkReference,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RpcCongestionLevelResponse {
+    /// The congestion level of the shard, from `0.0` to `1.0`.
+    pub congestion_level: f64,
+}
+
+#[derive(Debug, Error, serde::Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcCongestionLevelError {
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+    #[error("Block either has never been observed on the node or has been garbage collected: {error_message}")]
+    UnknownBlock { error_message: String },
+    #[error("Shard id {shard_id} does not exist")]
+    InvalidShardId { shard_id: u64 },
+    #[error("Chunk with hash {chunk_hash:?} has never been observed on this node")]
+    UnknownChunk {
+        chunk_hash: near_primitives::sharding::ChunkHash,
+    },
+}
+
+impl RpcHandlerResponse for RpcCongestionLevelResponse {}
+
+impl RpcHandlerError for RpcCongestionLevelError {
+    fn variant_name(&self) -> Option<String> {
+        common::variant_name(self)
+    }
+
+    fn parse_raw_error(value: serde_json::Value) -> Option<Result<Self, serde_json::Error>> {
+        common::parse_unknown_block!(value => Self)
+    }
+
+    fn is_retryable(&self) -> bool {
+        matches!(self, Self::InternalError { .. })
+    }
+
+    fn is_pruned(&self) -> bool {
+        matches!(self, Self::UnknownBlock { .. } | Self::UnknownChunk { .. })
+    }
+}
+
+impl RpcMethod for RpcCongestionLevelRequest {
+    type Response = RpcCongestionLevelResponse;
+    type Error = RpcCongestionLevelError;
+
+    fn method_name(&self) -> &str {
+        "EXPERIMENTAL_congestion_level"
+    }
+
+    fn params(&self) -> Result<serde_json::Value, io::Error> {
+        Ok(json!(self))
+    }
+}
+
+impl private::Sealed for RpcCongestionLevelRequest {}
+
+#[cfg(test)]
+mod tests {
+    use near_primitives::types::BlockId;
+
+    use super::*;
+
+    #[test]
+    fn params() {
+        let request = RpcCongestionLevelRequest {
+            chunk_reference: ChunkReference::BlockShardId {
+                block_id: BlockId::Height(42),
+                shard_id: 3,
+            },
+        };
+        assert_eq!(
+            request.params().unwrap(),
+            json!({ "block_id": 42, "shard_id": 3 })
+        );
+
+        let response =
+            RpcCongestionLevelResponse::parse(json!({ "congestion_level": 0.25 })).unwrap();
+        assert_eq!(response.congestion_level, 0.25);
+    }
+}
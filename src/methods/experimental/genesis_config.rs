@@ -1,3 +1,25 @@
+//! Returns the genesis configuration of the chain.
+//!
+//! The genesis records aren't part of it, see
+//! [`EXPERIMENTAL_genesis_records`](crate::methods::EXPERIMENTAL_genesis_records) for those.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use near_jsonrpc_client::{methods, JsonRpcClient};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JsonRpcClient::connect("https://rpc.testnet.near.org");
+//!
+//! let genesis = client
+//!     .call(methods::EXPERIMENTAL_genesis_config::RpcGenesisConfigRequest)
+//!     .await?;
+//!
+//! println!("{} started at height {}", genesis.chain_id, genesis.genesis_height);
+//! # Ok(())
+//! # }
+//! ```
 use super::*;
 
 pub type RpcGenesisConfigResponse = near_chain_configs::GenesisConfig;
@@ -0,0 +1,140 @@
+//! Returns a page of the records of the genesis state.
+//!
+//! Not every node serves this method, those that don't respond with a method not found error.
+//!
+//! The genesis state of a chain can be large, so rather than requesting each page, the records
+//! can be iterated over with [`JsonRpcClient::genesis_records`](crate::JsonRpcClient::genesis_records),
+//! which requests the pages as they're needed.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use near_jsonrpc_client::JsonRpcClient;
+//! use near_jsonrpc_client::methods::EXPERIMENTAL_genesis_records::StateRecord;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JsonRpcClient::connect("http://localhost:3030");
+//!
+//! let mut records = client.genesis_records(1000);
+//! let mut accounts = 0;
+//! while let Some(record) = records.next().await {
+//!     if let StateRecord::Account { .. } = record? {
+//!         accounts += 1;
+//!     }
+//! }
+//! println!("{} accounts at genesis", accounts);
+//! # Ok(())
+//! # }
+//! ```
+use super::*;
+
+pub use near_primitives::state_record::StateRecord;
+
+use crate::errors::JsonRpcError;
+use crate::JsonRpcClient;
+
+/// The range of records of a page.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Serialize, Deserialize)]
+pub struct Pagination {
+    /// The number of records before the page.
+    pub offset: usize,
+    /// The maximum number of records in the page.
+    pub limit: usize,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct RpcGenesisRecordsRequest {
+    pub pagination: Pagination,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RpcGenesisRecordsResponse {
+    pub pagination: Pagination,
+    /// The records of the page, fewer than the limit for the last page.
+    pub records: Vec<StateRecord>,
+}
+
+#[derive(Debug, Error, serde::Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcGenesisRecordsError {
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl RpcHandlerResponse for RpcGenesisRecordsResponse {}
+
+impl RpcHandlerError for RpcGenesisRecordsError {
+    fn variant_name(&self) -> Option<String> {
+        common::variant_name(self)
+    }
+
+    fn is_retryable(&self) -> bool {
+        matches!(self, Self::InternalError { .. })
+    }
+}
+
+impl RpcMethod for RpcGenesisRecordsRequest {
+    type Response = RpcGenesisRecordsResponse;
+    type Error = RpcGenesisRecordsError;
+
+    fn method_name(&self) -> &str {
+        "EXPERIMENTAL_genesis_records"
+    }
+
+    fn params(&self) -> Result<serde_json::Value, io::Error> {
+        Ok(json!(self))
+    }
+}
+
+impl private::Sealed for RpcGenesisRecordsRequest {}
+
+/// The records of the genesis state, requested a page at a time, see
+/// [`JsonRpcClient::genesis_records`].
+#[derive(Debug)]
+pub struct GenesisRecords<'a> {
+    client: &'a JsonRpcClient,
+    pagination: Pagination,
+    page: std::vec::IntoIter<StateRecord>,
+    done: bool,
+}
+
+impl<'a> GenesisRecords<'a> {
+    pub(crate) fn new(client: &'a JsonRpcClient, page_size: usize) -> Self {
+        Self {
+            client,
+            pagination: Pagination {
+                offset: 0,
+                limit: page_size.max(1),
+            },
+            page: Vec::new().into_iter(),
+            done: false,
+        }
+    }
+
+    /// The next record, or `None` once all the records have been returned.
+    ///
+    /// A page that fails to be requested is requested again on the next call.
+    pub async fn next(
+        &mut self,
+    ) -> Option<Result<StateRecord, JsonRpcError<RpcGenesisRecordsError>>> {
+        loop {
+            if let Some(record) = self.page.next() {
+                return Some(Ok(record));
+            }
+            if self.done {
+                return None;
+            }
+            let request = RpcGenesisRecordsRequest {
+                pagination: self.pagination,
+            };
+            let records = match self.client.call(request).await {
+                Ok(response) => response.records,
+                Err(err) => return Some(Err(err)),
+            };
+            self.done = records.len() < self.pagination.limit;
+            self.pagination.offset += records.len();
+            self.page = records.into_iter();
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Returns the proof that a block is part of the chain, as of a light client head.
+//!
+//! This complements the [`light_client_proof`](crate::methods::light_client_proof) method,
+//! which proves the execution outcome of a transaction or a receipt: the
+//! [`block_proof`](RpcLightClientBlockProofResponse::block_proof) is the merkle path from the
+//! block to the block merkle root of the light client head.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use near_jsonrpc_client::{methods, JsonRpcClient};
+//! use near_primitives::types::{BlockReference, Finality};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JsonRpcClient::connect("https://rpc.testnet.near.org");
+//!
+//! let head = client
+//!     .call(methods::block::RpcBlockRequest {
+//!         block_reference: BlockReference::Finality(Finality::Final),
+//!     })
+//!     .await?;
+//!
+//! // prove the parent of the head
+//! let request = methods::EXPERIMENTAL_light_client_block_proof::RpcLightClientBlockProofRequest {
+//!     block_hash: head.header.prev_hash,
+//!     light_client_head: head.header.hash,
+//! };
+//!
+//! let response = client.call(request).await?;
+//!
+//! println!("{} nodes in the block proof", response.block_proof.len());
+//! # Ok(())
+//! # }
+//! ```
+use super::*;
+
+pub use near_jsonrpc_primitives::types::light_client::RpcLightClientProofError;
+
+use near_primitives::hash::CryptoHash;
+
+#[derive(Debug, serde::Serialize)]
+pub struct RpcLightClientBlockProofRequest {
+    /// The hash of the block to prove.
+    pub block_hash: CryptoHash,
+    /// The hash of the head of the light client, the block to prove has to be its ancestor.
+    pub light_client_head: CryptoHash,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RpcLightClientBlockProofResponse {
+    /// The header of the block.
+    pub block_header_lite: near_primitives::views::LightClientBlockLiteView,
+    /// The merkle path proving the block is part of the chain.
+    pub block_proof: near_primitives::merkle::MerklePath,
+}
+
+impl RpcHandlerResponse for RpcLightClientBlockProofResponse {}
+
+impl RpcMethod for RpcLightClientBlockProofRequest {
+    type Response = RpcLightClientBlockProofResponse;
+    type Error = RpcLightClientProofError;
+
+    fn method_name(&self) -> &str {
+        "EXPERIMENTAL_light_client_block_proof"
+    }
+
+    fn params(&self) -> Result<serde_json::Value, io::Error> {
+        Ok(json!(self))
+    }
+}
+
+impl private::Sealed for RpcLightClientBlockProofRequest {}
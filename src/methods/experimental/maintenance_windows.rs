@@ -0,0 +1,90 @@
+//! Returns the ranges of block heights in the current epoch during which a validator isn't
+//! producing blocks or chunks, and its node can be restarted safely.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use near_jsonrpc_client::{methods, JsonRpcClient};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JsonRpcClient::connect("https://rpc.testnet.near.org");
+//!
+//! let request = methods::EXPERIMENTAL_maintenance_windows::RpcMaintenanceWindowsRequest {
+//!     account_id: "validator.poolv1.near".parse()?,
+//! };
+//!
+//! for window in client.call(request).await? {
+//!     println!("free from block {} to block {}", window.start, window.end);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use super::*;
+
+use near_primitives::types::{AccountId, BlockHeight};
+
+/// The ranges of block heights, each excluding its end.
+pub type RpcMaintenanceWindowsResponse = Vec<std::ops::Range<BlockHeight>>;
+
+#[derive(Debug, serde::Serialize)]
+pub struct RpcMaintenanceWindowsRequest {
+    pub account_id: AccountId,
+}
+
+#[derive(Debug, Error, serde::Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcMaintenanceWindowsError {
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl RpcHandlerResponse for RpcMaintenanceWindowsResponse {}
+
+impl RpcHandlerError for RpcMaintenanceWindowsError {
+    fn variant_name(&self) -> Option<String> {
+        common::variant_name(self)
+    }
+
+    fn is_retryable(&self) -> bool {
+        matches!(self, Self::InternalError { .. })
+    }
+}
+
+impl RpcMethod for RpcMaintenanceWindowsRequest {
+    type Response = RpcMaintenanceWindowsResponse;
+    type Error = RpcMaintenanceWindowsError;
+
+    fn method_name(&self) -> &str {
+        "EXPERIMENTAL_maintenance_windows"
+    }
+
+    fn params(&self) -> Result<serde_json::Value, io::Error> {
+        Ok(json!(self))
+    }
+}
+
+impl private::Sealed for RpcMaintenanceWindowsRequest {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows() {
+        let request = RpcMaintenanceWindowsRequest {
+            account_id: "validator.near".parse().unwrap(),
+        };
+        assert_eq!(
+            request.params().unwrap(),
+            json!({ "account_id": "validator.near" })
+        );
+
+        let response = RpcMaintenanceWindowsResponse::parse(json!([
+            { "start": 100, "end": 120 },
+            { "start": 150, "end": 151 },
+        ]))
+        .unwrap();
+        assert_eq!(response, [100..120, 150..151]);
+    }
+}
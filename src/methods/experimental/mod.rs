@@ -6,18 +6,36 @@ pub use changes as EXPERIMENTAL_changes;
 pub mod changes_in_block;
 pub use changes_in_block as EXPERIMENTAL_changes_in_block;
 
+pub mod client_config;
+pub use client_config as EXPERIMENTAL_client_config;
+
+pub mod congestion_level;
+pub use congestion_level as EXPERIMENTAL_congestion_level;
+
 pub mod check_tx;
 pub use check_tx as EXPERIMENTAL_check_tx;
 
 pub mod genesis_config;
 pub use genesis_config as EXPERIMENTAL_genesis_config;
 
+pub mod genesis_records;
+pub use genesis_records as EXPERIMENTAL_genesis_records;
+
+pub mod light_client_block_proof;
+pub use light_client_block_proof as EXPERIMENTAL_light_client_block_proof;
+
+pub mod maintenance_windows;
+pub use maintenance_windows as EXPERIMENTAL_maintenance_windows;
+
 pub mod protocol_config;
 pub use protocol_config as EXPERIMENTAL_protocol_config;
 
 pub mod receipt;
 pub use receipt as EXPERIMENTAL_receipt;
 
+pub mod split_storage_info;
+pub use split_storage_info as EXPERIMENTAL_split_storage_info;
+
 pub mod tx_status;
 pub use tx_status as EXPERIMENTAL_tx_status;
 
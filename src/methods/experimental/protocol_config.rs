@@ -1,17 +1,138 @@
+//! Returns the protocol configuration as of a block.
+//!
+//! The configuration varies from one nearcore release to the next, so only the long-standing
+//! fields are typed, the others being kept as JSON in [`RpcProtocolConfigResponse::other`].
+//! The runtime configuration has its fee tables typed, for estimating the gas of transactions
+//! as of the block, and the [limits](RuntimeConfigView::limits) transactions have to stay
+//! within can be parsed out of it.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use near_jsonrpc_client::{methods, JsonRpcClient};
+//! use near_primitives::types::{BlockId, BlockReference};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JsonRpcClient::connect("https://archival-rpc.mainnet.near.org");
+//!
+//! let request = methods::EXPERIMENTAL_protocol_config::RpcProtocolConfigRequest {
+//!     block_reference: BlockReference::BlockId(BlockId::Height(83975000)),
+//! };
+//!
+//! let config = client.call(request).await?;
+//!
+//! let fees = &config.runtime_config.transaction_costs.action_creation_config;
+//! println!(
+//!     "a transfer cost {} gas to send and {} gas to execute, at protocol version {}",
+//!     fees.transfer_cost.send_not_sir,
+//!     fees.transfer_cost.execution,
+//!     config.protocol_version
+//! );
+//! # Ok(())
+//! # }
+//! ```
 use super::*;
 
 pub use near_jsonrpc_primitives::types::config::{
     RpcProtocolConfigError, RpcProtocolConfigRequest,
 };
 
-pub type RpcProtocolConfigResponse = near_chain_configs::ProtocolConfigView;
+pub use near_primitives::runtime::config::AccountCreationConfig;
+pub use near_primitives::runtime::fees::RuntimeFeesConfig;
+pub use near_primitives::types::SyncCheckpoint;
+
+use near_primitives::serialize::u128_dec_format;
+use near_primitives::types::{Balance, BlockHeight, BlockHeightDelta, Gas, NumBlocks};
+use near_primitives::version::ProtocolVersion;
+
+#[derive(Debug, Deserialize)]
+pub struct RpcProtocolConfigResponse {
+    /// The version of the protocol as of the block.
+    pub protocol_version: ProtocolVersion,
+    /// The id of the chain.
+    pub chain_id: String,
+    /// The height of the genesis block.
+    pub genesis_height: BlockHeight,
+    /// The number of blocks in an epoch.
+    pub epoch_length: BlockHeightDelta,
+    /// The maximum gas a chunk can use.
+    pub gas_limit: Gas,
+    #[serde(with = "u128_dec_format")]
+    pub min_gas_price: Balance,
+    #[serde(with = "u128_dec_format")]
+    pub max_gas_price: Balance,
+    /// The number of blocks a transaction stays valid for, after the block it refers to.
+    pub transaction_validity_period: NumBlocks,
+    pub runtime_config: RuntimeConfigView,
+    /// The rest of the configuration.
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
+}
+
+/// The configuration of the runtime, mostly its economics.
+#[derive(Debug, Deserialize)]
+pub struct RuntimeConfigView {
+    /// The balance an account needs to hold per byte of storage it uses.
+    #[serde(with = "u128_dec_format")]
+    pub storage_amount_per_byte: Balance,
+    /// The fees of receipts and of each kind of action.
+    pub transaction_costs: RuntimeFeesConfig,
+    /// The configuration of the wasm runtime, kept as JSON since it varies the most between
+    /// releases.
+    pub wasm_config: serde_json::Value,
+    pub account_creation_config: AccountCreationConfig,
+}
+
+impl RuntimeConfigView {
+    /// The limits transactions have to stay within, from the `limit_config` of the wasm
+    /// configuration.
+    ///
+    /// They rarely change, so they can be parsed once and reused to
+    /// [build](crate::transaction::TransactionBuilder::limits) transactions.
+    pub fn limits(&self) -> Result<LimitConfig, serde_json::Error> {
+        LimitConfig::deserialize(&self.wasm_config["limit_config"])
+    }
+}
+
+/// The limits of the runtime on transactions and their actions, see
+/// [`RuntimeConfigView::limits`].
+///
+/// Only the limits checked before executing transactions are typed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct LimitConfig {
+    /// The maximum gas the function calls of a transaction can attach, in total.
+    pub max_total_prepaid_gas: Gas,
+    /// The maximum number of actions in a transaction.
+    pub max_actions_per_receipt: u64,
+    /// The maximum length of a method name, in bytes.
+    pub max_length_method_name: u64,
+    /// The maximum length of the method names a function call access key allows, in bytes,
+    /// counting one more byte for each name.
+    pub max_number_bytes_method_names: u64,
+    /// The maximum length of the arguments of a function call, in bytes.
+    pub max_arguments_length: u64,
+    /// The maximum size of a contract, in bytes.
+    pub max_contract_size: u64,
+    /// The maximum size of a transaction, borsh serialized and without its signature, in
+    /// bytes.
+    pub max_transaction_size: u64,
+}
 
 impl RpcHandlerResponse for RpcProtocolConfigResponse {}
 
 impl RpcHandlerError for RpcProtocolConfigError {
+    fn variant_name(&self) -> Option<String> {
+        common::variant_name(self)
+    }
+
     fn parse_raw_error(value: serde_json::Value) -> Option<Result<Self, serde_json::Error>> {
         common::parse_unknown_block!(value => Self)
     }
+
+    fn is_retryable(&self) -> bool {
+        matches!(self, Self::InternalError { .. })
+    }
 }
 
 impl RpcMethod for RpcProtocolConfigRequest {
@@ -28,3 +149,115 @@ impl RpcMethod for RpcProtocolConfigRequest {
 }
 
 impl private::Sealed for RpcProtocolConfigRequest {}
+
+#[cfg(test)]
+mod tests {
+    use near_primitives::types::BlockReference;
+
+    use super::*;
+
+    #[test]
+    fn block_references() {
+        let params = |block_reference| {
+            RpcProtocolConfigRequest { block_reference }
+                .params()
+                .unwrap()
+        };
+        assert_eq!(
+            params(BlockReference::SyncCheckpoint(SyncCheckpoint::Genesis)),
+            json!({ "sync_checkpoint": "genesis" })
+        );
+        assert_eq!(
+            params(BlockReference::SyncCheckpoint(
+                SyncCheckpoint::EarliestAvailable
+            )),
+            json!({ "sync_checkpoint": "earliest_available" })
+        );
+        assert_eq!(
+            params(near_primitives::types::BlockId::Height(42).into()),
+            json!({ "block_id": 42 })
+        );
+    }
+
+    #[test]
+    fn fee_tables() {
+        let config = RpcProtocolConfigResponse::parse(json!({
+            "protocol_version": 63,
+            "chain_id": "mainnet",
+            "genesis_height": 9820210,
+            "epoch_length": 43200,
+            "gas_limit": 1000000000000000u64,
+            "min_gas_price": "100000000",
+            "max_gas_price": "10000000000000000000000",
+            "transaction_validity_period": 86400,
+            "runtime_config": {
+                "storage_amount_per_byte": "10000000000000000000",
+                "transaction_costs": RuntimeFeesConfig::test(),
+                "wasm_config": { "regular_op_cost": 822756 },
+                "account_creation_config": {
+                    "min_allowed_top_level_account_length": 32,
+                    "registrar_account_id": "registrar",
+                },
+            },
+            "shard_layout": { "V1": {} },
+        }))
+        .unwrap();
+        assert_eq!(config.protocol_version, 63);
+        assert_eq!(config.min_gas_price, 100_000_000);
+        assert_eq!(
+            config.runtime_config.transaction_costs,
+            RuntimeFeesConfig::test()
+        );
+        assert_eq!(
+            config.runtime_config.storage_amount_per_byte,
+            10u128.pow(19)
+        );
+        assert_eq!(config.other["shard_layout"], json!({ "V1": {} }));
+    }
+
+    #[test]
+    fn limits() {
+        let runtime_config = |wasm_config| {
+            serde_json::from_value::<RuntimeConfigView>(json!({
+                "storage_amount_per_byte": "10000000000000000000",
+                "transaction_costs": RuntimeFeesConfig::test(),
+                "wasm_config": wasm_config,
+                "account_creation_config": {
+                    "min_allowed_top_level_account_length": 32,
+                    "registrar_account_id": "registrar",
+                },
+            }))
+            .unwrap()
+        };
+
+        let config = runtime_config(json!({
+            "regular_op_cost": 822756,
+            "limit_config": {
+                "max_gas_burnt": 300000000000000u64,
+                "max_total_prepaid_gas": 300000000000000u64,
+                "max_actions_per_receipt": 100,
+                "max_number_bytes_method_names": 2000,
+                "max_length_method_name": 256,
+                "max_arguments_length": 4194304,
+                "max_contract_size": 4194304,
+                "max_transaction_size": 4194304,
+                "max_length_storage_key": 4194304,
+            },
+        }));
+        assert_eq!(
+            config.limits().unwrap(),
+            LimitConfig {
+                max_total_prepaid_gas: 300_000_000_000_000,
+                max_actions_per_receipt: 100,
+                max_length_method_name: 256,
+                max_number_bytes_method_names: 2000,
+                max_arguments_length: 4 << 20,
+                max_contract_size: 4 << 20,
+                max_transaction_size: 4 << 20,
+            }
+        );
+
+        let config = runtime_config(json!({ "regular_op_cost": 822756 }));
+        assert!(config.limits().is_err());
+    }
+}
@@ -1,12 +1,60 @@
+//! Returns a receipt by its id.
+//!
+//! This resolves the receipt ids found in execution outcomes, like the ones of
+//! [`tx`](crate::methods::tx), to the receipts themselves: their predecessor, their receiver,
+//! and their actions or data.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use near_jsonrpc_client::{methods, JsonRpcClient};
+//! use near_jsonrpc_client::methods::EXPERIMENTAL_receipt::ReceiptReference;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JsonRpcClient::connect("https://archival-rpc.mainnet.near.org");
+//!
+//! let outcome = client
+//!     .call(methods::tx::RpcTransactionStatusRequest {
+//!         transaction_info: methods::tx::TransactionInfo::TransactionId {
+//!             hash: "B9aypWiMuiWR5kqzewL9eC96uZWA3qCMhLe67eBMWacq".parse()?,
+//!             account_id: "itranscend.near".parse()?,
+//!         },
+//!     })
+//!     .await?;
+//!
+//! let request = methods::EXPERIMENTAL_receipt::RpcReceiptRequest {
+//!     receipt_reference: ReceiptReference {
+//!         receipt_id: outcome.transaction_outcome.outcome.receipt_ids[0],
+//!     },
+//! };
+//!
+//! let receipt = client.call(request).await?;
+//!
+//! println!("{} -> {}", receipt.predecessor_id, receipt.receiver_id);
+//! # Ok(())
+//! # }
+//! ```
 use super::*;
 
-pub use near_jsonrpc_primitives::types::receipts::{RpcReceiptError, RpcReceiptRequest};
+pub use near_jsonrpc_primitives::types::receipts::{
+    ReceiptReference, RpcReceiptError, RpcReceiptRequest,
+};
+pub use near_primitives::views::ReceiptView;
 
-pub type RpcReceiptResponse = near_primitives::views::ReceiptView;
+pub type RpcReceiptResponse = ReceiptView;
 
 impl RpcHandlerResponse for RpcReceiptResponse {}
 
-impl RpcHandlerError for RpcReceiptError {}
+impl RpcHandlerError for RpcReceiptError {
+    fn variant_name(&self) -> Option<String> {
+        common::variant_name(self)
+    }
+
+    fn is_retryable(&self) -> bool {
+        matches!(self, Self::InternalError { .. })
+    }
+}
 
 impl RpcMethod for RpcReceiptRequest {
     type Response = RpcReceiptResponse;
@@ -22,3 +70,23 @@ impl RpcMethod for RpcReceiptRequest {
 }
 
 impl private::Sealed for RpcReceiptRequest {}
+
+#[cfg(test)]
+mod tests {
+    use near_primitives::hash::CryptoHash;
+
+    use super::*;
+
+    #[test]
+    fn params() {
+        let request = RpcReceiptRequest {
+            receipt_reference: ReceiptReference {
+                receipt_id: CryptoHash::default(),
+            },
+        };
+        assert_eq!(
+            request.params().unwrap(),
+            json!({ "receipt_id": "11111111111111111111111111111111" })
+        );
+    }
+}
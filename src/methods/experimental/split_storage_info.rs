@@ -0,0 +1,78 @@
+//! Returns the heads of the hot and cold stores of a node with split storage.
+//!
+//! Archival nodes with split storage keep recent data in a hot store, and copy it to a cold
+//! store holding the full history as blocks become final. A cold head lagging far behind the
+//! final head means historical queries past it may not be served yet.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use near_jsonrpc_client::{methods, JsonRpcClient};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JsonRpcClient::connect("https://archival-rpc.testnet.near.org");
+//!
+//! let info = client
+//!     .call(methods::EXPERIMENTAL_split_storage_info::RpcSplitStorageInfoRequest)
+//!     .await?;
+//!
+//! if let (Some(final_head), Some(cold_head)) = (info.final_head_height, info.cold_head_height) {
+//!     println!("the cold store is {} blocks behind", final_head.saturating_sub(cold_head));
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use super::*;
+
+use near_primitives::types::BlockHeight;
+
+#[derive(Debug)]
+pub struct RpcSplitStorageInfoRequest;
+
+#[derive(Debug, Deserialize)]
+pub struct RpcSplitStorageInfoResponse {
+    /// The height of the head of the chain.
+    pub head_height: Option<BlockHeight>,
+    /// The height of the final head of the chain.
+    pub final_head_height: Option<BlockHeight>,
+    /// The height of the last block copied to the cold store, if the node has one.
+    pub cold_head_height: Option<BlockHeight>,
+    /// The kind of the hot store, like `Hot` for nodes with split storage, or `RPC` and
+    /// `Archive` for nodes without.
+    pub hot_db_kind: Option<String>,
+}
+
+#[derive(Debug, Error, serde::Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcSplitStorageInfoError {
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl RpcHandlerResponse for RpcSplitStorageInfoResponse {}
+
+impl RpcHandlerError for RpcSplitStorageInfoError {
+    fn variant_name(&self) -> Option<String> {
+        common::variant_name(self)
+    }
+
+    fn is_retryable(&self) -> bool {
+        matches!(self, Self::InternalError { .. })
+    }
+}
+
+impl RpcMethod for RpcSplitStorageInfoRequest {
+    type Response = RpcSplitStorageInfoResponse;
+    type Error = RpcSplitStorageInfoError;
+
+    fn method_name(&self) -> &str {
+        "EXPERIMENTAL_split_storage_info"
+    }
+
+    fn params(&self) -> Result<serde_json::Value, io::Error> {
+        Ok(json!({}))
+    }
+}
+
+impl private::Sealed for RpcSplitStorageInfoRequest {}
@@ -1,20 +1,65 @@
+//! Queries the status of a transaction, with the receipts it produced.
+//!
+//! Unlike [`tx`](crate::methods::tx), the response includes the receipts themselves, not only
+//! their outcomes.
+//!
+//! Newer nodes can also wait for the transaction to reach an execution status before
+//! responding, see [`wait_until`](RpcTransactionStatusRequest::wait_until). Older nodes
+//! respond from their current view of the transaction, so leave it unset for them.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use near_jsonrpc_client::{methods, JsonRpcClient};
+//! use near_jsonrpc_client::methods::send_tx::TxExecutionStatus;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JsonRpcClient::connect("https://archival-rpc.mainnet.near.org");
+//!
+//! let request = methods::EXPERIMENTAL_tx_status::RpcTransactionStatusRequest {
+//!     transaction_info: methods::EXPERIMENTAL_tx_status::TransactionInfo::TransactionId {
+//!         hash: "B9aypWiMuiWR5kqzewL9eC96uZWA3qCMhLe67eBMWacq".parse()?,
+//!         account_id: "itranscend.near".parse()?,
+//!     },
+//!     wait_until: Some(TxExecutionStatus::Final),
+//! };
+//!
+//! let response = client.call(request).await?;
+//!
+//! for receipt in response.receipts {
+//!     println!("{}: {} -> {}", receipt.receipt_id, receipt.predecessor_id, receipt.receiver_id);
+//! }
+//! # Ok(())
+//! # }
+//! ```
 use super::*;
 
 pub use near_jsonrpc_primitives::types::transactions::RpcTransactionError;
 pub use near_jsonrpc_primitives::types::transactions::TransactionInfo;
 
+use super::send_tx::TxExecutionStatus;
+
 pub type RpcTransactionStatusResponse =
     near_primitives::views::FinalExecutionOutcomeWithReceiptView;
 
 #[derive(Debug)]
 pub struct RpcTransactionStatusRequest {
     pub transaction_info: TransactionInfo,
+    /// The execution status to wait for the transaction to reach, on nodes that support it.
+    ///
+    /// The response only holds the outcome once the transaction is executed, so this has to be
+    /// one of [`ExecutedOptimistic`](TxExecutionStatus::ExecutedOptimistic),
+    /// [`Executed`](TxExecutionStatus::Executed) or [`Final`](TxExecutionStatus::Final), other
+    /// statuses fail the request before it's sent.
+    pub wait_until: Option<TxExecutionStatus>,
 }
 
 impl From<RpcTransactionStatusRequest>
     for near_jsonrpc_primitives::types::transactions::RpcTransactionStatusCommonRequest
 {
     fn from(this: RpcTransactionStatusRequest) -> Self {
+        // the node side request has no `wait_until`
         Self {
             transaction_info: this.transaction_info,
         }
@@ -32,15 +77,87 @@ impl RpcMethod for RpcTransactionStatusRequest {
     }
 
     fn params(&self) -> Result<serde_json::Value, io::Error> {
-        Ok(match &self.transaction_info {
-            TransactionInfo::Transaction(signed_transaction) => {
-                json!([common::serialize_signed_transaction(signed_transaction)?])
+        let wait_until = match self.wait_until {
+            None => {
+                return Ok(match &self.transaction_info {
+                    TransactionInfo::Transaction(signed_transaction) => {
+                        json!([common::serialize_signed_transaction(signed_transaction)?])
+                    }
+                    TransactionInfo::TransactionId { hash, account_id } => {
+                        json!([hash, account_id])
+                    }
+                })
             }
-            TransactionInfo::TransactionId { hash, account_id } => {
-                json!([hash, account_id])
+            Some(
+                wait_until @ (TxExecutionStatus::ExecutedOptimistic
+                | TxExecutionStatus::Executed
+                | TxExecutionStatus::Final),
+            ) => wait_until,
+            Some(wait_until) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "can't wait until {:?}, there's no outcome before execution",
+                        wait_until
+                    ),
+                ))
             }
+        };
+        // only the named params carry `wait_until`
+        Ok(match &self.transaction_info {
+            TransactionInfo::Transaction(signed_transaction) => json!({
+                "signed_tx_base64": common::serialize_signed_transaction(signed_transaction)?,
+                "wait_until": wait_until,
+            }),
+            TransactionInfo::TransactionId { hash, account_id } => json!({
+                "tx_hash": hash,
+                "sender_account_id": account_id,
+                "wait_until": wait_until,
+            }),
         })
     }
+
+    fn transaction_hash(&self) -> Option<near_primitives::hash::CryptoHash> {
+        Some(common::transaction_hash(&self.transaction_info))
+    }
 }
 
 impl private::Sealed for RpcTransactionStatusRequest {}
+
+#[cfg(test)]
+mod tests {
+    use near_primitives::hash::CryptoHash;
+
+    use super::*;
+
+    #[test]
+    fn wait_until() {
+        let mut request = RpcTransactionStatusRequest {
+            transaction_info: TransactionInfo::TransactionId {
+                hash: CryptoHash::default(),
+                account_id: "itranscend.near".parse().unwrap(),
+            },
+            wait_until: None,
+        };
+        assert_eq!(
+            request.params().unwrap(),
+            json!(["11111111111111111111111111111111", "itranscend.near"])
+        );
+
+        request.wait_until = Some(TxExecutionStatus::Final);
+        assert_eq!(
+            request.params().unwrap(),
+            json!({
+                "tx_hash": "11111111111111111111111111111111",
+                "sender_account_id": "itranscend.near",
+                "wait_until": "FINAL",
+            })
+        );
+
+        request.wait_until = Some(TxExecutionStatus::Included);
+        assert_eq!(
+            request.params().unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+}
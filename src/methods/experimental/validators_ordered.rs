@@ -1,8 +1,37 @@
+//! Returns the block producers of the epoch of a block, in the order they were chosen in, with
+//! their stakes and public keys.
+//!
+//! This is the list a light client checks the `next_bps` of a
+//! [`next_light_client_block`](crate::methods::next_light_client_block) against, when the
+//! block is in the epoch before the one of the light client block.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use near_jsonrpc_client::{methods, JsonRpcClient};
+//! use near_primitives::types::BlockId;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JsonRpcClient::connect("https://archival-rpc.mainnet.near.org");
+//!
+//! let request = methods::EXPERIMENTAL_validators_ordered::RpcValidatorsOrderedRequest {
+//!     block_id: Some(BlockId::Height(83975000)),
+//! };
+//!
+//! for validator in client.call(request).await? {
+//!     let validator = validator.into_validator_stake();
+//!     println!("{} {} {}", validator.account_id(), validator.public_key(), validator.stake());
+//! }
+//! # Ok(())
+//! # }
+//! ```
 use super::*;
 
 pub use near_jsonrpc_primitives::types::validator::{
     RpcValidatorError, RpcValidatorsOrderedRequest, RpcValidatorsOrderedResponse,
 };
+pub use near_primitives::views::validator_stake_view::{ValidatorStakeView, ValidatorStakeViewV1};
 
 impl RpcHandlerResponse for RpcValidatorsOrderedResponse {}
 
@@ -20,3 +49,36 @@ impl RpcMethod for RpcValidatorsOrderedRequest {
 }
 
 impl private::Sealed for RpcValidatorsOrderedRequest {}
+
+#[cfg(test)]
+mod tests {
+    use near_primitives::types::BlockId;
+
+    use super::*;
+
+    #[test]
+    fn ordered_validators() {
+        let request = RpcValidatorsOrderedRequest {
+            block_id: Some(BlockId::Height(42)),
+        };
+        assert_eq!(request.params().unwrap(), json!({ "block_id": 42 }));
+
+        let validators = RpcValidatorsOrderedResponse::parse(json!([{
+            "validator_stake_struct_version": "V1",
+            "account_id": "validator.poolv1.near",
+            "public_key": "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp",
+            "stake": "1000000000000000000000000",
+        }]))
+        .unwrap();
+        assert_eq!(
+            validators,
+            [ValidatorStakeView::V1(ValidatorStakeViewV1 {
+                account_id: "validator.poolv1.near".parse().unwrap(),
+                public_key: "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"
+                    .parse()
+                    .unwrap(),
+                stake: 10u128.pow(24),
+            })]
+        );
+    }
+}
@@ -7,9 +7,17 @@ pub type RpcGasPriceResponse = near_primitives::views::GasPriceView;
 impl RpcHandlerResponse for RpcGasPriceResponse {}
 
 impl RpcHandlerError for RpcGasPriceError {
+    fn variant_name(&self) -> Option<String> {
+        common::variant_name(self)
+    }
+
     fn parse_raw_error(value: serde_json::Value) -> Option<Result<Self, serde_json::Error>> {
         common::parse_unknown_block!(value => Self)
     }
+
+    fn is_retryable(&self) -> bool {
+        matches!(self, Self::InternalError { .. })
+    }
 }
 
 impl RpcMethod for RpcGasPriceRequest {
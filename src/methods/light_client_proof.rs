@@ -1,5 +1,8 @@
 //! Returns the proofs for a transaction execution.
 //!
+//! A fetched proof should be checked with [`verify`], against the light client head it was
+//! requested for, before the outcome it holds can be trusted.
+//!
 //! ```
 //! use near_jsonrpc_client::{methods, JsonRpcClient};
 //! use near_primitives::types::TransactionOrReceiptId;
@@ -32,12 +35,154 @@ pub use near_jsonrpc_primitives::types::light_client::{
     RpcLightClientProofError,
 };
 
+use near_primitives::hash::{hash, CryptoHash};
+use near_primitives::merkle::{combine_hash, compute_root_from_path};
+use near_primitives::transaction::PartialExecutionStatus;
+use near_primitives::views::{
+    ExecutionOutcomeWithIdView, ExecutionStatusView, LightClientBlockLiteView,
+};
+
+/// A light client proof that doesn't hold, see [`verify`].
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ProofVerificationError {
+    #[error("the outcome has an invalid success value: {0}")]
+    InvalidSuccessValue(String),
+    #[error("the outcome root of the proof is {computed}, but the block header has {expected}")]
+    OutcomeRootMismatch {
+        expected: CryptoHash,
+        computed: CryptoHash,
+    },
+    #[error("the block merkle root of the proof is {computed}, but the light client head has {expected}")]
+    BlockMerkleRootMismatch {
+        expected: CryptoHash,
+        computed: CryptoHash,
+    },
+}
+
+/// Verify an execution proof against the block merkle root of the trusted light client head it
+/// was requested for, like `head.inner_lite.block_merkle_root`.
+///
+/// This checks that the outcome is part of the outcome root of the block header in the proof,
+/// and that the block is part of the chain of the light client head. It doesn't check the
+/// light client head itself, which is up to the light client.
+///
+/// ## Example
+///
+/// ```no_run
+/// use near_jsonrpc_client::{methods, JsonRpcClient};
+/// use near_jsonrpc_client::methods::light_client_proof::{self, RpcLightClientExecutionProofRequest};
+/// use near_primitives::types::{BlockReference, Finality, TransactionOrReceiptId};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = JsonRpcClient::connect("https://archival-rpc.mainnet.near.org");
+///
+/// // a light client would use a head it has verified itself
+/// let head = client
+///     .call(methods::block::RpcBlockRequest {
+///         block_reference: BlockReference::Finality(Finality::Final),
+///     })
+///     .await?;
+///
+/// let proof = client
+///     .call(RpcLightClientExecutionProofRequest {
+///         id: TransactionOrReceiptId::Transaction {
+///             transaction_hash: "47sXP4jKXCMpkUS6kcxsfNU7tqysYr5fxWFdEXQkZh6z".parse()?,
+///             sender_id: "aurora.pool.near".parse()?,
+///         },
+///         light_client_head: head.header.hash,
+///     })
+///     .await?;
+///
+/// light_client_proof::verify(&proof, &head.header.block_merkle_root)?;
+/// println!("verified outcome: {:?}", proof.outcome_proof.outcome.status);
+/// # Ok(())
+/// # }
+/// ```
+pub fn verify(
+    proof: &RpcLightClientExecutionProofResponse,
+    block_merkle_root: &CryptoHash,
+) -> Result<(), ProofVerificationError> {
+    let outcome_hash = CryptoHash::hash_borsh(&outcome_hashes(&proof.outcome_proof)?);
+    let shard_outcome_root = compute_root_from_path(&proof.outcome_proof.proof, outcome_hash);
+    let outcome_root = compute_root_from_path(
+        &proof.outcome_root_proof,
+        CryptoHash::hash_borsh(&shard_outcome_root),
+    );
+    let expected = proof.block_header_lite.inner_lite.outcome_root;
+    if outcome_root != expected {
+        return Err(ProofVerificationError::OutcomeRootMismatch {
+            expected,
+            computed: outcome_root,
+        });
+    }
+
+    let computed = compute_root_from_path(&proof.block_proof, block_hash(&proof.block_header_lite));
+    if computed != *block_merkle_root {
+        return Err(ProofVerificationError::BlockMerkleRootMismatch {
+            expected: *block_merkle_root,
+            computed,
+        });
+    }
+    Ok(())
+}
+
+/// The hashes an outcome is merklized as: its id, the parts of it that the light client can
+/// check, and its logs.
+fn outcome_hashes(
+    outcome: &ExecutionOutcomeWithIdView,
+) -> Result<Vec<CryptoHash>, ProofVerificationError> {
+    let status = match outcome.outcome.status {
+        ExecutionStatusView::Unknown => PartialExecutionStatus::Unknown,
+        ExecutionStatusView::Failure(_) => PartialExecutionStatus::Failure,
+        ExecutionStatusView::SuccessValue(ref value) => PartialExecutionStatus::SuccessValue(
+            near_primitives::serialize::from_base64(value)
+                .map_err(|err| ProofVerificationError::InvalidSuccessValue(err.to_string()))?,
+        ),
+        ExecutionStatusView::SuccessReceiptId(id) => PartialExecutionStatus::SuccessReceiptId(id),
+    };
+    // serialized like the node's `PartialExecutionOutcome`
+    let partial_outcome = (
+        &outcome.outcome.receipt_ids,
+        outcome.outcome.gas_burnt,
+        outcome.outcome.tokens_burnt,
+        &outcome.outcome.executor_id,
+        status,
+    );
+    let mut hashes = vec![outcome.id, CryptoHash::hash_borsh(&partial_outcome)];
+    hashes.extend(outcome.outcome.logs.iter().map(|log| hash(log.as_bytes())));
+    Ok(hashes)
+}
+
+fn block_hash(header: &LightClientBlockLiteView) -> CryptoHash {
+    let inner_lite =
+        near_primitives::block_header::BlockHeaderInnerLite::from(header.inner_lite.clone());
+    combine_hash(
+        &combine_hash(
+            &CryptoHash::hash_borsh(&inner_lite),
+            &header.inner_rest_hash,
+        ),
+        &header.prev_block_hash,
+    )
+}
+
 impl RpcHandlerResponse for RpcLightClientExecutionProofResponse {}
 
 impl RpcHandlerError for RpcLightClientProofError {
+    fn variant_name(&self) -> Option<String> {
+        common::variant_name(self)
+    }
+
     fn parse_raw_error(value: serde_json::Value) -> Option<Result<Self, serde_json::Error>> {
         common::parse_unknown_block!(value => Self)
     }
+
+    fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::UnavailableShard { .. } | Self::InternalError { .. }
+        )
+    }
 }
 
 impl RpcMethod for RpcLightClientExecutionProofRequest {
@@ -54,3 +199,90 @@ impl RpcMethod for RpcLightClientExecutionProofRequest {
 }
 
 impl private::Sealed for RpcLightClientExecutionProofRequest {}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+    use near_primitives::block_header::{BlockHeader, BlockHeaderInnerLite};
+    use near_primitives::merkle::{Direction, MerklePathItem};
+    use near_primitives::transaction::{ExecutionOutcome, ExecutionOutcomeWithId, ExecutionStatus};
+    use near_primitives::views::BlockHeaderInnerLiteView;
+
+    use super::*;
+
+    #[test]
+    fn verify_proof() {
+        let outcome = ExecutionOutcomeWithId {
+            id: hash(b"transaction"),
+            outcome: ExecutionOutcome {
+                logs: vec!["transferred".to_string()],
+                receipt_ids: vec![hash(b"receipt")],
+                gas_burnt: 42,
+                tokens_burnt: 4200,
+                executor_id: "alice.near".parse().unwrap(),
+                status: ExecutionStatus::SuccessValue(b"ok".to_vec()),
+                ..Default::default()
+            },
+        };
+        let outcome_proof = vec![MerklePathItem {
+            hash: hash(b"sibling outcome"),
+            direction: Direction::Left,
+        }];
+        let shard_outcome_root =
+            compute_root_from_path(&outcome_proof, CryptoHash::hash_borsh(&outcome.to_hashes()));
+        let outcome_root = CryptoHash::hash_borsh(&shard_outcome_root);
+
+        let inner_lite = BlockHeaderInnerLiteView {
+            height: 100,
+            epoch_id: hash(b"epoch"),
+            next_epoch_id: hash(b"next epoch"),
+            prev_state_root: hash(b"state"),
+            outcome_root,
+            timestamp: 1,
+            timestamp_nanosec: 1,
+            next_bp_hash: hash(b"block producers"),
+            block_merkle_root: hash(b"previous blocks"),
+        };
+        let inner_lite_bytes = BlockHeaderInnerLite::from(inner_lite.clone())
+            .try_to_vec()
+            .unwrap();
+        let prev_block_hash = hash(b"previous block");
+        let block_hash =
+            BlockHeader::compute_hash(prev_block_hash, &inner_lite_bytes, b"inner rest");
+        let block_proof = vec![MerklePathItem {
+            hash: hash(b"next block"),
+            direction: Direction::Right,
+        }];
+        let block_merkle_root = compute_root_from_path(&block_proof, block_hash);
+
+        let mut proof = RpcLightClientExecutionProofResponse {
+            outcome_proof: ExecutionOutcomeWithIdView {
+                proof: outcome_proof,
+                block_hash,
+                id: outcome.id,
+                outcome: outcome.outcome.into(),
+            },
+            outcome_root_proof: vec![],
+            block_header_lite: LightClientBlockLiteView {
+                prev_block_hash,
+                inner_rest_hash: hash(b"inner rest"),
+                inner_lite,
+            },
+            block_proof,
+        };
+        assert_eq!(verify(&proof, &block_merkle_root), Ok(()));
+
+        assert!(matches!(
+            verify(&proof, &hash(b"another head")),
+            Err(ProofVerificationError::BlockMerkleRootMismatch { computed, .. })
+                if computed == block_merkle_root
+        ));
+
+        proof.outcome_proof.outcome.gas_burnt += 1;
+        assert!(matches!(
+            verify(&proof, &block_merkle_root),
+            Err(ProofVerificationError::OutcomeRootMismatch { expected, .. })
+                if expected == outcome_root
+        ));
+    }
+}
@@ -20,6 +20,31 @@ where
 
     fn params(&self) -> Result<serde_json::Value, io::Error>;
 
+    /// Whether the method can safely be called more than once with the same
+    /// parameters, e.g. when retrying a failed call.
+    ///
+    /// Defaults to `true`, methods with side effects like broadcasting transactions
+    /// override this to return `false`.
+    fn is_idempotent(&self) -> bool {
+        true
+    }
+
+    /// The hash of the transaction the method submits, or looks up the status of.
+    ///
+    /// Defaults to `None`, see [`JsonRpcMultiClient::sticky_transactions`](crate::JsonRpcMultiClient::sticky_transactions).
+    fn transaction_hash(&self) -> Option<near_primitives::hash::CryptoHash> {
+        None
+    }
+
+    /// Another name the method is served under, like the stable name of an experimental
+    /// method, to call it by when the server responds that it doesn't know the method by
+    /// [`method_name`](Self::method_name).
+    ///
+    /// Defaults to `None`.
+    fn alias(&self) -> Option<&str> {
+        None
+    }
+
     fn parse_handler_response(
         response: serde_json::Value,
     ) -> Result<Result<Self::Response, Self::Error>, serde_json::Error> {
@@ -42,6 +67,58 @@ where
     fn params(&self) -> Result<serde_json::Value, io::Error> {
         T::params(self)
     }
+
+    fn is_idempotent(&self) -> bool {
+        T::is_idempotent(self)
+    }
+
+    fn transaction_hash(&self) -> Option<near_primitives::hash::CryptoHash> {
+        T::transaction_hash(self)
+    }
+
+    fn alias(&self) -> Option<&str> {
+        T::alias(self)
+    }
+
+    fn parse_handler_response(
+        response: serde_json::Value,
+    ) -> Result<Result<Self::Response, Self::Error>, serde_json::Error> {
+        T::parse_handler_response(response)
+    }
+}
+
+/// A method, called by its [`alias`](RpcMethod::alias).
+pub(crate) struct Aliased<'a, M>(pub(crate) &'a M);
+
+impl<M> private::Sealed for Aliased<'_, M> {}
+impl<M> RpcMethod for Aliased<'_, M>
+where
+    M: RpcMethod,
+{
+    type Response = M::Response;
+    type Error = M::Error;
+
+    fn method_name(&self) -> &str {
+        self.0.alias().unwrap_or_else(|| self.0.method_name())
+    }
+
+    fn params(&self) -> Result<serde_json::Value, io::Error> {
+        self.0.params()
+    }
+
+    fn is_idempotent(&self) -> bool {
+        self.0.is_idempotent()
+    }
+
+    fn transaction_hash(&self) -> Option<near_primitives::hash::CryptoHash> {
+        self.0.transaction_hash()
+    }
+
+    fn parse_handler_response(
+        response: serde_json::Value,
+    ) -> Result<Result<Self::Response, Self::Error>, serde_json::Error> {
+        M::parse_handler_response(response)
+    }
 }
 
 pub trait RpcHandlerResponse: serde::de::DeserializeOwned {
@@ -64,6 +141,30 @@ pub trait RpcHandlerError: serde::de::DeserializeOwned {
     fn parse_raw_error(_error: serde_json::Value) -> Option<Result<Self, serde_json::Error>> {
         None
     }
+
+    /// Whether the error is likely to go away on its own, such that the call
+    /// is worth retrying, like timeouts or internal server errors.
+    ///
+    /// Defaults to `false`.
+    fn is_retryable(&self) -> bool {
+        false
+    }
+
+    /// The name of the error's variant, as reported by the server, like `UNKNOWN_BLOCK`
+    /// or `TIMEOUT_ERROR`, see [`ErrorClass::Handler`](crate::metrics::ErrorClass::Handler).
+    ///
+    /// Defaults to `None`.
+    fn variant_name(&self) -> Option<String> {
+        None
+    }
+
+    /// Whether the error may be due to the server having garbage collected the
+    /// requested data, such that an archival server could still serve it.
+    ///
+    /// Defaults to `false`.
+    fn is_pruned(&self) -> bool {
+        false
+    }
 }
 
 pub mod block;
@@ -76,6 +177,7 @@ pub mod light_client_proof;
 pub mod network_info;
 pub mod next_light_client_block;
 pub mod query;
+pub mod send_tx;
 pub mod status;
 pub mod tx;
 pub mod validators;
@@ -85,9 +187,15 @@ mod experimental;
 pub use experimental::EXPERIMENTAL_changes;
 pub use experimental::EXPERIMENTAL_changes_in_block;
 pub use experimental::EXPERIMENTAL_check_tx;
+pub use experimental::EXPERIMENTAL_client_config;
+pub use experimental::EXPERIMENTAL_congestion_level;
 pub use experimental::EXPERIMENTAL_genesis_config;
+pub use experimental::EXPERIMENTAL_genesis_records;
+pub use experimental::EXPERIMENTAL_light_client_block_proof;
+pub use experimental::EXPERIMENTAL_maintenance_windows;
 pub use experimental::EXPERIMENTAL_protocol_config;
 pub use experimental::EXPERIMENTAL_receipt;
+pub use experimental::EXPERIMENTAL_split_storage_info;
 pub use experimental::EXPERIMENTAL_tx_status;
 pub use experimental::EXPERIMENTAL_validators_ordered;
 // ======== experimental ========
@@ -96,7 +204,7 @@ pub use experimental::EXPERIMENTAL_validators_ordered;
 #[cfg(feature = "any")]
 mod any;
 #[cfg(feature = "any")]
-pub use any::{request as any, RpcAnyRequest};
+pub use any::{request as any, Raw, RpcAnyRequest};
 // ======== any ========
 
 // ======== sandbox ========
@@ -164,6 +272,27 @@ mod common {
     }
     pub(crate) use _parse_unknown_block as parse_unknown_block;
 
+    pub fn transaction_hash(
+        transaction_info: &near_jsonrpc_primitives::types::transactions::TransactionInfo,
+    ) -> near_primitives::hash::CryptoHash {
+        match transaction_info {
+            near_jsonrpc_primitives::types::transactions::TransactionInfo::Transaction(
+                signed_transaction,
+            ) => signed_transaction.get_hash(),
+            near_jsonrpc_primitives::types::transactions::TransactionInfo::TransactionId {
+                hash,
+                ..
+            } => *hash,
+        }
+    }
+
+    /// The `name` NEAR handler errors are serialized with.
+    pub fn variant_name<E: serde::Serialize>(err: &E) -> Option<String> {
+        serde_json::to_value(err).ok()?["name"]
+            .as_str()
+            .map(str::to_string)
+    }
+
     pub fn serialize_signed_transaction(
         tx: &near_primitives::transaction::SignedTransaction,
     ) -> Result<String, io::Error> {
@@ -193,6 +322,10 @@ mod common {
 
     #[cfg(feature = "any")]
     impl RpcHandlerError for serde_json::Value {
+        fn variant_name(&self) -> Option<String> {
+            variant_name(self)
+        }
+
         fn parse(handler_error: serde_json::Value) -> Result<Self, serde_json::Error> {
             Ok(handler_error)
         }
@@ -203,6 +336,10 @@ mod common {
 
     // broadcast_tx_commit, tx, EXPERIMENTAL_check_tx, EXPERIMENTAL_tx_status
     impl RpcHandlerError for near_jsonrpc_primitives::types::transactions::RpcTransactionError {
+        fn variant_name(&self) -> Option<String> {
+            variant_name(self)
+        }
+
         fn parse_raw_error(value: serde_json::Value) -> Option<Result<Self, serde_json::Error>> {
             match serde_json::from_value::<near_jsonrpc_primitives::errors::ServerError>(value) {
                 Ok(near_jsonrpc_primitives::errors::ServerError::TxExecutionError(
@@ -212,16 +349,47 @@ mod common {
                 _ => None,
             }
         }
+
+        fn is_retryable(&self) -> bool {
+            matches!(self, Self::TimeoutError | Self::InternalError { .. })
+        }
+
+        fn is_pruned(&self) -> bool {
+            matches!(self, Self::UnknownTransaction { .. })
+        }
     }
 
     // health, status
-    impl RpcHandlerError for near_jsonrpc_primitives::types::status::RpcStatusError {}
+    impl RpcHandlerError for near_jsonrpc_primitives::types::status::RpcStatusError {
+        fn variant_name(&self) -> Option<String> {
+            variant_name(self)
+        }
+
+        fn is_retryable(&self) -> bool {
+            matches!(
+                self,
+                Self::NodeIsSyncing | Self::NoNewBlocks { .. } | Self::InternalError { .. }
+            )
+        }
+    }
 
     // EXPERIMENTAL_changes, EXPERIMENTAL_changes_in_block
     impl RpcHandlerError for near_jsonrpc_primitives::types::changes::RpcStateChangesError {
+        fn variant_name(&self) -> Option<String> {
+            variant_name(self)
+        }
+
         fn parse_raw_error(value: serde_json::Value) -> Option<Result<Self, serde_json::Error>> {
             parse_unknown_block!(value => Self)
         }
+
+        fn is_retryable(&self) -> bool {
+            matches!(self, Self::NotSyncedYet | Self::InternalError { .. })
+        }
+
+        fn is_pruned(&self) -> bool {
+            matches!(self, Self::UnknownBlock { .. })
+        }
     }
 
     // EXPERIMENTAL_broadcast_tx_sync, EXPERIMENTAL_check_tx
@@ -231,5 +399,16 @@ mod common {
     }
 
     // validators, EXPERIMENTAL_validators_ordered
-    impl RpcHandlerError for near_jsonrpc_primitives::types::validator::RpcValidatorError {}
+    impl RpcHandlerError for near_jsonrpc_primitives::types::validator::RpcValidatorError {
+        fn variant_name(&self) -> Option<String> {
+            variant_name(self)
+        }
+
+        fn is_retryable(&self) -> bool {
+            matches!(
+                self,
+                Self::ValidatorInfoUnavailable | Self::InternalError { .. }
+            )
+        }
+    }
 }
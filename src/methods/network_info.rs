@@ -2,6 +2,12 @@
 //!
 //! This includes information about active peers, transmitted data, known producers, etc.
 //!
+//! Each of the [`active_peers`](RpcNetworkInfoResponse::active_peers) is a [`RpcPeerInfo`], with
+//! its peer id, and its address and account id when the node knows them. The
+//! [`known_producers`](RpcNetworkInfoResponse::known_producers) are block and chunk producers
+//! from the routing table, as [`RpcKnownProducer`]s. The node doesn't report the heights of its
+//! peers here.
+//!
 //! ## Example
 //!
 //! ```
@@ -25,15 +31,24 @@
 use super::*;
 
 pub use near_jsonrpc_primitives::types::network_info::{
-    RpcNetworkInfoError, RpcNetworkInfoResponse,
+    RpcKnownProducer, RpcNetworkInfoError, RpcNetworkInfoResponse, RpcPeerInfo,
 };
+pub use near_primitives::network::PeerId;
 
 #[derive(Debug)]
 pub struct RpcNetworkInfoRequest;
 
 impl RpcHandlerResponse for RpcNetworkInfoResponse {}
 
-impl RpcHandlerError for RpcNetworkInfoError {}
+impl RpcHandlerError for RpcNetworkInfoError {
+    fn variant_name(&self) -> Option<String> {
+        common::variant_name(self)
+    }
+
+    fn is_retryable(&self) -> bool {
+        matches!(self, Self::InternalError { .. })
+    }
+}
 
 impl RpcMethod for RpcNetworkInfoRequest {
     type Response = RpcNetworkInfoResponse;
@@ -49,3 +64,55 @@ impl RpcMethod for RpcNetworkInfoRequest {
 }
 
 impl private::Sealed for RpcNetworkInfoRequest {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_response() {
+        let response = RpcNetworkInfoResponse::parse(json!({
+            "active_peers": [
+                {
+                    "id": "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp",
+                    "addr": "34.94.158.10:24567",
+                    "account_id": "node0",
+                },
+                {
+                    "id": "ed25519:7PGseFbWxvYVgZ89K1uTJKYoKetWs7BJtbyXDzfbAcqX",
+                    "addr": null,
+                    "account_id": null,
+                },
+            ],
+            "num_active_peers": 2,
+            "peer_max_count": 40,
+            "sent_bytes_per_sec": 1024,
+            "received_bytes_per_sec": 2048,
+            "known_producers": [
+                {
+                    "account_id": "node0",
+                    "addr": "34.94.158.10:24567",
+                    "peer_id": "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp",
+                },
+            ],
+        }))
+        .unwrap();
+
+        assert_eq!(response.num_active_peers, 2);
+        assert_eq!(response.sent_bytes_per_sec, 1024);
+        assert_eq!(response.received_bytes_per_sec, 2048);
+
+        let peer = &response.active_peers[0];
+        assert_eq!(
+            peer.id.public_key().to_string(),
+            "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"
+        );
+        assert_eq!(peer.addr, Some("34.94.158.10:24567".parse().unwrap()));
+        assert_eq!(peer.account_id, Some("node0".parse().unwrap()));
+        assert!(response.active_peers[1].addr.is_none());
+
+        let producer = &response.known_producers[0];
+        assert_eq!(producer.account_id.as_ref(), "node0");
+        assert_eq!(producer.peer_id, peer.id);
+    }
+}
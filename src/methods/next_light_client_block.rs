@@ -1,3 +1,29 @@
+//! Returns the next light client block, as of the last block known to the light client.
+//!
+//! The response is `None` when there's no newer block to move the light client to, that is when
+//! the last known block is in the epoch of the head of the chain, and the next block producers
+//! are already known.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use near_jsonrpc_client::{methods, JsonRpcClient};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JsonRpcClient::connect("https://rpc.testnet.near.org");
+//!
+//! let request = methods::next_light_client_block::RpcLightClientNextBlockRequest {
+//!     last_block_hash: "ANm3jm5wq1Z4rJv6tXWyiDtC3wYKpXVHY4iq6bE1te7B".parse()?,
+//! };
+//!
+//! match client.call(request).await? {
+//!     Some(block) => println!("next light client block at height {}", block.inner_lite.height),
+//!     None => println!("the light client is up to date"),
+//! }
+//! # Ok(())
+//! # }
+//! ```
 use super::*;
 
 pub use near_jsonrpc_primitives::types::light_client::{
@@ -7,12 +33,28 @@ pub use near_primitives::views::LightClientBlockView;
 
 pub type RpcLightClientNextBlockResponse = Option<LightClientBlockView>;
 
-impl RpcHandlerResponse for RpcLightClientNextBlockResponse {}
+impl RpcHandlerResponse for RpcLightClientNextBlockResponse {
+    fn parse(value: serde_json::Value) -> Result<Self, serde_json::Error> {
+        // the node flattens the missing block into an empty object
+        match value {
+            serde_json::Value::Object(ref map) if map.is_empty() => Ok(None),
+            value => serde_json::from_value(value),
+        }
+    }
+}
 
 impl RpcHandlerError for RpcLightClientNextBlockError {
+    fn variant_name(&self) -> Option<String> {
+        common::variant_name(self)
+    }
+
     fn parse_raw_error(value: serde_json::Value) -> Option<Result<Self, serde_json::Error>> {
         common::parse_unknown_block!(value => Self)
     }
+
+    fn is_retryable(&self) -> bool {
+        matches!(self, Self::InternalError { .. })
+    }
 }
 
 impl RpcMethod for RpcLightClientNextBlockRequest {
@@ -29,3 +71,19 @@ impl RpcMethod for RpcLightClientNextBlockRequest {
 }
 
 impl private::Sealed for RpcLightClientNextBlockRequest {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_newer_block() {
+        assert!(RpcLightClientNextBlockResponse::parse(json!({}))
+            .unwrap()
+            .is_none());
+        assert!(RpcLightClientNextBlockResponse::parse(json!(null))
+            .unwrap()
+            .is_none());
+        assert!(RpcLightClientNextBlockResponse::parse(json!({ "prev_block_hash": "" })).is_err());
+    }
+}
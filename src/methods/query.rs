@@ -1,10 +1,26 @@
 use super::*;
 
 pub use near_jsonrpc_primitives::types::query::{RpcQueryError, RpcQueryRequest, RpcQueryResponse};
+pub use near_primitives::types::SyncCheckpoint;
 
 impl RpcHandlerResponse for RpcQueryResponse {}
 
-impl RpcHandlerError for RpcQueryError {}
+impl RpcHandlerError for RpcQueryError {
+    fn variant_name(&self) -> Option<String> {
+        common::variant_name(self)
+    }
+
+    fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::NoSyncedBlocks | Self::UnavailableShard { .. } | Self::InternalError { .. }
+        )
+    }
+
+    fn is_pruned(&self) -> bool {
+        matches!(self, Self::UnknownBlock { .. })
+    }
+}
 
 impl private::Sealed for RpcQueryRequest {}
 
@@ -23,61 +39,267 @@ impl RpcMethod for RpcQueryRequest {
     fn parse_handler_response(
         response: serde_json::Value,
     ) -> Result<Result<Self::Response, Self::Error>, serde_json::Error> {
-        match serde_json::from_value::<QueryResponse>(response)? {
-            QueryResponse::HandlerResponse(r) => Ok(Ok(r)),
-            QueryResponse::HandlerError(LegacyQueryError {
-                error,
-                block_height,
-                block_hash,
-            }) => {
-                let mut err_parts = error.split(' ');
-                let query_error = if let (
-                    Some("access"),
-                    Some("key"),
-                    Some(pk),
-                    Some("does"),
-                    Some("not"),
-                    Some("exist"),
-                    Some("while"),
-                    Some("viewing"),
-                    None,
-                ) = (
-                    err_parts.next(),
-                    err_parts.next(),
-                    err_parts.next(),
-                    err_parts.next(),
-                    err_parts.next(),
-                    err_parts.next(),
-                    err_parts.next(),
-                    err_parts.next(),
-                    err_parts.next(),
-                ) {
-                    let public_key = pk
-                        .parse::<near_crypto::PublicKey>()
-                        .map_err(serde::de::Error::custom)?;
-                    RpcQueryError::UnknownAccessKey {
-                        public_key,
-                        block_height,
-                        block_hash,
-                    }
-                } else {
-                    RpcQueryError::ContractExecutionError {
-                        vm_error: error,
-                        block_height,
-                        block_hash,
-                    }
-                };
-
-                Ok(Err(query_error))
-            }
+        parse_query_response(response)
+    }
+}
+
+/// A `view_state` query, which, unlike [`QueryRequest::ViewState`], can ask newer nodes for the
+/// proof of the values.
+///
+/// [`QueryRequest::ViewState`]: near_primitives::views::QueryRequest::ViewState
+///
+/// ## Example
+///
+/// ```no_run
+/// use near_jsonrpc_client::{methods, JsonRpcClient};
+/// use near_primitives::types::{BlockReference, Finality};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = JsonRpcClient::connect("https://rpc.testnet.near.org");
+///
+/// let request = methods::query::RpcViewStateRequest {
+///     block_reference: BlockReference::Finality(Finality::Final),
+///     account_id: "guest-book.testnet".parse()?,
+///     prefix: b"STATE".to_vec(),
+///     include_proof: true,
+/// };
+///
+/// let response = client.call(request).await?;
+///
+/// for node in &response.proof {
+///     println!("proof node {}, {} bytes", node.hash(), node.as_bytes().len());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, serde::Serialize)]
+pub struct RpcViewStateRequest {
+    #[serde(flatten)]
+    pub block_reference: near_primitives::types::BlockReference,
+    pub account_id: near_primitives::types::AccountId,
+    /// The prefix of the keys to view the values of, as raw bytes. An empty prefix matches
+    /// every key.
+    #[serde(
+        rename = "prefix_base64",
+        with = "near_primitives::serialize::base64_format"
+    )]
+    pub prefix: Vec<u8>,
+    /// Whether to ask for the proof of the values, which older nodes ignore.
+    pub include_proof: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RpcViewStateResponse {
+    /// The values under the prefix.
+    pub values: Vec<StateValue>,
+    /// The trie nodes proving the values, if they were asked for and the node supports it.
+    #[serde(default)]
+    pub proof: Vec<TrieProofNode>,
+    pub block_height: near_primitives::types::BlockHeight,
+    pub block_hash: near_primitives::hash::CryptoHash,
+}
+
+/// A value in the state of a contract.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+pub struct StateValue {
+    #[serde(with = "near_primitives::serialize::base64_format")]
+    pub key: Vec<u8>,
+    #[serde(with = "near_primitives::serialize::base64_format")]
+    pub value: Vec<u8>,
+}
+
+/// A node of the state trie, as it's stored, and referred to by its hash in its parent.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+pub struct TrieProofNode(#[serde(with = "near_primitives::serialize::base64_format")] Vec<u8>);
+
+impl TrieProofNode {
+    /// The hash of the node, which the state root is for the root node of the trie.
+    pub fn hash(&self) -> near_primitives::hash::CryptoHash {
+        near_primitives::hash::hash(&self.0)
+    }
+
+    /// The serialized node.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl RpcHandlerResponse for RpcViewStateResponse {}
+
+impl private::Sealed for RpcViewStateRequest {}
+
+impl RpcMethod for RpcViewStateRequest {
+    type Response = RpcViewStateResponse;
+    type Error = RpcQueryError;
+
+    fn method_name(&self) -> &str {
+        "query"
+    }
+
+    fn params(&self) -> Result<serde_json::Value, io::Error> {
+        let mut params = json!(self);
+        params["request_type"] = json!("view_state");
+        Ok(params)
+    }
+
+    fn parse_handler_response(
+        response: serde_json::Value,
+    ) -> Result<Result<Self::Response, Self::Error>, serde_json::Error> {
+        parse_query_response(response)
+    }
+}
+
+/// A `call_function` query, calling a view method of a contract.
+///
+/// Unlike [`QueryRequest::CallFunction`], the response has the result, the logs emitted by the
+/// call, and the block it was made at, all at hand.
+///
+/// [`QueryRequest::CallFunction`]: near_primitives::views::QueryRequest::CallFunction
+///
+/// ## Example
+///
+/// ```no_run
+/// use near_jsonrpc_client::{methods, JsonRpcClient};
+/// use near_primitives::types::{BlockReference, Finality};
+/// use serde_json::json;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = JsonRpcClient::connect("https://rpc.testnet.near.org");
+///
+/// let request = methods::query::RpcCallFunctionRequest {
+///     block_reference: BlockReference::Finality(Finality::Final),
+///     account_id: "guest-book.testnet".parse()?,
+///     method_name: "getMessages".to_string(),
+///     args: json!({}).to_string().into_bytes(),
+/// };
+///
+/// let response = client.call(request).await?;
+///
+/// for log in &response.logs {
+///     println!("log: {}", log);
+/// }
+/// let messages: Vec<serde_json::Value> = response.json()?;
+/// println!("{} messages as of block {}", messages.len(), response.block_height);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, serde::Serialize)]
+pub struct RpcCallFunctionRequest {
+    #[serde(flatten)]
+    pub block_reference: near_primitives::types::BlockReference,
+    pub account_id: near_primitives::types::AccountId,
+    pub method_name: String,
+    /// The arguments to the method, as raw bytes, usually JSON.
+    #[serde(
+        rename = "args_base64",
+        with = "near_primitives::serialize::base64_format"
+    )]
+    pub args: Vec<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RpcCallFunctionResponse {
+    /// The value the method returned, as raw bytes.
+    pub result: Vec<u8>,
+    /// The logs the method emitted.
+    pub logs: Vec<String>,
+    pub block_height: near_primitives::types::BlockHeight,
+    pub block_hash: near_primitives::hash::CryptoHash,
+}
+
+impl RpcCallFunctionResponse {
+    /// Parse the value the method returned as JSON, which is how most contracts return values.
+    pub fn json<T: serde::de::DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_slice(&self.result)
+    }
+}
+
+impl RpcHandlerResponse for RpcCallFunctionResponse {}
+
+impl private::Sealed for RpcCallFunctionRequest {}
+
+impl RpcMethod for RpcCallFunctionRequest {
+    type Response = RpcCallFunctionResponse;
+    type Error = RpcQueryError;
+
+    fn method_name(&self) -> &str {
+        "query"
+    }
+
+    fn params(&self) -> Result<serde_json::Value, io::Error> {
+        let mut params = json!(self);
+        params["request_type"] = json!("call_function");
+        Ok(params)
+    }
+
+    fn parse_handler_response(
+        response: serde_json::Value,
+    ) -> Result<Result<Self::Response, Self::Error>, serde_json::Error> {
+        parse_query_response(response)
+    }
+}
+
+/// Parse the response to a query, which may be an error in the place of the result, on older
+/// nodes.
+fn parse_query_response<T: serde::de::DeserializeOwned>(
+    response: serde_json::Value,
+) -> Result<Result<T, RpcQueryError>, serde_json::Error> {
+    match serde_json::from_value::<QueryResponse<T>>(response)? {
+        QueryResponse::HandlerResponse(r) => Ok(Ok(r)),
+        QueryResponse::HandlerError(LegacyQueryError {
+            error,
+            block_height,
+            block_hash,
+        }) => {
+            let mut err_parts = error.split(' ');
+            let query_error = if let (
+                Some("access"),
+                Some("key"),
+                Some(pk),
+                Some("does"),
+                Some("not"),
+                Some("exist"),
+                Some("while"),
+                Some("viewing"),
+                None,
+            ) = (
+                err_parts.next(),
+                err_parts.next(),
+                err_parts.next(),
+                err_parts.next(),
+                err_parts.next(),
+                err_parts.next(),
+                err_parts.next(),
+                err_parts.next(),
+                err_parts.next(),
+            ) {
+                let public_key = pk
+                    .parse::<near_crypto::PublicKey>()
+                    .map_err(serde::de::Error::custom)?;
+                RpcQueryError::UnknownAccessKey {
+                    public_key,
+                    block_height,
+                    block_hash,
+                }
+            } else {
+                RpcQueryError::ContractExecutionError {
+                    vm_error: error,
+                    block_height,
+                    block_hash,
+                }
+            };
+
+            Ok(Err(query_error))
         }
     }
 }
 
 #[derive(serde::Deserialize)]
 #[serde(untagged)]
-enum QueryResponse {
-    HandlerResponse(RpcQueryResponse),
+enum QueryResponse<T> {
+    HandlerResponse(T),
     HandlerError(LegacyQueryError),
 }
 
@@ -90,9 +312,131 @@ struct LegacyQueryError {
 
 #[cfg(test)]
 mod tests {
-    use {super::*, crate::*};
+    use super::*;
+    #[cfg(feature = "reqwest-transport")]
+    use crate::JsonRpcClient;
+
+    #[test]
+    fn sync_checkpoints() {
+        let request = RpcQueryRequest {
+            block_reference: near_primitives::types::BlockReference::SyncCheckpoint(
+                SyncCheckpoint::EarliestAvailable,
+            ),
+            request: near_primitives::views::QueryRequest::ViewAccount {
+                account_id: "guest-book.testnet".parse().unwrap(),
+            },
+        };
+        assert_eq!(
+            request.params().unwrap(),
+            json!({
+                "request_type": "view_account",
+                "sync_checkpoint": "earliest_available",
+                "account_id": "guest-book.testnet",
+            })
+        );
+
+        let request = RpcViewStateRequest {
+            block_reference: near_primitives::types::BlockReference::SyncCheckpoint(
+                SyncCheckpoint::Genesis,
+            ),
+            account_id: "guest-book.testnet".parse().unwrap(),
+            prefix: vec![],
+            include_proof: false,
+        };
+        assert_eq!(request.params().unwrap()["sync_checkpoint"], "genesis");
+    }
+
+    #[test]
+    fn view_state_with_proof() {
+        let request = RpcViewStateRequest {
+            block_reference: near_primitives::types::BlockId::Height(42).into(),
+            account_id: "guest-book.testnet".parse().unwrap(),
+            prefix: b"STATE".to_vec(),
+            include_proof: true,
+        };
+        assert_eq!(
+            request.params().unwrap(),
+            json!({
+                "request_type": "view_state",
+                "block_id": 42,
+                "account_id": "guest-book.testnet",
+                "prefix_base64": "U1RBVEU=",
+                "include_proof": true,
+            })
+        );
+
+        let response = RpcViewStateRequest::parse_handler_response(json!({
+            "values": [{ "key": "U1RBVEU=", "value": "AQID" }],
+            "proof": ["AGFzbQ=="],
+            "block_height": 42,
+            "block_hash": near_primitives::hash::CryptoHash::default(),
+        }))
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            response.values,
+            [StateValue {
+                key: b"STATE".to_vec(),
+                value: vec![1, 2, 3],
+            }]
+        );
+        assert_eq!(response.proof[0].as_bytes(), b"\0asm");
+        assert_eq!(
+            response.proof[0].hash(),
+            near_primitives::hash::hash(b"\0asm")
+        );
+
+        // older nodes send the errors in the place of the result
+        let err = RpcViewStateRequest::parse_handler_response(json!({
+            "error": "wasm execution failed",
+            "block_height": 42,
+            "block_hash": near_primitives::hash::CryptoHash::default(),
+        }))
+        .unwrap()
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            RpcQueryError::ContractExecutionError {
+                block_height: 42,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn call_function() {
+        let request = RpcCallFunctionRequest {
+            block_reference: near_primitives::types::BlockId::Height(42).into(),
+            account_id: "guest-book.testnet".parse().unwrap(),
+            method_name: "getMessages".to_string(),
+            args: b"{}".to_vec(),
+        };
+        assert_eq!(
+            request.params().unwrap(),
+            json!({
+                "request_type": "call_function",
+                "block_id": 42,
+                "account_id": "guest-book.testnet",
+                "method_name": "getMessages",
+                "args_base64": "e30=",
+            })
+        );
+
+        let response = RpcCallFunctionRequest::parse_handler_response(json!({
+            "result": b"[1,2]",
+            "logs": ["viewed 2 messages"],
+            "block_height": 42,
+            "block_hash": near_primitives::hash::CryptoHash::default(),
+        }))
+        .unwrap()
+        .unwrap();
+        assert_eq!(response.json::<Vec<u32>>().unwrap(), [1, 2]);
+        assert_eq!(response.logs, ["viewed 2 messages"]);
+        assert_eq!(response.block_height, 42);
+    }
 
     #[tokio::test]
+    #[cfg(feature = "reqwest-transport")]
     async fn test_unknown_access_key() -> Result<(), Box<dyn std::error::Error>> {
         let client = JsonRpcClient::connect("https://archival-rpc.testnet.near.org");
 
@@ -122,6 +466,7 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg(feature = "reqwest-transport")]
     async fn test_contract_execution_error() -> Result<(), Box<dyn std::error::Error>> {
         let client = JsonRpcClient::connect("https://archival-rpc.testnet.near.org");
 
@@ -1,3 +1,31 @@
+//! Fast forwards the chain of a sandbox node by the specified number of blocks.
+//!
+//! This lets tests reach heights that would otherwise take a while, like the ones past which
+//! time-locked logic unlocks. The node only responds once it has reached the new height, so a
+//! call with a [`timeout`](crate::CallOptions::timeout) or a
+//! [`deadline`](crate::CallOptions::deadline) needs one long enough for the jump.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use near_jsonrpc_client::{methods, CallOptions, JsonRpcClient};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JsonRpcClient::connect("http://localhost:3030");
+//!
+//! let request = methods::sandbox_fast_forward::RpcSandboxFastForwardRequest {
+//!     delta_height: 10_000,
+//! };
+//!
+//! client
+//!     .call_with_options(request, CallOptions::new().timeout(Duration::from_secs(120)))
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
 use super::*;
 
 pub use near_jsonrpc_primitives::types::sandbox::{
@@ -6,7 +34,15 @@ pub use near_jsonrpc_primitives::types::sandbox::{
 
 impl RpcHandlerResponse for RpcSandboxFastForwardResponse {}
 
-impl RpcHandlerError for RpcSandboxFastForwardError {}
+impl RpcHandlerError for RpcSandboxFastForwardError {
+    fn variant_name(&self) -> Option<String> {
+        common::variant_name(self)
+    }
+
+    fn is_retryable(&self) -> bool {
+        matches!(self, Self::InternalError { .. })
+    }
+}
 
 impl RpcMethod for RpcSandboxFastForwardRequest {
     type Response = RpcSandboxFastForwardResponse;
@@ -19,6 +55,24 @@ impl RpcMethod for RpcSandboxFastForwardRequest {
     fn params(&self) -> Result<serde_json::Value, io::Error> {
         Ok(json!(self))
     }
+
+    fn is_idempotent(&self) -> bool {
+        false
+    }
 }
 
 impl private::Sealed for RpcSandboxFastForwardRequest {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params() {
+        let request = RpcSandboxFastForwardRequest {
+            delta_height: 10_000,
+        };
+        assert_eq!(request.params().unwrap(), json!({ "delta_height": 10000 }));
+        assert!(!request.is_idempotent());
+    }
+}
@@ -1,12 +1,64 @@
+//! Patches the state of a sandbox node with the specified records.
+//!
+//! This is how tests fork state into a [near-sandbox](https://github.com/near/sandbox) node:
+//! accounts, access keys, contract code and contract data, each as a [`StateRecord`], are
+//! written as is, without transactions. Data records take their keys and values as raw bytes,
+//! they are base64 encoded when the request is sent.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use near_jsonrpc_client::{methods, JsonRpcClient};
+//! use near_jsonrpc_client::methods::sandbox_patch_state::{AccessKey, StateRecord};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JsonRpcClient::connect("http://localhost:3030");
+//!
+//! let account_id: near_primitives::types::AccountId = "guest-book.test.near".parse()?;
+//!
+//! let request = methods::sandbox_patch_state::RpcSandboxPatchStateRequest {
+//!     records: vec![
+//!         StateRecord::Contract {
+//!             account_id: account_id.clone(),
+//!             code: std::fs::read("guest_book.wasm")?,
+//!         },
+//!         StateRecord::AccessKey {
+//!             account_id: account_id.clone(),
+//!             public_key: "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp".parse()?,
+//!             access_key: AccessKey::full_access(),
+//!         },
+//!         StateRecord::Data {
+//!             account_id,
+//!             data_key: b"STATE".to_vec(),
+//!             value: vec![0; 8],
+//!         },
+//!     ],
+//! };
+//!
+//! client.call(request).await?;
+//! # Ok(())
+//! # }
+//! ```
 use super::*;
 
 pub use near_jsonrpc_primitives::types::sandbox::{
     RpcSandboxPatchStateError, RpcSandboxPatchStateRequest, RpcSandboxPatchStateResponse,
 };
+pub use near_primitives::account::{AccessKey, Account};
+pub use near_primitives::state_record::StateRecord;
 
 impl RpcHandlerResponse for RpcSandboxPatchStateResponse {}
 
-impl RpcHandlerError for RpcSandboxPatchStateError {}
+impl RpcHandlerError for RpcSandboxPatchStateError {
+    fn variant_name(&self) -> Option<String> {
+        common::variant_name(self)
+    }
+
+    fn is_retryable(&self) -> bool {
+        matches!(self, Self::InternalError { .. })
+    }
+}
 
 impl RpcMethod for RpcSandboxPatchStateRequest {
     type Response = RpcSandboxPatchStateResponse;
@@ -19,6 +71,52 @@ impl RpcMethod for RpcSandboxPatchStateRequest {
     fn params(&self) -> Result<serde_json::Value, io::Error> {
         Ok(json!(self))
     }
+
+    fn is_idempotent(&self) -> bool {
+        false
+    }
 }
 
 impl private::Sealed for RpcSandboxPatchStateRequest {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records() {
+        let request = RpcSandboxPatchStateRequest {
+            records: vec![
+                StateRecord::Contract {
+                    account_id: "guest-book.test.near".parse().unwrap(),
+                    code: b"\0asm".to_vec(),
+                },
+                StateRecord::Data {
+                    account_id: "guest-book.test.near".parse().unwrap(),
+                    data_key: b"STATE".to_vec(),
+                    value: vec![1, 2, 3],
+                },
+            ],
+        };
+        assert_eq!(
+            request.params().unwrap(),
+            json!({
+                "records": [
+                    {
+                        "Contract": {
+                            "account_id": "guest-book.test.near",
+                            "code": "AGFzbQ==",
+                        }
+                    },
+                    {
+                        "Data": {
+                            "account_id": "guest-book.test.near",
+                            "data_key": "U1RBVEU=",
+                            "value": "AQID",
+                        }
+                    },
+                ]
+            })
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! Sends a signed transaction, waiting until it reaches the specified execution status.
+//!
+//! Unlike [`broadcast_tx_async`](super::broadcast_tx_async), which returns as soon as the
+//! transaction is submitted, and [`broadcast_tx_commit`](super::broadcast_tx_commit), which waits
+//! for it to be executed, `send_tx` lets the caller choose what to wait for, from the
+//! transaction being [included](TxExecutionStatus::Included) in a block to it being
+//! [final](TxExecutionStatus::Final).
+//!
+//! This method is only available on newer nodes, older ones respond with a method not found error.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use near_jsonrpc_client::{methods, JsonRpcClient};
+//! use near_jsonrpc_client::methods::send_tx::TxExecutionStatus;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JsonRpcClient::connect("https://rpc.testnet.near.org");
+//!
+//! # let signed_transaction: methods::send_tx::SignedTransaction = unimplemented!();
+//! let request = methods::send_tx::RpcSendTransactionRequest {
+//!     signed_transaction,
+//!     wait_until: TxExecutionStatus::ExecutedOptimistic,
+//! };
+//!
+//! let response = client.call(request).await?;
+//!
+//! assert!(response.final_execution_outcome.is_some());
+//! # Ok(())
+//! # }
+//! ```
+use super::*;
+
+pub use near_jsonrpc_primitives::types::transactions::RpcTransactionError;
+pub use near_primitives::transaction::SignedTransaction;
+pub use near_primitives::views::FinalExecutionOutcomeViewEnum;
+
+/// How far along the execution of a transaction is.
+///
+/// Each status implies the ones before it, except for [`Executed`](Self::Executed), which
+/// doesn't imply [`IncludedFinal`](Self::IncludedFinal).
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TxExecutionStatus {
+    /// The transaction was submitted, but not included in a block yet.
+    None,
+    /// The transaction was included in a block, which may not be final yet.
+    Included,
+    /// The transaction and all its receipts were executed, in blocks that may not be final yet.
+    ExecutedOptimistic,
+    /// The block the transaction was included in is final.
+    IncludedFinal,
+    /// The transaction and all its receipts were executed, in blocks that may not all be
+    /// final yet, although the block the transaction was included in is.
+    Executed,
+    /// The transaction, all its receipts, and the blocks they were executed in, are final.
+    Final,
+}
+
+/// The response to a `send_tx` call.
+///
+/// This mirrors the response of newer nearcore nodes, which reports the outcome alongside the
+/// status reached.
+#[derive(Debug, Deserialize)]
+pub struct RpcTransactionResponse {
+    /// The outcome of the transaction, if it was executed, which it isn't for the
+    /// [`None`](TxExecutionStatus::None), [`Included`](TxExecutionStatus::Included) and
+    /// [`IncludedFinal`](TxExecutionStatus::IncludedFinal) statuses.
+    #[serde(flatten)]
+    pub final_execution_outcome: Option<FinalExecutionOutcomeViewEnum>,
+    /// The execution status the transaction reached.
+    pub final_execution_status: TxExecutionStatus,
+}
+
+#[derive(Debug)]
+pub struct RpcSendTransactionRequest {
+    pub signed_transaction: SignedTransaction,
+    pub wait_until: TxExecutionStatus,
+}
+
+impl RpcHandlerResponse for RpcTransactionResponse {}
+
+impl RpcMethod for RpcSendTransactionRequest {
+    type Response = RpcTransactionResponse;
+    type Error = RpcTransactionError;
+
+    fn method_name(&self) -> &str {
+        "send_tx"
+    }
+
+    fn params(&self) -> Result<serde_json::Value, io::Error> {
+        Ok(json!({
+            "signed_tx_base64": common::serialize_signed_transaction(&self.signed_transaction)?,
+            "wait_until": self.wait_until,
+        }))
+    }
+
+    fn is_idempotent(&self) -> bool {
+        false
+    }
+
+    fn transaction_hash(&self) -> Option<near_primitives::hash::CryptoHash> {
+        Some(self.signed_transaction.get_hash())
+    }
+}
+
+impl private::Sealed for RpcSendTransactionRequest {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn included_without_outcome() {
+        let response = RpcTransactionResponse::parse(json!({
+            "final_execution_status": "INCLUDED",
+        }))
+        .unwrap();
+        assert_eq!(response.final_execution_status, TxExecutionStatus::Included);
+        assert!(response.final_execution_outcome.is_none());
+        assert_eq!(
+            serde_json::to_value(TxExecutionStatus::ExecutedOptimistic).unwrap(),
+            "EXECUTED_OPTIMISTIC"
+        );
+    }
+}
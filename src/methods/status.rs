@@ -2,6 +2,10 @@
 //!
 //! This includes information about sync status, nearcore node version, protocol version, the current set of validators, etc.
 //!
+//! Fields only returned by newer nodes, like the [`uptime_sec`](RpcStatusResponse::uptime_sec)
+//! of the node or the [`epoch_id`](StatusSyncInfo::epoch_id) it's synced to, are optional and
+//! left unset when talking to older nodes.
+//!
 //! ## Example
 //!
 //! ```
@@ -26,7 +30,63 @@ use super::*;
 
 pub use near_jsonrpc_primitives::types::status::RpcStatusError;
 
-pub type RpcStatusResponse = near_primitives::views::StatusResponse;
+pub use near_primitives::version::Version;
+pub use near_primitives::views::ValidatorInfo;
+
+use chrono::{DateTime, Utc};
+use near_crypto::PublicKey;
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::{AccountId, BlockHeight};
+use near_primitives::version::ProtocolVersion;
+
+#[derive(Debug, serde::Serialize, Deserialize)]
+pub struct RpcStatusResponse {
+    /// The version of the nearcore binary.
+    pub version: Version,
+    /// The id of the chain.
+    pub chain_id: String,
+    /// The protocol version currently active on the chain.
+    pub protocol_version: ProtocolVersion,
+    /// The latest protocol version the node supports.
+    pub latest_protocol_version: ProtocolVersion,
+    /// The address of the RPC server, if the node has one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rpc_addr: Option<String>,
+    /// The validators of the current epoch.
+    pub validators: Vec<ValidatorInfo>,
+    /// The sync status of the node.
+    pub sync_info: StatusSyncInfo,
+    /// The account id of the node, if it's a validator.
+    pub validator_account_id: Option<AccountId>,
+    /// The public key the node validates with, if it's a validator.
+    pub validator_public_key: Option<PublicKey>,
+    /// The public key the node is known by on the network.
+    pub node_public_key: Option<PublicKey>,
+    /// The number of seconds since the node started.
+    pub uptime_sec: Option<i64>,
+    /// The hash of the genesis block.
+    pub genesis_hash: Option<CryptoHash>,
+    /// Debug information about the node, only returned on request, kept as JSON.
+    pub detailed_debug_status: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, Deserialize)]
+pub struct StatusSyncInfo {
+    pub latest_block_hash: CryptoHash,
+    pub latest_block_height: BlockHeight,
+    pub latest_state_root: CryptoHash,
+    pub latest_block_time: DateTime<Utc>,
+    /// Whether the node is still catching up with the chain.
+    pub syncing: bool,
+    /// The earliest block the node has, unset for archival nodes.
+    pub earliest_block_hash: Option<CryptoHash>,
+    pub earliest_block_height: Option<BlockHeight>,
+    pub earliest_block_time: Option<DateTime<Utc>>,
+    /// The epoch of the latest block.
+    pub epoch_id: Option<CryptoHash>,
+    /// The height the epoch of the latest block started at.
+    pub epoch_start_height: Option<BlockHeight>,
+}
 
 #[derive(Debug)]
 pub struct RpcStatusRequest;
@@ -47,3 +107,70 @@ impl RpcMethod for RpcStatusRequest {
 }
 
 impl private::Sealed for RpcStatusRequest {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status() -> serde_json::Value {
+        json!({
+            "version": { "version": "1.0.0", "build": "test" },
+            "chain_id": "testnet",
+            "protocol_version": 56,
+            "latest_protocol_version": 57,
+            "validators": [{ "account_id": "node0", "is_slashed": false }],
+            "sync_info": {
+                "latest_block_hash": "11111111111111111111111111111111",
+                "latest_block_height": 100,
+                "latest_state_root": "11111111111111111111111111111111",
+                "latest_block_time": "2022-01-01T00:00:00Z",
+                "syncing": false,
+                "earliest_block_hash": null,
+                "earliest_block_height": null,
+                "earliest_block_time": null,
+            },
+            "validator_account_id": null,
+        })
+    }
+
+    #[test]
+    fn older_nodes() {
+        let status = RpcStatusResponse::parse(status()).unwrap();
+
+        assert_eq!(status.protocol_version, 56);
+        assert_eq!(status.latest_protocol_version, 57);
+        assert_eq!(status.sync_info.latest_block_height, 100);
+        assert!(status.uptime_sec.is_none());
+        assert!(status.genesis_hash.is_none());
+        assert!(status.sync_info.epoch_id.is_none());
+    }
+
+    #[test]
+    fn newer_nodes() {
+        let mut status = status();
+        status["validator_account_id"] = json!("node0");
+        status["validator_public_key"] =
+            json!("ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp");
+        status["node_public_key"] = json!("ed25519:7PGseFbWxvYVgZ89K1uTJKYoKetWs7BJtbyXDzfbAcqX");
+        status["uptime_sec"] = json!(3600);
+        status["genesis_hash"] = json!("11111111111111111111111111111111");
+        status["sync_info"]["epoch_id"] = json!("11111111111111111111111111111111");
+        status["sync_info"]["epoch_start_height"] = json!(80);
+        let status = RpcStatusResponse::parse(status).unwrap();
+
+        assert_eq!(status.validator_account_id, Some("node0".parse().unwrap()));
+        assert_eq!(
+            status.validator_public_key,
+            Some(
+                "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"
+                    .parse()
+                    .unwrap()
+            )
+        );
+        assert!(status.node_public_key.is_some());
+        assert_eq!(status.uptime_sec, Some(3600));
+        assert_eq!(status.genesis_hash, Some(CryptoHash::default()));
+        assert_eq!(status.sync_info.epoch_id, Some(CryptoHash::default()));
+        assert_eq!(status.sync_info.epoch_start_height, Some(80));
+    }
+}
@@ -65,6 +65,10 @@ impl RpcMethod for RpcTransactionStatusRequest {
             }
         })
     }
+
+    fn transaction_hash(&self) -> Option<near_primitives::hash::CryptoHash> {
+        Some(common::transaction_hash(&self.transaction_info))
+    }
 }
 
 impl private::Sealed for RpcTransactionStatusRequest {}
@@ -1,6 +1,38 @@
+//! Returns the validators of an epoch, with their stakes and how well they produced blocks and
+//! chunks.
+//!
+//! The epoch is specified with an [`EpochReference`]: the latest one, an epoch id, or any
+//! block in the epoch.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use near_jsonrpc_client::{methods, JsonRpcClient};
+//! use near_jsonrpc_client::methods::validators::EpochReference;
+//! use near_primitives::types::BlockId;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JsonRpcClient::connect("https://archival-rpc.mainnet.near.org");
+//!
+//! let request = methods::validators::RpcValidatorRequest {
+//!     epoch_reference: EpochReference::BlockId(BlockId::Height(83975000)),
+//! };
+//!
+//! let response = client.call(request).await?;
+//!
+//! println!(
+//!     "{} validators in the epoch starting at {}",
+//!     response.current_validators.len(),
+//!     response.epoch_start_height
+//! );
+//! # Ok(())
+//! # }
+//! ```
 use super::*;
 
 pub use near_jsonrpc_primitives::types::validator::{RpcValidatorError, RpcValidatorRequest};
+pub use near_primitives::types::EpochReference;
 
 pub type RpcValidatorResponse = near_primitives::views::EpochValidatorInfo;
 
@@ -15,8 +47,41 @@ impl RpcMethod for RpcValidatorRequest {
     }
 
     fn params(&self) -> Result<serde_json::Value, io::Error> {
-        Ok(json!(self))
+        // the positional form is the one every node accepts, but it has no room for epoch ids
+        Ok(match &self.epoch_reference {
+            EpochReference::Latest => json!([null]),
+            EpochReference::BlockId(block_id) => json!([block_id]),
+            EpochReference::EpochId(epoch_id) => json!({ "epoch_id": epoch_id }),
+        })
     }
 }
 
 impl private::Sealed for RpcValidatorRequest {}
+
+#[cfg(test)]
+mod tests {
+    use near_primitives::hash::CryptoHash;
+    use near_primitives::types::{BlockId, EpochId};
+
+    use super::*;
+
+    #[test]
+    fn epoch_references() {
+        let params = |epoch_reference| RpcValidatorRequest { epoch_reference }.params().unwrap();
+        assert_eq!(params(EpochReference::Latest), json!([null]));
+        assert_eq!(
+            params(EpochReference::BlockId(BlockId::Height(42))),
+            json!([42])
+        );
+        assert_eq!(
+            params(EpochReference::BlockId(
+                BlockId::Hash(CryptoHash::default())
+            )),
+            json!(["11111111111111111111111111111111"])
+        );
+        assert_eq!(
+            params(EpochReference::EpochId(EpochId(CryptoHash::default()))),
+            json!({ "epoch_id": "11111111111111111111111111111111" })
+        );
+    }
+}
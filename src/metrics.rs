@@ -0,0 +1,514 @@
+//! Call metrics.
+//!
+//! A [`MetricsSink`] configured on the client, with [`JsonRpcClient::metrics_sink`](crate::JsonRpcClient::metrics_sink),
+//! is notified whenever a call starts, succeeds or fails, by method name, along with the latency
+//! of completed calls, and the [class](ErrorClass) of the error failed calls ended with. Retries
+//! are part of the call they retry. Method errors are classified by their NEAR variant, like
+//! `unknown_block` or `invalid_transaction`, and requests the server rejected as invalid apart
+//! from its own failures, so that alerting can tell provider problems from bad requests.
+//!
+//! Every attempt at a call is also reported once the call completes, with
+//! [`MetricsSink::call_attempts`], in a single batch spanning retries and, with a
+//! [`JsonRpcMultiClient`](crate::JsonRpcMultiClient), hedges and failover to other endpoints.
+//! Each [`Attempt`] holds its index, the endpoint it was made at and its outcome, which shows
+//! which endpoints failed before the one that served the call.
+//!
+//! With the `prometheus` feature flag, `PrometheusMetrics` records these as counters and a
+//! latency histogram, in a [`prometheus::Registry`](https://docs.rs/prometheus).
+//!
+//! Separately, a [`SlowCallPolicy`], configured with
+//! [`JsonRpcClient::slow_calls`](crate::JsonRpcClient::slow_calls), reports calls taking longer
+//! than a threshold, with a `tracing` warning when the `tracing` feature flag is enabled, and
+//! with a callback of your own.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::sync::atomic::{AtomicU64, Ordering};
+//! use std::time::Duration;
+//!
+//! use near_jsonrpc_client::{metrics::{ErrorClass, MetricsSink}, JsonRpcClient};
+//!
+//! #[derive(Default)]
+//! struct FailureCounter(AtomicU64);
+//!
+//! impl MetricsSink for FailureCounter {
+//!     fn call_failed(&self, method: &str, error: ErrorClass, _latency: Duration) {
+//!         self.0.fetch_add(1, Ordering::Relaxed);
+//!         eprintln!("{} failed: {}", method, error);
+//!     }
+//! }
+//!
+//! let client = JsonRpcClient::connect("https://rpc.testnet.near.org")
+//!     .metrics_sink(FailureCounter::default());
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::errors::{JsonRpcError, JsonRpcServerError, RpcTransportError};
+use crate::methods::{RpcHandlerError, RpcMethod};
+use crate::retry::Instant;
+use crate::MethodCallResult;
+
+#[cfg(feature = "prometheus")]
+mod prometheus;
+#[cfg(feature = "prometheus")]
+pub use self::prometheus::PrometheusMetrics;
+
+/// Receives metrics about calls, see the [module-level documentation](self).
+///
+/// All methods do nothing by default.
+pub trait MetricsSink: Send + Sync + 'static {
+    /// A call to the specified method started.
+    fn call_started(&self, _method: &str) {}
+
+    /// A call to the specified method succeeded, after `latency`.
+    fn call_succeeded(&self, _method: &str, _latency: Duration) {}
+
+    /// A call to the specified method failed with an error of the specified class, after `latency`.
+    fn call_failed(&self, _method: &str, _error: ErrorClass, _latency: Duration) {}
+
+    /// A call to the specified method completed, after the specified attempts, in the order
+    /// they completed.
+    fn call_attempts(&self, _method: &str, _attempts: &[Attempt]) {}
+}
+
+/// An attempt at a call, see [`MetricsSink::call_attempts`].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Attempt {
+    /// The index of the attempt, starting at `1`, in the order the attempts started across
+    /// every endpoint tried.
+    pub index: u32,
+    /// The address of the server the attempt was made at.
+    pub server_addr: String,
+    /// The class of error the attempt failed with, or `None` if it succeeded.
+    pub error: Option<ErrorClass>,
+    /// How long the attempt took.
+    pub latency: Duration,
+}
+
+/// The attempts made at a call, shared by the clients making them.
+#[derive(Debug, Default)]
+pub(crate) struct AttemptLog {
+    next: AtomicU32,
+    attempts: Mutex<Vec<Attempt>>,
+}
+
+impl AttemptLog {
+    /// The index of the next attempt to start.
+    pub(crate) fn next_index(&self) -> u32 {
+        self.next.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    pub(crate) fn record(&self, attempt: Attempt) {
+        self.attempts.lock().unwrap().push(attempt);
+    }
+
+    /// Report the attempts made to the sink, if any.
+    pub(crate) fn report(self, sink: Option<&dyn MetricsSink>, method: &str) {
+        if let Some(sink) = sink {
+            sink.call_attempts(method, &self.attempts.into_inner().unwrap());
+        }
+    }
+}
+
+/// The class of error a call failed with, see [`MetricsSink::call_failed`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum ErrorClass {
+    /// The request couldn't be sent, or the response couldn't be read or parsed.
+    Transport,
+    /// The request timed out.
+    TimedOut,
+    /// The server is rate limiting the client.
+    RateLimited,
+    /// The endpoint's circuit is open.
+    CircuitOpen,
+    /// The server responded with an unexpected HTTP status.
+    Status,
+    /// The server rejected the request as invalid.
+    InvalidRequest,
+    /// The server failed to handle the request.
+    Server,
+    /// The method failed, with its own error type.
+    ///
+    /// Holds the name of the error's variant in snake case, like `unknown_block`, if the method
+    /// error reports it, see [`RpcHandlerError::variant_name`].
+    Handler(Option<String>),
+    /// The call didn't complete within its deadline.
+    DeadlineExceeded,
+}
+
+impl ErrorClass {
+    /// Classify the error a call failed with.
+    pub fn of<E: RpcHandlerError>(err: &JsonRpcError<E>) -> Self {
+        match err {
+            JsonRpcError::TransportError(RpcTransportError::TimedOut) => Self::TimedOut,
+            JsonRpcError::TransportError(RpcTransportError::RateLimited { .. }) => {
+                Self::RateLimited
+            }
+            JsonRpcError::TransportError(RpcTransportError::CircuitOpen) => Self::CircuitOpen,
+            JsonRpcError::TransportError(_) => Self::Transport,
+            JsonRpcError::ServerError(JsonRpcServerError::HandlerError(err)) => {
+                Self::Handler(err.variant_name().map(|name| name.to_lowercase()))
+            }
+            JsonRpcError::ServerError(JsonRpcServerError::RequestValidationError(_)) => {
+                Self::InvalidRequest
+            }
+            JsonRpcError::ServerError(JsonRpcServerError::ResponseStatusError(_)) => Self::Status,
+            JsonRpcError::ServerError(_) => Self::Server,
+            JsonRpcError::DeadlineExceeded(_) => Self::DeadlineExceeded,
+        }
+    }
+
+    /// A short snake case name for the class, suitable as a metric label.
+    ///
+    /// Method errors are named after their variant, falling back to `handler`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Transport => "transport",
+            Self::TimedOut => "timed_out",
+            Self::RateLimited => "rate_limited",
+            Self::CircuitOpen => "circuit_open",
+            Self::Status => "status",
+            Self::InvalidRequest => "invalid_request",
+            Self::Server => "server",
+            Self::Handler(Some(variant)) => variant,
+            Self::Handler(None) => "handler",
+            Self::DeadlineExceeded => "deadline_exceeded",
+        }
+    }
+}
+
+impl fmt::Display for ErrorClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A call that took longer than its threshold, see [`SlowCallPolicy`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct SlowCall<'a> {
+    /// The method called.
+    pub method: &'a str,
+    /// The params of the call, serialized and truncated to a few hundred characters.
+    ///
+    /// Signed transactions are redacted.
+    pub params: String,
+    /// The address of the server called.
+    pub server_addr: &'a str,
+    /// How long the call took.
+    pub latency: Duration,
+    /// The threshold the call went over.
+    pub threshold: Duration,
+}
+
+type SlowCallFn = dyn Fn(&SlowCall<'_>) + Send + Sync;
+
+/// Reports calls taking longer than a threshold, see the [module-level documentation](self).
+///
+/// ## Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use near_jsonrpc_client::{metrics::SlowCallPolicy, JsonRpcClient};
+///
+/// let policy = SlowCallPolicy::new(Duration::from_secs(2))
+///     .method("query", Duration::from_millis(500))
+///     .on_slow_call(|call| eprintln!("slow {} call: {:?}", call.method, call.latency));
+///
+/// let client = JsonRpcClient::connect("https://rpc.testnet.near.org").slow_calls(policy);
+/// ```
+#[derive(Clone)]
+pub struct SlowCallPolicy {
+    threshold: Duration,
+    methods: HashMap<String, Duration>,
+    hook: Option<Arc<SlowCallFn>>,
+}
+
+/// How many characters of the params are reported, see [`SlowCall::params`].
+const PARAMS_SUMMARY_LEN: usize = 256;
+
+impl SlowCallPolicy {
+    /// Report calls taking longer than the specified threshold.
+    pub fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            methods: HashMap::new(),
+            hook: None,
+        }
+    }
+
+    /// Use a different threshold for calls to the specified method.
+    pub fn method<M: Into<String>>(mut self, method: M, threshold: Duration) -> Self {
+        self.methods.insert(method.into(), threshold);
+        self
+    }
+
+    /// Call the specified function with every slow call.
+    ///
+    /// This is called on the task making the call, so it should return quickly.
+    pub fn on_slow_call<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&SlowCall<'_>) + Send + Sync + 'static,
+    {
+        self.hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// The threshold for calls to the specified method.
+    pub fn threshold(&self, method: &str) -> Duration {
+        self.methods.get(method).copied().unwrap_or(self.threshold)
+    }
+
+    /// Report the call, if it was slow.
+    pub(crate) fn check<M: RpcMethod>(&self, method: &M, server_addr: &str, latency: Duration) {
+        let threshold = self.threshold(method.method_name());
+        if latency <= threshold {
+            return;
+        }
+        // signed transactions are signable material, best kept out of the logs
+        let mut params = match method.method_name() {
+            "broadcast_tx_async" | "broadcast_tx_commit" | "send_tx" => "[redacted]".to_string(),
+            _ => method
+                .params()
+                .map(|params| params.to_string())
+                .unwrap_or_default(),
+        };
+        if let Some((end, _)) = params.char_indices().nth(PARAMS_SUMMARY_LEN) {
+            params.truncate(end);
+            params.push_str("...");
+        }
+        let call = SlowCall {
+            method: method.method_name(),
+            params,
+            server_addr,
+            latency,
+            threshold,
+        };
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            method = call.method,
+            params = %call.params,
+            server_addr = call.server_addr,
+            latency_ms = call.latency.as_millis() as u64,
+            threshold_ms = call.threshold.as_millis() as u64,
+            "slow call"
+        );
+        if let Some(ref hook) = self.hook {
+            hook(&call);
+        }
+    }
+}
+
+impl fmt::Debug for SlowCallPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlowCallPolicy")
+            .field("threshold", &self.threshold)
+            .field("methods", &self.methods)
+            .field("on_slow_call", &self.hook.is_some())
+            .finish()
+    }
+}
+
+/// Report a call to the sink, as it starts and completes.
+pub(crate) async fn metered<T, E: RpcHandlerError>(
+    sink: &dyn MetricsSink,
+    method: &str,
+    call: impl Future<Output = MethodCallResult<T, E>>,
+) -> MethodCallResult<T, E> {
+    sink.call_started(method);
+    let start = Instant::now();
+    let result = call.await;
+    match result {
+        Ok(_) => sink.call_succeeded(method, start.elapsed()),
+        Err(ref err) => sink.call_failed(method, ErrorClass::of(err), start.elapsed()),
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::methods;
+    use crate::transport::testing::{json_rpc_result, FnTransport};
+    use crate::transport::{
+        BoxFuture, Transport, TransportError, TransportRequest, TransportResponse,
+    };
+    use crate::JsonRpcClient;
+
+    /// Fails to connect while `failing` is set.
+    fn flaky_transport(failing: Arc<AtomicBool>) -> impl Transport {
+        FnTransport::new(move |_, payload| {
+            if failing.load(Ordering::SeqCst) {
+                return Err(TransportError::SendError("connection refused".into()));
+            }
+            Ok(json_rpc_result(&payload["id"], serde_json::Value::Null))
+        })
+    }
+
+    #[derive(Clone, Default)]
+    struct RecordingSink(Arc<Mutex<Vec<String>>>);
+
+    impl MetricsSink for RecordingSink {
+        fn call_started(&self, method: &str) {
+            self.0.lock().unwrap().push(format!("started {}", method));
+        }
+
+        fn call_succeeded(&self, method: &str, _latency: Duration) {
+            self.0.lock().unwrap().push(format!("succeeded {}", method));
+        }
+
+        fn call_failed(&self, method: &str, error: ErrorClass, _latency: Duration) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("failed {} {}", method, error));
+        }
+    }
+
+    #[tokio::test]
+    async fn metrics_sink() {
+        let failing = Arc::new(AtomicBool::new(false));
+        let transport = flaky_transport(failing.clone());
+        let sink = RecordingSink::default();
+        let client = JsonRpcClient::with_transport(transport)
+            .connect("http://localhost:3030")
+            .metrics_sink(sink.clone());
+
+        client
+            .call(methods::health::RpcHealthRequest)
+            .await
+            .unwrap();
+        failing.store(true, Ordering::SeqCst);
+        client
+            .call(methods::health::RpcHealthRequest)
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            *sink.0.lock().unwrap(),
+            [
+                "started health",
+                "succeeded health",
+                "started health",
+                "failed health transport",
+            ]
+        );
+    }
+
+    #[test]
+    fn error_classes() {
+        let unknown_block = JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+            methods::block::RpcBlockError::UnknownBlock {
+                error_message: "DB Not Found Error".to_string(),
+            },
+        ));
+        assert_eq!(ErrorClass::of(&unknown_block).as_str(), "unknown_block");
+
+        let timeout = JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+            methods::tx::RpcTransactionError::TimeoutError,
+        ));
+        assert_eq!(ErrorClass::of(&timeout).as_str(), "timeout_error");
+
+        let invalid = JsonRpcError::<methods::tx::RpcTransactionError>::ServerError(
+            JsonRpcServerError::RequestValidationError(
+                near_jsonrpc_primitives::errors::RpcRequestValidationErrorKind::ParseError {
+                    error_message: "invalid params".to_string(),
+                },
+            ),
+        );
+        assert_eq!(ErrorClass::of(&invalid), ErrorClass::InvalidRequest);
+    }
+
+    #[tokio::test]
+    async fn slow_calls() {
+        /// Responds after a delay.
+        struct SlowTransport;
+
+        impl Transport for SlowTransport {
+            fn send(
+                &self,
+                request: TransportRequest,
+            ) -> BoxFuture<'_, Result<TransportResponse, TransportError>> {
+                Box::pin(async move {
+                    crate::retry::sleep(Duration::from_millis(20)).await;
+                    let payload: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+                    Ok(json_rpc_result(&payload["id"], serde_json::Value::Null))
+                })
+            }
+        }
+
+        let slow = Arc::new(Mutex::new(vec![]));
+        let policy = SlowCallPolicy::new(Duration::from_secs(3600)).on_slow_call({
+            let slow = slow.clone();
+            move |call| {
+                slow.lock().unwrap().push((
+                    call.method.to_string(),
+                    call.params.clone(),
+                    call.threshold,
+                ))
+            }
+        });
+        let client = JsonRpcClient::with_transport(SlowTransport).connect("http://localhost:3030");
+
+        let client = client.slow_calls(policy.clone());
+        client
+            .call(methods::health::RpcHealthRequest)
+            .await
+            .unwrap();
+        assert!(slow.lock().unwrap().is_empty());
+
+        let client = client.slow_calls(policy.method("health", Duration::from_millis(5)));
+        client
+            .call(methods::health::RpcHealthRequest)
+            .await
+            .unwrap();
+        assert_eq!(
+            *slow.lock().unwrap(),
+            [(
+                "health".to_string(),
+                "null".to_string(),
+                Duration::from_millis(5)
+            )]
+        );
+    }
+
+    #[cfg(feature = "prometheus")]
+    #[tokio::test]
+    async fn prometheus_metrics() {
+        let registry = ::prometheus::Registry::new();
+        let client = JsonRpcClient::with_transport(flaky_transport(Default::default()))
+            .connect("http://localhost:3030")
+            .metrics_sink(PrometheusMetrics::new(&registry).unwrap());
+
+        client
+            .call(methods::health::RpcHealthRequest)
+            .await
+            .unwrap();
+
+        let families = registry.gather();
+        let value = |name: &str| {
+            families
+                .iter()
+                .find(|family| family.get_name() == name)
+                .map(|family| family.get_metric()[0].get_counter().get_value())
+        };
+        assert_eq!(value("near_jsonrpc_calls_started_total"), Some(1.0));
+        assert_eq!(value("near_jsonrpc_calls_succeeded_total"), Some(1.0));
+        // counters only show up once they have a label set
+        assert_eq!(value("near_jsonrpc_calls_failed_total"), None);
+        assert!(PrometheusMetrics::new(&registry).is_err());
+    }
+}
@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry};
+
+use super::{ErrorClass, MetricsSink};
+
+/// A [`MetricsSink`] recording calls in a [`prometheus::Registry`].
+///
+/// The following metrics are registered, all labelled by `method`:
+///   - `near_jsonrpc_calls_started_total`
+///   - `near_jsonrpc_calls_succeeded_total`
+///   - `near_jsonrpc_calls_failed_total`, also labelled by `error`, see [`ErrorClass::as_str`]
+///   - `near_jsonrpc_call_duration_seconds`, a histogram of the latency of completed calls
+///
+/// Available with the `prometheus` feature flag.
+///
+/// ## Example
+///
+/// ```
+/// use near_jsonrpc_client::{metrics::PrometheusMetrics, JsonRpcClient};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let registry = prometheus::Registry::new();
+///
+/// let client = JsonRpcClient::connect("https://rpc.testnet.near.org")
+///     .metrics_sink(PrometheusMetrics::new(&registry)?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct PrometheusMetrics {
+    started: IntCounterVec,
+    succeeded: IntCounterVec,
+    failed: IntCounterVec,
+    duration: HistogramVec,
+}
+
+impl PrometheusMetrics {
+    /// Create the metrics, and register them in the specified registry.
+    ///
+    /// Fails if metrics with the same names are already registered.
+    pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let metrics = Self {
+            started: IntCounterVec::new(
+                Opts::new(
+                    "near_jsonrpc_calls_started_total",
+                    "Number of JSON RPC calls started",
+                ),
+                &["method"],
+            )?,
+            succeeded: IntCounterVec::new(
+                Opts::new(
+                    "near_jsonrpc_calls_succeeded_total",
+                    "Number of JSON RPC calls that succeeded",
+                ),
+                &["method"],
+            )?,
+            failed: IntCounterVec::new(
+                Opts::new(
+                    "near_jsonrpc_calls_failed_total",
+                    "Number of JSON RPC calls that failed, by class of error",
+                ),
+                &["method", "error"],
+            )?,
+            duration: HistogramVec::new(
+                HistogramOpts::new(
+                    "near_jsonrpc_call_duration_seconds",
+                    "Latency of completed JSON RPC calls, retries included",
+                ),
+                &["method"],
+            )?,
+        };
+        registry.register(Box::new(metrics.started.clone()))?;
+        registry.register(Box::new(metrics.succeeded.clone()))?;
+        registry.register(Box::new(metrics.failed.clone()))?;
+        registry.register(Box::new(metrics.duration.clone()))?;
+        Ok(metrics)
+    }
+}
+
+impl MetricsSink for PrometheusMetrics {
+    fn call_started(&self, method: &str) {
+        self.started.with_label_values(&[method]).inc();
+    }
+
+    fn call_succeeded(&self, method: &str, latency: Duration) {
+        self.succeeded.with_label_values(&[method]).inc();
+        self.duration
+            .with_label_values(&[method])
+            .observe(latency.as_secs_f64());
+    }
+
+    fn call_failed(&self, method: &str, error: ErrorClass, latency: Duration) {
+        self.failed
+            .with_label_values(&[method, error.as_str()])
+            .inc();
+        self.duration
+            .with_label_values(&[method])
+            .observe(latency.as_secs_f64());
+    }
+}
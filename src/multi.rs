@@ -0,0 +1,2223 @@
+//! Failover across multiple RPC endpoints.
+//!
+//! A [`JsonRpcMultiClient`] exposes the same `call` interface as [`JsonRpcClient`], only,
+//! calls failing because of connection errors, timeouts or `5xx` responses are transparently
+//! sent again to the next endpoint, in order. So are calls to endpoints whose
+//! [circuit](crate::circuit) is open, or that are rate limiting the client.
+//!
+//! Methods that aren't safe to call more than once, like `broadcast_tx_commit`, only fail over
+//! when the request couldn't be sent at all, or was rejected by a rate limit, see [`RpcMethod::is_idempotent`](crate::methods::RpcMethod::is_idempotent).
+//!
+//! By default, every call starts at the first endpoint. Read calls can instead be spread across
+//! the endpoints with a [`LoadBalancing`] strategy, while writes keep starting at the
+//! [primary](JsonRpcMultiClient::primary) endpoint.
+//!
+//! For tail latency sensitive reads, calls can also be [hedged](JsonRpcMultiClient::hedge).
+//!
+//! Routing and retries can be adjusted for specific methods with [method policies](JsonRpcMultiClient::method_policies),
+//! and transaction status polls can be kept on the endpoint the transaction was submitted to,
+//! with [sticky routing](JsonRpcMultiClient::sticky_transactions).
+//!
+//! The client keeps rolling latency and error rate statistics for each endpoint, which
+//! [`LoadBalancing::LatencyAware`] routes by, and which can be inspected with
+//! [`JsonRpcMultiClient::endpoint_stats`].
+//!
+//! A [deadline](JsonRpcMultiClient::deadline) bounds each call as a whole, across retries,
+//! hedges and failover attempts.
+//!
+//! With [health checking](JsonRpcMultiClient::health_check), endpoints that are syncing or lagging
+//! behind are taken out of rotation until they recover. Endpoints whose writes keep failing while
+//! reads succeed can be [degraded to read-only](JsonRpcMultiClient::read_only_degradation).
+//!
+//! ## Example
+//!
+//! ```
+//! use near_jsonrpc_client::{methods, JsonRpcMultiClient};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JsonRpcMultiClient::connect([
+//!     "https://rpc.testnet.near.org",
+//!     "https://archival-rpc.testnet.near.org",
+//! ]);
+//!
+//! let server_status = client.call(methods::status::RpcStatusRequest).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::future::{select, Either};
+use near_primitives::hash::CryptoHash;
+use thiserror::Error;
+
+use crate::circuit::CircuitState;
+
+use crate::errors::{
+    CallAttempt, DeadlineExceededError, JsonRpcError, JsonRpcTransportSendError, RpcTransportError,
+};
+use crate::layer::Layer;
+use crate::metrics::{AttemptLog, ErrorClass, MetricsSink, SlowCallPolicy};
+use crate::rate_limit::RateLimiter;
+use crate::request_id::IdGenerator;
+use crate::retry::Instant;
+use crate::retry::RetryPolicy;
+use crate::{methods, CallMeta, CallOptions, JsonRpcClient, MethodCallResult};
+
+#[cfg(any(
+    feature = "reqwest-transport",
+    feature = "hyper-transport",
+    feature = "isahc-transport"
+))]
+use crate::AsUrl;
+
+/// How read calls are spread across endpoints, see [`JsonRpcMultiClient::load_balancing`].
+///
+/// Whichever endpoint a call starts at, it fails over to the others in order of preference.
+#[derive(Clone, Debug)]
+pub enum LoadBalancing {
+    /// Start every call at the primary endpoint.
+    Primary,
+    /// Start each call at the next endpoint, in turn.
+    RoundRobin,
+    /// Start calls at each endpoint in proportion to its weight, one weight per endpoint.
+    ///
+    /// Endpoints with a weight of `0` are only used for failover.
+    Weighted(Vec<u32>),
+    /// Start each call at the healthiest of two endpoints picked at random, going by their
+    /// [stats](JsonRpcMultiClient::endpoint_stats) and their [circuit](crate::circuit), if any.
+    ///
+    /// Each percent of failed calls counts as an extra 10 milliseconds of latency.
+    LatencyAware,
+}
+
+impl Default for LoadBalancing {
+    fn default() -> Self {
+        Self::Primary
+    }
+}
+
+/// Weight of the latest sample in the rolling statistics.
+const EWMA_WEIGHT: f64 = 0.2;
+
+/// Latency penalty for an endpoint failing every call, in seconds.
+const ERROR_PENALTY: f64 = 1.0;
+
+/// How many of the latest calls the latency percentiles are computed over.
+const LATENCY_SAMPLES: usize = 256;
+
+/// Rolling statistics about the calls to an endpoint, see [`JsonRpcMultiClient::endpoint_stats`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct EndpointStats {
+    /// The address of the endpoint.
+    pub server_addr: String,
+    /// Exponentially weighted moving average of the call latency, if the endpoint was called.
+    pub latency: Option<Duration>,
+    /// Exponentially weighted moving average of the failure rate, between `0` and `1`.
+    ///
+    /// Connection failures, timeouts and `5xx` responses count as failures.
+    pub error_rate: f64,
+    /// Total number of calls sent to the endpoint.
+    pub calls: u64,
+    /// Total number of failed calls.
+    pub failures: u64,
+    /// Total number of calls that returned an error, by class of error, whether or not they
+    /// count as failures of the endpoint.
+    pub errors: HashMap<ErrorClass, u64>,
+    /// Median latency of the latest calls, if the endpoint was called.
+    pub p50_latency: Option<Duration>,
+    /// 99th percentile latency of the latest calls, if the endpoint was called.
+    pub p99_latency: Option<Duration>,
+    /// Whether the endpoint passed its last [health check](JsonRpcMultiClient::health_check).
+    pub healthy: bool,
+    /// Whether the endpoint was evicted by its last [health check](JsonRpcMultiClient::health_check),
+    /// for lagging behind the other endpoints.
+    pub stale: bool,
+    /// The latest block height the endpoint reported in its last health check, if any.
+    pub latest_block_height: Option<u64>,
+    /// Whether the endpoint is accepting writes, see [`JsonRpcMultiClient::read_only_degradation`].
+    pub mode: EndpointMode,
+    write_failures: u32,
+    read_failed: bool,
+    samples: VecDeque<Duration>,
+}
+
+impl EndpointStats {
+    fn new(server_addr: String) -> Self {
+        Self {
+            server_addr,
+            latency: None,
+            error_rate: 0.0,
+            calls: 0,
+            failures: 0,
+            errors: HashMap::new(),
+            p50_latency: None,
+            p99_latency: None,
+            healthy: true,
+            stale: false,
+            latest_block_height: None,
+            mode: EndpointMode::ReadWrite,
+            write_failures: 0,
+            read_failed: false,
+            samples: VecDeque::with_capacity(LATENCY_SAMPLES),
+        }
+    }
+
+    fn record(&mut self, latency: Duration, failed: bool) {
+        self.latency = Some(match self.latency {
+            Some(average) => average.mul_f64(1.0 - EWMA_WEIGHT) + latency.mul_f64(EWMA_WEIGHT),
+            None => latency,
+        });
+        let sample = if failed { 1.0 } else { 0.0 };
+        self.error_rate = self.error_rate * (1.0 - EWMA_WEIGHT) + sample * EWMA_WEIGHT;
+        self.calls += 1;
+        self.failures += failed as u64;
+
+        if self.samples.len() == LATENCY_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(latency);
+    }
+
+    /// Fill in the latency percentiles, which are only computed when the stats are read.
+    fn with_percentiles(mut self) -> Self {
+        let mut samples: Vec<_> = self.samples.iter().copied().collect();
+        samples.sort_unstable();
+        let percentile = |q: f64| {
+            let index = ((q * samples.len() as f64).ceil() as usize).max(1) - 1;
+            samples.get(index).copied()
+        };
+        self.p50_latency = percentile(0.5);
+        self.p99_latency = percentile(0.99);
+        self
+    }
+
+    fn record_error(&mut self, class: ErrorClass) {
+        *self.errors.entry(class).or_default() += 1;
+    }
+
+    /// Record the outcome of a call for the endpoint's mode, returning the change
+    /// of mode, if any.
+    fn record_mode(
+        &mut self,
+        write: bool,
+        failed: bool,
+        write_failures: u32,
+    ) -> Option<EndpointModeChange> {
+        if !write {
+            self.read_failed = failed;
+        } else if failed {
+            self.write_failures += 1;
+        } else {
+            self.write_failures = 0;
+        }
+        let from = self.mode;
+        self.mode = match from {
+            EndpointMode::ReadWrite
+                if self.write_failures >= write_failures && !self.read_failed =>
+            {
+                EndpointMode::ReadOnly
+            }
+            EndpointMode::ReadOnly if self.write_failures == 0 => EndpointMode::ReadWrite,
+            mode => mode,
+        };
+        (self.mode != from).then(|| EndpointModeChange {
+            server_addr: self.server_addr.clone(),
+            from,
+            to: self.mode,
+        })
+    }
+
+    /// Expected cost of a call, in seconds. Unknown endpoints are optimistically free.
+    fn cost(&self) -> f64 {
+        self.latency.map_or(0.0, |latency| latency.as_secs_f64()) + self.error_rate * ERROR_PENALTY
+    }
+}
+
+/// Whether an endpoint is accepting writes, see [`JsonRpcMultiClient::read_only_degradation`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EndpointMode {
+    /// Reads and writes are routed to the endpoint as usual.
+    ReadWrite,
+    /// Writes keep failing on the endpoint, while reads succeed. Reads are still routed to it,
+    /// but writes only fall back to it once the other endpoints failed.
+    ReadOnly,
+}
+
+/// An endpoint changed mode, see [`JsonRpcMultiClient::on_mode_change`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct EndpointModeChange {
+    /// The address of the endpoint.
+    pub server_addr: String,
+    /// The mode the endpoint was in.
+    pub from: EndpointMode,
+    /// The mode the endpoint is now in.
+    pub to: EndpointMode,
+}
+
+/// How calls to a class of methods are routed and retried, see [`JsonRpcMultiClient::method_policies`].
+#[derive(Clone, Debug, Default)]
+pub struct MethodPolicy {
+    primary_only: bool,
+    retry_policy: Option<RetryPolicy>,
+}
+
+impl MethodPolicy {
+    /// Create a new method policy, routing calls like any other, with the endpoints' own
+    /// retry policies.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only send calls to the primary endpoint, without load balancing, hedging or failover.
+    pub fn primary_only(mut self) -> Self {
+        self.primary_only = true;
+        self
+    }
+
+    /// Retry calls according to the specified policy, instead of the endpoints' own.
+    ///
+    /// This is still subject to whether the method is [idempotent](crate::methods::RpcMethod::is_idempotent).
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Don't retry calls, whatever the endpoints' own retry policies.
+    pub fn no_retries(self) -> Self {
+        self.retry_policy(RetryPolicy::new().max_attempts(1))
+    }
+}
+
+type ClassifyFn = dyn Fn(&str) -> Option<MethodPolicy> + Send + Sync;
+
+/// Picks the policy for calls to a method, by its name.
+#[derive(Clone)]
+struct MethodClassifier(Arc<ClassifyFn>);
+
+impl fmt::Debug for MethodClassifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MethodClassifier")
+    }
+}
+
+type ModeChangeFn = dyn Fn(&EndpointModeChange) + Send + Sync;
+
+/// Observes endpoints changing mode.
+#[derive(Clone)]
+struct ModeChangeHook(Arc<ModeChangeFn>);
+
+impl fmt::Debug for ModeChangeHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ModeChangeHook")
+    }
+}
+
+/// Receives the attempts at every call, see [`JsonRpcMultiClient::metrics_sink`].
+#[derive(Clone)]
+struct Metrics(Arc<dyn MetricsSink>);
+
+impl fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MetricsSink")
+    }
+}
+
+/// The endpoints transactions were submitted to, see [`JsonRpcMultiClient::sticky_transactions`].
+#[derive(Clone, Debug)]
+struct StickyRoutes {
+    window: Duration,
+    routes: Arc<Mutex<HashMap<CryptoHash, (usize, Instant)>>>,
+}
+
+impl StickyRoutes {
+    fn get(&self, hash: &CryptoHash) -> Option<usize> {
+        match self.routes.lock().unwrap().get(hash) {
+            Some((index, submitted_at)) if submitted_at.elapsed() < self.window => Some(*index),
+            _ => None,
+        }
+    }
+
+    fn insert(&self, hash: CryptoHash, index: usize) {
+        let mut routes = self.routes.lock().unwrap();
+        routes.retain(|_, (_, submitted_at)| submitted_at.elapsed() < self.window);
+        routes.insert(hash, (index, Instant::now()));
+    }
+}
+
+/// A NEAR JSON RPC Client, failing over across multiple endpoints.
+///
+/// See the [module-level documentation](self) for more details.
+#[derive(Clone, Debug)]
+pub struct JsonRpcMultiClient {
+    clients: Vec<JsonRpcClient>,
+    load_balancing: LoadBalancing,
+    primary: usize,
+    next: Arc<AtomicUsize>,
+    stats: Arc<Vec<Mutex<EndpointStats>>>,
+    hedge_delay: Option<Duration>,
+    deadline: Option<Duration>,
+    method_policies: Option<MethodClassifier>,
+    sticky: Option<StickyRoutes>,
+    read_only_after: Option<u32>,
+    on_mode_change: Option<ModeChangeHook>,
+    metrics: Option<Metrics>,
+}
+
+impl JsonRpcMultiClient {
+    /// Connect to multiple JSON RPC servers using the default connector, in order of preference.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if no server address is specified.
+    #[cfg(any(
+        feature = "reqwest-transport",
+        feature = "hyper-transport",
+        feature = "isahc-transport"
+    ))]
+    pub fn connect<U: AsUrl, I: IntoIterator<Item = U>>(server_addrs: I) -> Self {
+        Self::new(server_addrs.into_iter().map(JsonRpcClient::connect))
+    }
+
+    /// Create a client failing over across the specified clients, in order of preference.
+    ///
+    /// This is useful for configuring each endpoint separately, e.g with its own
+    /// headers or [retry policy](JsonRpcClient::retry_policy).
+    ///
+    /// ## Panics
+    ///
+    /// Panics if no client is specified.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use near_jsonrpc_client::{retry::RetryPolicy, JsonRpcClient, JsonRpcMultiClient};
+    ///
+    /// let client = JsonRpcMultiClient::new([
+    ///     JsonRpcClient::connect("https://rpc.testnet.near.org").retry_policy(RetryPolicy::new()),
+    ///     JsonRpcClient::connect("https://archival-rpc.testnet.near.org"),
+    /// ]);
+    /// ```
+    pub fn new<I: IntoIterator<Item = JsonRpcClient>>(clients: I) -> Self {
+        let clients: Vec<_> = clients.into_iter().collect();
+        assert!(
+            !clients.is_empty(),
+            "a multi client needs at least one endpoint"
+        );
+        let stats = clients
+            .iter()
+            .map(|client| Mutex::new(EndpointStats::new(client.server_addr().to_string())))
+            .collect();
+        Self {
+            stats: Arc::new(stats),
+            clients,
+            load_balancing: LoadBalancing::default(),
+            primary: 0,
+            next: Default::default(),
+            hedge_delay: None,
+            deadline: None,
+            method_policies: None,
+            sticky: None,
+            read_only_after: None,
+            on_mode_change: None,
+            metrics: None,
+        }
+    }
+
+    /// Spread read calls across the endpoints according to the specified strategy.
+    ///
+    /// Calls to methods that aren't [idempotent](crate::methods::RpcMethod::is_idempotent),
+    /// like `broadcast_tx_commit`, always start at the primary endpoint.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if [`LoadBalancing::Weighted`] doesn't specify exactly one weight per endpoint,
+    /// or if all the weights are `0`.
+    ///
+    /// ## Example
+    ///
+    /// Sending three quarters of the reads to a self-hosted node:
+    ///
+    /// ```
+    /// use near_jsonrpc_client::{multi::LoadBalancing, JsonRpcMultiClient};
+    ///
+    /// let client = JsonRpcMultiClient::connect(["https://rpc.testnet.near.org", "http://localhost:3030"])
+    ///     .load_balancing(LoadBalancing::Weighted(vec![1, 3]));
+    /// ```
+    pub fn load_balancing(mut self, load_balancing: LoadBalancing) -> Self {
+        if let LoadBalancing::Weighted(ref weights) = load_balancing {
+            assert_eq!(
+                weights.len(),
+                self.clients.len(),
+                "expected one weight per endpoint"
+            );
+            assert!(
+                weights.iter().any(|weight| *weight > 0),
+                "expected at least one non-zero weight"
+            );
+        }
+        self.load_balancing = load_balancing;
+        self
+    }
+
+    /// Set the endpoint writes start at, by its index, defaults to the first one.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn primary(mut self, index: usize) -> Self {
+        assert!(index < self.clients.len(), "primary endpoint out of bounds");
+        self.primary = index;
+        self
+    }
+
+    /// Hedge read calls that haven't completed within the specified delay, by sending
+    /// the same request to the next endpoint, and going with whichever responds first.
+    ///
+    /// This trades extra load on the endpoints for lower tail latency. Calls to methods
+    /// that aren't [idempotent](crate::methods::RpcMethod::is_idempotent) are never hedged.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use near_jsonrpc_client::JsonRpcMultiClient;
+    ///
+    /// let client = JsonRpcMultiClient::connect([
+    ///     "https://rpc.testnet.near.org",
+    ///     "https://archival-rpc.testnet.near.org",
+    /// ])
+    /// .hedge(Duration::from_millis(300));
+    /// ```
+    pub fn hedge(mut self, delay: Duration) -> Self {
+        self.hedge_delay = Some(delay);
+        self
+    }
+
+    /// Bound the total time spent on a call, across all its retries, hedges and failover
+    /// attempts.
+    ///
+    /// Each endpoint is only given what's left of the deadline, and calls running out of time
+    /// fail with a [`DeadlineExceeded`](JsonRpcError::DeadlineExceeded) error, carrying the
+    /// attempts made across all endpoints. This can be overridden for a single call with
+    /// [`CallOptions::deadline`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use near_jsonrpc_client::{retry::RetryPolicy, JsonRpcMultiClient};
+    ///
+    /// let client = JsonRpcMultiClient::connect([
+    ///     "https://rpc.testnet.near.org",
+    ///     "https://archival-rpc.testnet.near.org",
+    /// ])
+    /// .deadline(Duration::from_secs(2));
+    /// ```
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Route and retry calls differently depending on the method, according to the policy
+    /// the classifier returns for the method name.
+    ///
+    /// Methods the classifier returns `None` for are handled as usual.
+    ///
+    /// ## Example
+    ///
+    /// Sending transactions to the primary endpoint only, without retrying them, while
+    /// retrying reads more aggressively:
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use near_jsonrpc_client::{multi::MethodPolicy, retry::RetryPolicy, JsonRpcMultiClient};
+    ///
+    /// let client = JsonRpcMultiClient::connect([
+    ///     "https://rpc.testnet.near.org",
+    ///     "https://archival-rpc.testnet.near.org",
+    /// ])
+    /// .method_policies(|method| match method {
+    ///     "broadcast_tx_async" | "broadcast_tx_commit" => {
+    ///         Some(MethodPolicy::new().primary_only().no_retries())
+    ///     }
+    ///     "query" | "block" | "tx" => Some(MethodPolicy::new().retry_policy(
+    ///         RetryPolicy::new().max_attempts(5).base_delay(Duration::from_millis(50)),
+    ///     )),
+    ///     _ => None,
+    /// });
+    /// ```
+    pub fn method_policies<F>(mut self, classifier: F) -> Self
+    where
+        F: Fn(&str) -> Option<MethodPolicy> + Send + Sync + 'static,
+    {
+        self.method_policies = Some(MethodClassifier(Arc::new(classifier)));
+        self
+    }
+
+    /// Route the status polls for a transaction to the endpoint it was submitted to, for the
+    /// specified window after its submission.
+    ///
+    /// Freshly submitted transactions may not be visible on the other endpoints yet, so
+    /// calls to `tx` and `EXPERIMENTAL_tx_status` for a transaction submitted with
+    /// `broadcast_tx_async` or `broadcast_tx_commit` start at the endpoint that accepted it,
+    /// and only fail over to the others as usual.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use near_jsonrpc_client::JsonRpcMultiClient;
+    ///
+    /// let client = JsonRpcMultiClient::connect([
+    ///     "https://rpc.testnet.near.org",
+    ///     "https://archival-rpc.testnet.near.org",
+    /// ])
+    /// .sticky_transactions(Duration::from_secs(30));
+    /// ```
+    pub fn sticky_transactions(mut self, window: Duration) -> Self {
+        self.sticky = Some(StickyRoutes {
+            window,
+            routes: Default::default(),
+        });
+        self
+    }
+
+    /// Degrade endpoints to [read-only](EndpointMode::ReadOnly) once writes, like
+    /// `broadcast_tx_commit`, fail on them the specified number of times in a row, while
+    /// reads keep succeeding.
+    ///
+    /// Read-only endpoints keep serving reads, but writes are directed to the other endpoints
+    /// first, only falling back to read-only ones as a last resort. A write succeeding on a
+    /// read-only endpoint restores it. Changes of mode can be observed with
+    /// [`on_mode_change`](Self::on_mode_change).
+    ///
+    /// Values below 1 are treated as 1.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use near_jsonrpc_client::JsonRpcMultiClient;
+    ///
+    /// let client = JsonRpcMultiClient::connect([
+    ///     "https://rpc.testnet.near.org",
+    ///     "https://archival-rpc.testnet.near.org",
+    /// ])
+    /// .read_only_degradation(3)
+    /// .on_mode_change(|change| {
+    ///     println!("{} is now {:?}", change.server_addr, change.to);
+    /// });
+    /// ```
+    pub fn read_only_degradation(mut self, write_failures: u32) -> Self {
+        self.read_only_after = Some(write_failures.max(1));
+        self
+    }
+
+    /// Call the specified hook whenever an endpoint changes mode, see
+    /// [`read_only_degradation`](Self::read_only_degradation).
+    pub fn on_mode_change<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&EndpointModeChange) + Send + Sync + 'static,
+    {
+        self.on_mode_change = Some(ModeChangeHook(Arc::new(hook)));
+        self
+    }
+
+    /// Limit the rate of requests sent to each endpoint separately, according to the
+    /// specified rate limiter.
+    ///
+    /// Each endpoint gets its own bucket, with the limiter's configuration. To share a
+    /// single limit across endpoints instead, configure the same limiter on each client with
+    /// [`JsonRpcClient::rate_limiter`], and pass them to [`JsonRpcMultiClient::new`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use near_jsonrpc_client::{rate_limit::RateLimiter, JsonRpcMultiClient};
+    ///
+    /// let client = JsonRpcMultiClient::connect([
+    ///     "https://rpc.testnet.near.org",
+    ///     "https://archival-rpc.testnet.near.org",
+    /// ])
+    /// .rate_limiter(RateLimiter::new(10.0).burst(20));
+    /// ```
+    pub fn rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        for client in &mut self.clients {
+            client.rate_limiter = Some(limiter.unshared());
+        }
+        self
+    }
+
+    /// Report the calls made to every endpoint to the specified metrics sink.
+    ///
+    /// Each endpoint tried is reported as a call of its own, while the
+    /// [attempts](MetricsSink::call_attempts) across endpoints are reported together, once per
+    /// call, see the [`metrics`](crate::metrics) module for more details.
+    pub fn metrics_sink<S: MetricsSink>(mut self, sink: S) -> Self {
+        let sink: Arc<dyn MetricsSink> = Arc::new(sink);
+        for client in &mut self.clients {
+            client.metrics = Some(sink.clone());
+        }
+        self.metrics = Some(Metrics(sink));
+        self
+    }
+
+    /// Report calls to every endpoint taking longer than the thresholds of the specified
+    /// policy, see [`JsonRpcClient::slow_calls`].
+    ///
+    /// Each endpoint tried is checked separately.
+    pub fn slow_calls(mut self, policy: SlowCallPolicy) -> Self {
+        for client in &mut self.clients {
+            client.slow_calls = Some(policy.clone());
+        }
+        self
+    }
+
+    /// Generate the JSON RPC request ids of calls to every endpoint with the specified
+    /// generator, see [`JsonRpcClient::id_generator`].
+    ///
+    /// Each endpoint tried gets an id of its own.
+    pub fn id_generator<G: IdGenerator>(mut self, generator: G) -> Self {
+        let generator: Arc<dyn IdGenerator> = Arc::new(generator);
+        for client in &mut self.clients {
+            client.id_generator = Some(generator.clone());
+        }
+        self
+    }
+
+    /// Add a layer of middleware around the transport of every endpoint, inside the layers
+    /// already added.
+    ///
+    /// The same layer is shared across endpoints, see the [`layer`](crate::layer) module for
+    /// more details.
+    pub fn layer<L: Layer>(mut self, layer: L) -> Self {
+        let layer: Arc<dyn Layer> = Arc::new(layer);
+        for client in &mut self.clients {
+            client.layers.push(layer.clone());
+        }
+        self
+    }
+
+    /// Get the statistics for each endpoint, in order of preference.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use near_jsonrpc_client::JsonRpcMultiClient;
+    ///
+    /// let client = JsonRpcMultiClient::connect([
+    ///     "https://rpc.testnet.near.org",
+    ///     "https://archival-rpc.testnet.near.org",
+    /// ]);
+    ///
+    /// for stats in client.endpoint_stats() {
+    ///     println!(
+    ///         "{}: {} calls, {} failed, p99 {:?}",
+    ///         stats.server_addr, stats.calls, stats.failures, stats.p99_latency
+    ///     );
+    /// }
+    /// ```
+    pub fn endpoint_stats(&self) -> Vec<EndpointStats> {
+        self.stats
+            .iter()
+            .map(|stats| {
+                // sorting the samples happens after letting go of the lock
+                let stats = stats.lock().unwrap().clone();
+                stats.with_percentiles()
+            })
+            .collect()
+    }
+
+    /// Check that every endpoint is on the same network, returning its chain id.
+    ///
+    /// This calls `status` on every endpoint, failing with [`ValidationError::Mismatch`] if they
+    /// don't all report the same chain id, as mixing up e.g. `testnet` and `mainnet` endpoints
+    /// would otherwise make for inconsistent responses. Meant to be called before first use.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use near_jsonrpc_client::JsonRpcMultiClient;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = JsonRpcMultiClient::connect([
+    ///     "https://rpc.testnet.near.org",
+    ///     "https://archival-rpc.testnet.near.org",
+    /// ]);
+    ///
+    /// assert_eq!(client.validate_endpoints().await?, "testnet");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn validate_endpoints(&self) -> Result<String, ValidationError> {
+        let statuses = futures_util::future::join_all(
+            self.clients
+                .iter()
+                .map(|client| client.call(methods::status::RpcStatusRequest)),
+        )
+        .await;
+
+        let mut chain_ids = Vec::with_capacity(statuses.len());
+        for (client, status) in self.clients.iter().zip(statuses) {
+            match status {
+                Ok(status) => chain_ids.push((client.server_addr().to_string(), status.chain_id)),
+                Err(error) => {
+                    return Err(ValidationError::Call {
+                        server_addr: client.server_addr().to_string(),
+                        error,
+                    })
+                }
+            }
+        }
+        let (_, chain_id) = &chain_ids[0];
+        if chain_ids.iter().any(|(_, other)| other != chain_id) {
+            return Err(ValidationError::Mismatch(ChainIdMismatchError {
+                chain_ids,
+            }));
+        }
+        Ok(chain_ids.swap_remove(0).1)
+    }
+
+    /// Check the health of every endpoint once, see [`health_check`](Self::health_check).
+    pub async fn check_health(&self, max_block_lag: u64) {
+        let statuses = futures_util::future::join_all(
+            self.clients
+                .iter()
+                .map(|client| client.call(methods::status::RpcStatusRequest)),
+        )
+        .await;
+
+        let heights: Vec<_> = statuses
+            .iter()
+            .map(|status| match status {
+                Ok(status) if !status.sync_info.syncing => {
+                    Some(status.sync_info.latest_block_height)
+                }
+                _ => None,
+            })
+            .collect();
+
+        // the height reached by at least half of the synced endpoints
+        let mut synced: Vec<_> = heights.iter().flatten().copied().collect();
+        synced.sort_unstable_by(|a, b| b.cmp(a));
+        let quorum_height = synced
+            .get(synced.len().saturating_sub(1) / 2)
+            .copied()
+            .unwrap_or(0);
+
+        for ((stats, height), status) in self.stats.iter().zip(heights).zip(statuses) {
+            let mut stats = stats.lock().unwrap();
+            stats.stale = height.map_or(false, |height| {
+                quorum_height.saturating_sub(height) > max_block_lag
+            });
+            stats.healthy = height.is_some() && !stats.stale;
+            stats.latest_block_height = status
+                .ok()
+                .map(|status| status.sync_info.latest_block_height);
+        }
+    }
+
+    /// Periodically check the health of every endpoint.
+    ///
+    /// Endpoints failing to respond to a `status` call, or reporting that they're syncing, are
+    /// marked as unhealthy. Unhealthy endpoints are only called once every healthy endpoint
+    /// has failed, until they pass a health check again.
+    ///
+    /// Endpoints that respond, but lag more than `max_block_lag` blocks behind the height
+    /// reached by at least half of the synced endpoints, are marked as [stale](EndpointStats::stale)
+    /// instead, and evicted: they aren't called at all, as their responses would be outdated.
+    /// Each health check probes them again, re-admitting them once they catch up.
+    ///
+    /// The returned future runs forever, and is meant to be spawned in the background,
+    /// on the executor of your choice. The health checks stop when it's dropped.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use near_jsonrpc_client::JsonRpcMultiClient;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let client = JsonRpcMultiClient::connect([
+    ///     "https://rpc.testnet.near.org",
+    ///     "https://archival-rpc.testnet.near.org",
+    /// ]);
+    ///
+    /// let health_check = tokio::spawn(client.health_check(Duration::from_secs(10), 10));
+    /// # health_check.abort();
+    /// # }
+    /// ```
+    pub fn health_check(
+        &self,
+        interval: Duration,
+        max_block_lag: u64,
+    ) -> impl std::future::Future<Output = ()> + Send + 'static {
+        let client = self.clone();
+        async move {
+            loop {
+                client.check_health(max_block_lag).await;
+                futures_timer::Delay::new(interval).await;
+            }
+        }
+    }
+
+    fn method_policy(&self, method_name: &str) -> Option<MethodPolicy> {
+        (self.method_policies.as_ref()?.0)(method_name)
+    }
+
+    /// The endpoint a transaction status poll should start at, if it's sticky.
+    fn sticky_route<M: methods::RpcMethod>(&self, method: &M) -> Option<usize> {
+        match (&self.sticky, method.is_idempotent()) {
+            (Some(sticky), true) => sticky.get(&method.transaction_hash()?),
+            _ => None,
+        }
+    }
+
+    fn is_stale(&self, index: usize) -> bool {
+        self.stats[index].lock().unwrap().stale
+    }
+
+    fn is_read_only(&self, index: usize) -> bool {
+        self.stats[index].lock().unwrap().mode == EndpointMode::ReadOnly
+    }
+
+    fn is_healthy(&self, index: usize) -> bool {
+        self.stats[index].lock().unwrap().healthy
+    }
+
+    fn cost(&self, index: usize) -> f64 {
+        let client = &self.clients[index];
+        let open = client.circuit_breaker.as_ref().map_or(false, |breaker| {
+            breaker.state(client.server_addr()) == CircuitState::Open
+        });
+        if open || !self.is_healthy(index) {
+            return f64::INFINITY;
+        }
+        self.stats[index].lock().unwrap().cost()
+    }
+
+    /// Pick the endpoint a read call starts at.
+    fn select(&self) -> usize {
+        match self.load_balancing {
+            LoadBalancing::Primary => self.primary,
+            LoadBalancing::RoundRobin => {
+                self.next.fetch_add(1, Ordering::Relaxed) % self.clients.len()
+            }
+            LoadBalancing::Weighted(ref weights) => {
+                let total = weights.iter().map(|weight| *weight as usize).sum::<usize>();
+                let mut point = self.next.fetch_add(1, Ordering::Relaxed) % total;
+                weights
+                    .iter()
+                    .position(|weight| match point.checked_sub(*weight as usize) {
+                        Some(rest) => {
+                            point = rest;
+                            false
+                        }
+                        None => true,
+                    })
+                    .unwrap_or(self.primary)
+            }
+            LoadBalancing::LatencyAware => {
+                let mut candidates = rand::seq::index::sample(
+                    &mut rand::thread_rng(),
+                    self.clients.len(),
+                    2.min(self.clients.len()),
+                )
+                .into_iter();
+                let first = candidates.next().unwrap_or(self.primary);
+                candidates.fold(first, |best, other| {
+                    if self.cost(other) < self.cost(best) {
+                        other
+                    } else {
+                        best
+                    }
+                })
+            }
+        }
+    }
+
+    /// Get the clients for each endpoint, in order of preference.
+    pub fn clients(&self) -> &[JsonRpcClient] {
+        &self.clients
+    }
+
+    /// Drop the pooled connections to all the endpoints, see
+    /// [`JsonRpcClient::refresh_connections`].
+    pub fn refresh_connections(&self) {
+        for client in &self.clients {
+            client.refresh_connections();
+        }
+    }
+
+    /// RPC method executor for the client.
+    pub async fn call<M>(&self, method: M) -> MethodCallResult<M::Response, M::Error>
+    where
+        M: methods::RpcMethod,
+    {
+        self.call_with_options(method, CallOptions::default()).await
+    }
+
+    /// RPC method executor for the client, with options applying to each attempt at this call.
+    ///
+    /// See [`JsonRpcClient::call_with_options`].
+    pub async fn call_with_options<M>(
+        &self,
+        method: M,
+        options: CallOptions,
+    ) -> MethodCallResult<M::Response, M::Error>
+    where
+        M: methods::RpcMethod,
+    {
+        self.execute(&method, options)
+            .await
+            .map(|(response, _)| response)
+    }
+
+    /// RPC method executor for the client, also returning metadata about the call,
+    /// including the endpoint that served it.
+    ///
+    /// The [latency](CallMeta::latency) accounts for every endpoint tried, while the
+    /// [attempts](CallMeta::attempts) are those at the endpoint that served the call.
+    pub async fn call_with_meta<M>(
+        &self,
+        method: M,
+    ) -> MethodCallResult<(M::Response, CallMeta), M::Error>
+    where
+        M: methods::RpcMethod,
+    {
+        let start = Instant::now();
+        self.execute(&method, CallOptions::default())
+            .await
+            .map(|(response, meta)| {
+                let latency = start.elapsed();
+                (response, CallMeta { latency, ..meta })
+            })
+    }
+
+    async fn execute<M>(
+        &self,
+        method: &M,
+        options: CallOptions,
+    ) -> MethodCallResult<(M::Response, CallMeta), M::Error>
+    where
+        M: methods::RpcMethod,
+    {
+        let log = AttemptLog::default();
+        let call = self.execute_logged(method, options, &log);
+        #[cfg(feature = "tracing")]
+        let call = tracing::Instrument::instrument(
+            call,
+            tracing::debug_span!("rpc_multi_call", method = method.method_name()),
+        );
+        let result = call.await;
+        log.report(
+            self.metrics.as_ref().map(|metrics| &*metrics.0),
+            method.method_name(),
+        );
+        result
+    }
+
+    async fn execute_logged<M>(
+        &self,
+        method: &M,
+        options: CallOptions,
+        log: &AttemptLog,
+    ) -> MethodCallResult<(M::Response, CallMeta), M::Error>
+    where
+        M: methods::RpcMethod,
+    {
+        let idempotent = method.is_idempotent();
+        let policy = self.method_policy(method.method_name());
+        let primary_only = policy.as_ref().map_or(false, |policy| policy.primary_only);
+        let (mut order, options) = match policy {
+            Some(MethodPolicy {
+                primary_only,
+                retry_policy,
+            }) => (
+                if primary_only {
+                    vec![self.primary]
+                } else {
+                    self.order(idempotent)
+                },
+                CallOptions {
+                    retry_policy: options.retry_policy.or(retry_policy),
+                    ..options
+                },
+            ),
+            None => (self.order(idempotent), options),
+        };
+        // status polls start where the transaction was submitted, even if it's out of rotation
+        if let Some(index) = self.sticky_route(method).filter(|_| !primary_only) {
+            order.retain(|i| *i != index);
+            order.insert(0, index);
+        }
+        let hedge_delay = self.hedge_delay.filter(|_| idempotent && order.len() > 1);
+        let mut order = order.into_iter().peekable();
+
+        let start = Instant::now();
+        let deadline = options
+            .deadline
+            .or(self.deadline)
+            .and_then(|deadline| start.checked_add(deadline));
+        let mut attempts = vec![];
+
+        if let Some(delay) = hedge_delay {
+            if let (Some(first), Some(second)) = (order.next(), order.next()) {
+                match self
+                    .hedged((first, second), delay, method, &options, deadline, log)
+                    .await
+                {
+                    Err(err) if order.peek().is_some() && should_fail_over(&err, idempotent) => {
+                        if deadline.is_some() {
+                            attempts.push(CallAttempt {
+                                error: err,
+                                elapsed: start.elapsed(),
+                            });
+                        }
+                    }
+                    result => return result,
+                }
+            }
+        }
+
+        while let Some(index) = order.next() {
+            let offset = start.elapsed();
+            let options = match options.within(deadline) {
+                Some(options) => options,
+                None => {
+                    return Err(JsonRpcError::DeadlineExceeded(DeadlineExceededError {
+                        attempts,
+                    }))
+                }
+            };
+            match self.attempt(index, method, options, log).await {
+                Err(err) if order.peek().is_some() && should_fail_over(&err, idempotent) => {
+                    if deadline.is_some() {
+                        attempts.push(CallAttempt {
+                            error: err,
+                            elapsed: start.elapsed(),
+                        });
+                    }
+                }
+                // carry the attempts made against the previous endpoints along
+                Err(JsonRpcError::DeadlineExceeded(err)) => {
+                    attempts.extend(err.attempts.into_iter().map(|attempt| CallAttempt {
+                        elapsed: offset + attempt.elapsed,
+                        ..attempt
+                    }));
+                    return Err(JsonRpcError::DeadlineExceeded(DeadlineExceededError {
+                        attempts,
+                    }));
+                }
+                result => return result,
+            }
+        }
+        unreachable!("at least one client")
+    }
+
+    /// The order in which endpoints should be tried for a call.
+    fn order(&self, idempotent: bool) -> Vec<usize> {
+        let first = if idempotent {
+            self.select()
+        } else {
+            self.primary
+        };
+        let mut order: Vec<_> = std::iter::once(first)
+            .chain((0..self.clients.len()).filter(|i| *i != first))
+            .collect();
+        // stale endpoints are evicted, unhealthy ones, and read-only ones for writes,
+        // are a last resort
+        order.retain(|index| !self.is_stale(*index));
+        order.sort_by_key(|index| {
+            (
+                !idempotent && self.is_read_only(*index),
+                !self.is_healthy(*index),
+            )
+        });
+        order
+    }
+
+    /// Send the same read call to multiple endpoints at once, and check that they
+    /// all agree on the response.
+    ///
+    /// Endpoints failing with connection errors, timeouts or `5xx` responses are
+    /// replaced by the next ones, in order. Any other error is returned as-is.
+    ///
+    /// Responses are compared in their serialized form, see [`call_quorum_by`](Self::call_quorum_by)
+    /// for comparing only parts of them.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the quorum is `0`, or larger than the number of endpoints.
+    pub async fn call_quorum<M>(
+        &self,
+        method: M,
+        quorum: usize,
+    ) -> Result<M::Response, QuorumError<M::Error, serde_json::Value>>
+    where
+        M: methods::RpcMethod,
+        M::Response: serde::Serialize,
+    {
+        self.call_quorum_by(method, quorum, |response| {
+            serde_json::to_value(response).unwrap_or(serde_json::Value::Null)
+        })
+        .await
+    }
+
+    /// Send the same read call to multiple endpoints at once, and check that they all
+    /// agree on the key extracted from their responses.
+    ///
+    /// See [`call_quorum`](Self::call_quorum) for more details.
+    ///
+    /// ## Example
+    ///
+    /// Checking that endpoints agree on the hash of the final block:
+    ///
+    /// ```no_run
+    /// use near_jsonrpc_client::{methods, multi::QuorumError, JsonRpcMultiClient};
+    /// use near_primitives::types::{BlockReference, Finality};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = JsonRpcMultiClient::connect([
+    ///     "https://rpc.mainnet.near.org",
+    ///     "https://rpc.mainnet.example.com",
+    ///     "https://near-rpc.example.org",
+    /// ]);
+    ///
+    /// let request = methods::block::RpcBlockRequest {
+    ///     block_reference: BlockReference::Finality(Finality::Final),
+    /// };
+    ///
+    /// match client.call_quorum_by(request, 2, |block| block.header.hash).await {
+    ///     Ok(block) => println!("final block: {}", block.header.hash),
+    ///     Err(QuorumError::Mismatch(mismatch)) => {
+    ///         for (server_addr, hash) in mismatch.responses {
+    ///             println!("{} reported {}", server_addr, hash);
+    ///         }
+    ///     }
+    ///     Err(QuorumError::Call(err)) => return Err(err.into()),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn call_quorum_by<M, K, F>(
+        &self,
+        method: M,
+        quorum: usize,
+        key: F,
+    ) -> Result<M::Response, QuorumError<M::Error, K>>
+    where
+        M: methods::RpcMethod,
+        K: PartialEq,
+        F: Fn(&M::Response) -> K,
+    {
+        let log = AttemptLog::default();
+        let result = self.quorum_logged(&method, quorum, key, &log).await;
+        log.report(
+            self.metrics.as_ref().map(|metrics| &*metrics.0),
+            method.method_name(),
+        );
+        result
+    }
+
+    async fn quorum_logged<M, K, F>(
+        &self,
+        method: &M,
+        quorum: usize,
+        key: F,
+        log: &AttemptLog,
+    ) -> Result<M::Response, QuorumError<M::Error, K>>
+    where
+        M: methods::RpcMethod,
+        K: PartialEq,
+        F: Fn(&M::Response) -> K,
+    {
+        assert!(
+            quorum > 0 && quorum <= self.clients.len(),
+            "the quorum must be between 1 and the number of endpoints"
+        );
+
+        let mut order = self.order(true);
+        // stale endpoints only make up the numbers
+        order.extend((0..self.clients.len()).filter(|index| self.is_stale(*index)));
+        let mut order = order.into_iter();
+        let mut responses = vec![];
+        let mut last_err = None;
+        let deadline = self
+            .deadline
+            .and_then(|deadline| Instant::now().checked_add(deadline));
+        while responses.len() < quorum {
+            let round: Vec<_> = order.by_ref().take(quorum - responses.len()).collect();
+            if round.is_empty() {
+                // not enough endpoints left to reach the quorum
+                return Err(QuorumError::Call(last_err.expect("a failed endpoint")));
+            }
+            let options = CallOptions {
+                retry_policy: self
+                    .method_policy(method.method_name())
+                    .and_then(|policy| policy.retry_policy),
+                ..Default::default()
+            };
+            let options = options.within(deadline).ok_or_else(|| {
+                QuorumError::Call(JsonRpcError::DeadlineExceeded(DeadlineExceededError {
+                    attempts: vec![],
+                }))
+            })?;
+            let results = futures_util::future::join_all(
+                round
+                    .into_iter()
+                    .map(|index| self.attempt(index, method, options.clone(), log)),
+            )
+            .await;
+            for result in results {
+                match result {
+                    Ok((response, meta)) => responses.push((meta.server_addr, response)),
+                    Err(err) if should_fail_over(&err, true) => last_err = Some(err),
+                    Err(err) => return Err(QuorumError::Call(err)),
+                }
+            }
+        }
+
+        let mut responses = responses
+            .into_iter()
+            .map(|(server_addr, response)| (server_addr, key(&response), response));
+        let (server_addr, first_key, first) = responses.next().expect("at least one response");
+        let rest: Vec<_> = responses.collect();
+        if rest.iter().all(|(_, key, _)| *key == first_key) {
+            return Ok(first);
+        }
+        Err(QuorumError::Mismatch(QuorumMismatchError {
+            responses: std::iter::once((server_addr, first_key))
+                .chain(
+                    rest.into_iter()
+                        .map(|(server_addr, key, _)| (server_addr, key)),
+                )
+                .collect(),
+        }))
+    }
+
+    /// Call an endpoint, hedging with another one if it doesn't respond in time.
+    async fn hedged<M>(
+        &self,
+        (first, second): (usize, usize),
+        delay: Duration,
+        method: &M,
+        options: &CallOptions,
+        deadline: Option<Instant>,
+        log: &AttemptLog,
+    ) -> MethodCallResult<(M::Response, CallMeta), M::Error>
+    where
+        M: methods::RpcMethod,
+    {
+        let primary = match options.within(deadline) {
+            Some(options) => self.attempt(first, method, options, log),
+            None => {
+                return Err(JsonRpcError::DeadlineExceeded(DeadlineExceededError {
+                    attempts: vec![],
+                }))
+            }
+        };
+        let timer = futures_timer::Delay::new(delay);
+        futures_util::pin_mut!(primary, timer);
+
+        let primary = match select(primary, timer).await {
+            Either::Left((result, _)) => match (result, options.within(deadline)) {
+                (Err(err), Some(options)) if should_fail_over(&err, true) => {
+                    return self.attempt(second, method, options, log).await
+                }
+                (result, _) => return result,
+            },
+            Either::Right((_, primary)) => primary,
+        };
+
+        let options = match options.within(deadline) {
+            Some(options) => options,
+            // out of time to hedge
+            None => return primary.await,
+        };
+        let hedge = self.attempt(second, method, options, log);
+        futures_util::pin_mut!(hedge);
+
+        // go with the first response, unless it's a failure of its endpoint
+        match select(primary, hedge).await {
+            Either::Left((Err(err), hedge)) if should_fail_over(&err, true) => hedge.await,
+            Either::Right((Err(err), primary)) if should_fail_over(&err, true) => primary.await,
+            Either::Left((result, _)) | Either::Right((result, _)) => result,
+        }
+    }
+
+    /// Call a single endpoint, keeping track of its stats.
+    async fn attempt<M>(
+        &self,
+        index: usize,
+        method: &M,
+        options: CallOptions,
+        log: &AttemptLog,
+    ) -> MethodCallResult<(M::Response, CallMeta), M::Error>
+    where
+        M: methods::RpcMethod,
+    {
+        let start = Instant::now();
+        // boxed, so that the futures of each client's layers don't add up on the stack
+        let result = Box::pin(self.clients[index].execute_logged(method, options, log)).await;
+        let failed = match result {
+            Err(JsonRpcError::TransportError(RpcTransportError::CircuitOpen)) => None,
+            Err(ref err) => Some(err.is_endpoint_failure()),
+            Ok(_) => Some(false),
+        };
+        if let Some(failed) = failed {
+            let change = {
+                let mut stats = self.stats[index].lock().unwrap();
+                stats.record(start.elapsed(), failed);
+                if let Err(ref err) = result {
+                    stats.record_error(ErrorClass::of(err));
+                }
+                self.read_only_after.and_then(|write_failures| {
+                    stats.record_mode(!method.is_idempotent(), failed, write_failures)
+                })
+            };
+            if let (Some(change), Some(hook)) = (change, &self.on_mode_change) {
+                (hook.0)(&change);
+            }
+        }
+        // the transaction reached the endpoint, unless the call could fail over
+        if let (Some(sticky), false) = (&self.sticky, method.is_idempotent()) {
+            match result {
+                Err(ref err) if should_fail_over(err, false) => {}
+                _ => {
+                    if let Some(hash) = method.transaction_hash() {
+                        sticky.insert(hash, index);
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Endpoints disagreed on the response to a quorum call, see [`JsonRpcMultiClient::call_quorum`].
+#[derive(Debug, Error)]
+#[error("endpoints disagreed on the response to the call")]
+pub struct QuorumMismatchError<K> {
+    /// The address of each endpoint that responded, along with the key of its response.
+    pub responses: Vec<(String, K)>,
+}
+
+/// An error from a quorum call, see [`JsonRpcMultiClient::call_quorum`].
+#[derive(Debug, Error)]
+pub enum QuorumError<E, K> {
+    #[error(transparent)]
+    Call(JsonRpcError<E>),
+    #[error(transparent)]
+    Mismatch(QuorumMismatchError<K>),
+}
+
+/// Endpoints reported different chain ids, see [`JsonRpcMultiClient::validate_endpoints`].
+#[derive(Debug, Error)]
+#[error("endpoints reported different chain ids: {chain_ids:?}")]
+pub struct ChainIdMismatchError {
+    /// The address of each endpoint, along with the chain id it reported.
+    pub chain_ids: Vec<(String, String)>,
+}
+
+/// An error from validating the endpoints, see [`JsonRpcMultiClient::validate_endpoints`].
+#[derive(Debug, Error)]
+pub enum ValidationError {
+    #[error("failed to get the status of [{server_addr}]: {error}")]
+    Call {
+        server_addr: String,
+        error: JsonRpcError<methods::status::RpcStatusError>,
+    },
+    #[error(transparent)]
+    Mismatch(ChainIdMismatchError),
+}
+
+fn should_fail_over<E>(err: &JsonRpcError<E>, idempotent: bool) -> bool {
+    match err {
+        JsonRpcError::TransportError(RpcTransportError::CircuitOpen)
+        | JsonRpcError::TransportError(RpcTransportError::RateLimited { .. })
+        | JsonRpcError::TransportError(RpcTransportError::SendError(
+            JsonRpcTransportSendError::PayloadSendError(_),
+        )) => true,
+        err => idempotent && err.is_endpoint_failure(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::errors::{JsonRpcServerError, JsonRpcServerResponseStatusError};
+    use crate::transport::testing::{json_rpc_result, FnTransport};
+    use crate::transport::{
+        BoxFuture, Transport, TransportError, TransportRequest, TransportResponse,
+    };
+
+    /// Responds to each endpoint with its own status, recording the endpoints called.
+    fn endpoints_transport(
+        statuses: Vec<(&'static str, http::StatusCode)>,
+    ) -> (impl Transport, Arc<Mutex<Vec<String>>>) {
+        let called = Arc::new(Mutex::new(vec![]));
+        let transport = FnTransport::new({
+            let called = called.clone();
+            move |request: &TransportRequest, payload: serde_json::Value| {
+                called.lock().unwrap().push(request.server_addr.clone());
+                let status = statuses
+                    .iter()
+                    .find(|(addr, _)| *addr == request.server_addr)
+                    .map_or(http::StatusCode::OK, |(_, status)| *status);
+                Ok(match status {
+                    http::StatusCode::OK => {
+                        json_rpc_result(&payload["id"], serde_json::Value::Null)
+                    }
+                    status => TransportResponse::new(status, vec![]),
+                })
+            }
+        });
+        (transport, called)
+    }
+
+    fn multi_client(
+        statuses: Vec<(&'static str, http::StatusCode)>,
+    ) -> (JsonRpcMultiClient, Arc<Mutex<Vec<String>>>) {
+        let (transport, called) = endpoints_transport(statuses);
+        let connector = JsonRpcClient::with_transport(transport);
+        let client = JsonRpcMultiClient::new(
+            ["http://a", "http://b", "http://c"]
+                .iter()
+                .map(|addr| connector.connect(*addr)),
+        );
+        (client, called)
+    }
+
+    #[tokio::test]
+    async fn fails_over() {
+        let (client, called) = multi_client(vec![
+            ("http://a", http::StatusCode::BAD_GATEWAY),
+            ("http://b", http::StatusCode::SERVICE_UNAVAILABLE),
+        ]);
+
+        let response = client
+            .call_with_meta(methods::health::RpcHealthRequest)
+            .await;
+
+        assert!(
+            matches!(response, Ok((_, ref meta)) if meta.url == "http://c"),
+            "expected a response from the last endpoint, found [{:?}]",
+            response
+        );
+        assert_eq!(
+            *called.lock().unwrap(),
+            ["http://a", "http://b", "http://c"]
+        );
+    }
+
+    #[tokio::test]
+    async fn attempts() {
+        /// Records the attempts at every call.
+        #[derive(Clone, Default)]
+        struct AttemptsSink(Arc<Mutex<Vec<Vec<crate::metrics::Attempt>>>>);
+
+        impl MetricsSink for AttemptsSink {
+            fn call_attempts(&self, _method: &str, attempts: &[crate::metrics::Attempt]) {
+                self.0.lock().unwrap().push(attempts.to_vec());
+            }
+        }
+
+        let sink = AttemptsSink::default();
+        let (client, _) = multi_client(vec![("http://a", http::StatusCode::BAD_GATEWAY)]);
+        let client = client.metrics_sink(sink.clone());
+
+        client
+            .call_with_options(
+                methods::health::RpcHealthRequest,
+                CallOptions::new().retry_policy(
+                    RetryPolicy::new()
+                        .max_attempts(2)
+                        .base_delay(Duration::ZERO),
+                ),
+            )
+            .await
+            .unwrap();
+
+        // reported once for the call as a whole, not for each endpoint
+        let calls = sink.0.lock().unwrap();
+        assert_eq!(calls.len(), 1);
+        let attempts: Vec<_> = calls[0]
+            .iter()
+            .map(|attempt| {
+                (
+                    attempt.index,
+                    attempt.server_addr.as_str(),
+                    attempt.error.as_ref().map(ErrorClass::as_str),
+                )
+            })
+            .collect();
+        assert_eq!(
+            attempts,
+            [
+                (1, "http://a", Some("status")),
+                (2, "http://a", Some("status")),
+                (3, "http://b", None),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn returns_non_endpoint_errors() {
+        let (client, called) = multi_client(vec![("http://a", http::StatusCode::UNAUTHORIZED)]);
+
+        let response = client.call(methods::health::RpcHealthRequest).await;
+
+        assert!(
+            matches!(
+                response,
+                Err(JsonRpcError::ServerError(
+                    JsonRpcServerError::ResponseStatusError(
+                        JsonRpcServerResponseStatusError::Unauthorized
+                    )
+                ))
+            ),
+            "expected an unauthorized error, found [{:?}]",
+            response
+        );
+        assert_eq!(*called.lock().unwrap(), ["http://a"]);
+    }
+
+    async fn served_by(client: &JsonRpcMultiClient, calls: usize) -> Vec<String> {
+        let mut served = vec![];
+        for _ in 0..calls {
+            let (_, meta) = client
+                .call_with_meta(methods::health::RpcHealthRequest)
+                .await
+                .unwrap();
+            served.push(meta.url);
+        }
+        served
+    }
+
+    #[tokio::test]
+    async fn round_robin() {
+        let (client, _) = multi_client(vec![]);
+        let client = client.load_balancing(LoadBalancing::RoundRobin);
+
+        assert_eq!(
+            served_by(&client, 4).await,
+            ["http://a", "http://b", "http://c", "http://a"]
+        );
+    }
+
+    #[tokio::test]
+    async fn weighted() {
+        let (client, called) = multi_client(vec![("http://b", http::StatusCode::BAD_GATEWAY)]);
+        let client = client.load_balancing(LoadBalancing::Weighted(vec![1, 0, 3]));
+
+        assert_eq!(
+            served_by(&client, 4).await,
+            ["http://a", "http://c", "http://c", "http://c"]
+        );
+        // `b` is only for failover, and isn't healthy anyway
+        assert!(!called.lock().unwrap().iter().any(|addr| addr == "http://b"));
+    }
+
+    #[tokio::test]
+    async fn writes_start_at_primary() {
+        let (client, called) = multi_client(vec![("http://b", http::StatusCode::BAD_GATEWAY)]);
+        let client = client.load_balancing(LoadBalancing::RoundRobin).primary(1);
+
+        let signed_transaction = near_primitives::transaction::SignedTransaction::new(
+            near_crypto::Signature::empty(near_crypto::KeyType::ED25519),
+            near_primitives::transaction::Transaction {
+                signer_id: "alice.near".parse().unwrap(),
+                public_key: near_crypto::PublicKey::empty(near_crypto::KeyType::ED25519),
+                nonce: 1,
+                receiver_id: "bob.near".parse().unwrap(),
+                block_hash: Default::default(),
+                actions: vec![],
+            },
+        );
+
+        let response = client
+            .call(methods::broadcast_tx_async::RpcBroadcastTxAsyncRequest { signed_transaction })
+            .await;
+
+        // the primary's `5xx` isn't failed over, the transaction may have been received
+        assert!(response.is_err());
+        assert_eq!(*called.lock().unwrap(), ["http://b"]);
+    }
+
+    #[tokio::test]
+    async fn latency_aware() {
+        // delays responses from `http://a`
+        let connector = JsonRpcClient::with_transport(FnTransport::new(|request, payload| {
+            if request.server_addr == "http://a" {
+                std::thread::sleep(std::time::Duration::from_millis(20));
+            }
+            Ok(json_rpc_result(&payload["id"], serde_json::Value::Null))
+        }));
+        // with two endpoints, both are always candidates
+        let client = JsonRpcMultiClient::new(
+            ["http://a", "http://b"]
+                .iter()
+                .map(|addr| connector.connect(*addr)),
+        )
+        .load_balancing(LoadBalancing::LatencyAware);
+
+        let served = served_by(&client, 10).await;
+
+        // once `a` has been measured, `b` is always preferred
+        assert!(served[2..].iter().all(|addr| addr == "http://b"));
+
+        let stats = client.endpoint_stats();
+        assert_eq!(stats[1].server_addr, "http://b");
+        assert!(stats[0].latency.unwrap() > stats[1].latency.unwrap());
+        assert_eq!(stats[0].calls + stats[1].calls, 10);
+        assert_eq!(stats[1].failures, 0);
+    }
+
+    #[test]
+    fn endpoint_stats() {
+        let mut stats = EndpointStats::new("http://a".to_string());
+        stats.record(Duration::from_millis(100), false);
+        assert_eq!(stats.latency, Some(Duration::from_millis(100)));
+
+        stats.record(Duration::from_millis(200), true);
+        assert_eq!(stats.latency, Some(Duration::from_millis(120)));
+        assert!((stats.error_rate - EWMA_WEIGHT).abs() < f64::EPSILON);
+        assert_eq!((stats.calls, stats.failures), (2, 1));
+
+        // a 20% error rate costs as much as 200ms of latency
+        assert!((stats.cost() - 0.32).abs() < 1e-9);
+
+        for millis in 1..=98 {
+            stats.record(Duration::from_millis(millis), false);
+        }
+        let percentiles = stats.clone().with_percentiles();
+        assert_eq!(percentiles.p50_latency, Some(Duration::from_millis(50)));
+        assert_eq!(percentiles.p99_latency, Some(Duration::from_millis(100)));
+
+        // only the latest calls count
+        for _ in 0..LATENCY_SAMPLES {
+            stats.record(Duration::from_millis(1), false);
+        }
+        assert_eq!(
+            stats.clone().with_percentiles().p99_latency,
+            Some(Duration::from_millis(1))
+        );
+
+        stats.record_error(ErrorClass::TimedOut);
+        stats.record_error(ErrorClass::TimedOut);
+        stats.record_error(ErrorClass::Handler(None));
+        assert_eq!(stats.errors[&ErrorClass::TimedOut], 2);
+        assert_eq!(stats.errors[&ErrorClass::Handler(None)], 1);
+    }
+
+    #[tokio::test]
+    async fn hedged() {
+        /// Stalls responses from `http://a`.
+        struct StallingTransport<T>(T);
+
+        impl<T: Transport> Transport for StallingTransport<T> {
+            fn send(
+                &self,
+                request: TransportRequest,
+            ) -> BoxFuture<'_, Result<TransportResponse, TransportError>> {
+                Box::pin(async move {
+                    if request.server_addr == "http://a" {
+                        futures_timer::Delay::new(std::time::Duration::from_secs(5)).await;
+                    }
+                    self.0.send(request).await
+                })
+            }
+        }
+
+        let (inner, called) = endpoints_transport(vec![]);
+        let connector = JsonRpcClient::with_transport(StallingTransport(inner));
+        let client = JsonRpcMultiClient::new(
+            ["http://a", "http://b"]
+                .iter()
+                .map(|addr| connector.connect(*addr)),
+        )
+        .hedge(Duration::from_millis(20));
+
+        let start = Instant::now();
+        let (_, meta) = client
+            .call_with_meta(methods::health::RpcHealthRequest)
+            .await
+            .unwrap();
+
+        assert_eq!(meta.url, "http://b");
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert_eq!(*called.lock().unwrap(), ["http://b"]);
+    }
+
+    #[tokio::test]
+    async fn method_policies() {
+        let (client, called) =
+            multi_client(vec![("http://a", http::StatusCode::SERVICE_UNAVAILABLE)]);
+
+        // sent to the primary only, without retries
+        let primary_only = client.clone().method_policies(|method| match method {
+            "health" => Some(MethodPolicy::new().primary_only().no_retries()),
+            _ => None,
+        });
+        let response = primary_only.call(methods::health::RpcHealthRequest).await;
+        assert!(response.is_err());
+        assert_eq!(*called.lock().unwrap(), ["http://a"]);
+
+        // retried on each endpoint before failing over
+        called.lock().unwrap().clear();
+        let retried = client.method_policies(|method| match method {
+            "health" => Some(
+                MethodPolicy::new()
+                    .retry_policy(RetryPolicy::new().base_delay(Duration::from_millis(1))),
+            ),
+            _ => None,
+        });
+        let response = retried.call(methods::health::RpcHealthRequest).await;
+        assert!(response.is_ok());
+        assert_eq!(
+            *called.lock().unwrap(),
+            ["http://a", "http://a", "http://a", "http://b"]
+        );
+    }
+
+    #[tokio::test]
+    async fn deadline() {
+        use crate::retry::RetryPolicy;
+
+        let (transport, called) = endpoints_transport(vec![
+            ("http://a", http::StatusCode::SERVICE_UNAVAILABLE),
+            ("http://b", http::StatusCode::SERVICE_UNAVAILABLE),
+        ]);
+        let connector = JsonRpcClient::with_transport(transport);
+        let client = JsonRpcMultiClient::new([
+            connector.connect("http://a"),
+            connector.connect("http://b").retry_policy(
+                RetryPolicy::new()
+                    .max_attempts(100)
+                    .base_delay(Duration::from_millis(30))
+                    .jitter(false),
+            ),
+            connector.connect("http://c"),
+        ])
+        .deadline(Duration::from_millis(100));
+
+        let start = Instant::now();
+        let response = client.call(methods::health::RpcHealthRequest).await;
+
+        // b retries within what's left of the deadline, instead of starting over
+        let history = match response {
+            Err(JsonRpcError::DeadlineExceeded(err)) => err.attempts,
+            response => panic!("expected a deadline exceeded error, found [{:?}]", response),
+        };
+        assert!(start.elapsed() < Duration::from_millis(150));
+        let called = called.lock().unwrap();
+        assert_eq!(called[0], "http://a");
+        assert!(called.len() > 2 && called[1..].iter().all(|addr| addr == "http://b"));
+        // the history covers the attempts against both endpoints
+        assert_eq!(history.len(), called.len());
+        assert!(history
+            .windows(2)
+            .all(|attempts| attempts[0].elapsed <= attempts[1].elapsed));
+    }
+
+    /// Responds to each endpoint with its own result.
+    fn results_transport(results: Vec<(&'static str, serde_json::Value)>) -> impl Transport {
+        FnTransport::new(move |request, payload| Ok(endpoint_result(&results, request, &payload)))
+    }
+
+    /// Responds with the endpoint's result, or with `502 Bad Gateway` if it has none.
+    fn endpoint_result(
+        results: &[(&'static str, serde_json::Value)],
+        request: &TransportRequest,
+        payload: &serde_json::Value,
+    ) -> TransportResponse {
+        match results
+            .iter()
+            .find(|(addr, _)| *addr == request.server_addr)
+        {
+            Some((_, result)) => json_rpc_result(&payload["id"], result.clone()),
+            None => TransportResponse::new(http::StatusCode::BAD_GATEWAY, vec![]),
+        }
+    }
+
+    #[cfg(feature = "any")]
+    #[tokio::test]
+    async fn quorum() {
+        let results = vec![
+            ("http://b", serde_json::json!({ "height": 10, "hash": "x" })),
+            ("http://c", serde_json::json!({ "height": 10, "hash": "x" })),
+            ("http://d", serde_json::json!({ "height": 9, "hash": "y" })),
+        ];
+        // every endpoint redirects, mismatches are still reported by the configured address
+        let connector = JsonRpcClient::with_transport(FnTransport::new(move |request, payload| {
+            Ok(endpoint_result(&results, request, &payload)
+                .with_url(format!("{}/redirected", request.server_addr)))
+        }));
+        let client = JsonRpcMultiClient::new(
+            ["http://a", "http://b", "http://c", "http://d"]
+                .iter()
+                .map(|addr| connector.connect(*addr)),
+        );
+        let request = || {
+            methods::any::<Result<serde_json::Value, serde_json::Value>>(
+                "block",
+                serde_json::json!({ "finality": "final" }),
+            )
+        };
+
+        // `a` is down, and replaced by `c`
+        let block = client.call_quorum(request(), 2).await.unwrap();
+        assert_eq!(block["hash"], "x");
+
+        // only three endpoints are up
+        let err = match client
+            .call_quorum_by(request(), 4, |block| block["hash"].clone())
+            .await
+        {
+            Err(QuorumError::Call(err)) => err,
+            result => panic!("expected a call error, found [{:?}]", result),
+        };
+        assert!(err.is_retryable());
+
+        let mismatch = match client
+            .call_quorum_by(request(), 3, |block| block["hash"].clone())
+            .await
+        {
+            Err(QuorumError::Mismatch(mismatch)) => mismatch,
+            result => panic!("expected a mismatch, found [{:?}]", result),
+        };
+        assert_eq!(
+            mismatch.responses,
+            [
+                ("http://b".to_string(), serde_json::json!("x")),
+                ("http://c".to_string(), serde_json::json!("x")),
+                ("http://d".to_string(), serde_json::json!("y")),
+            ]
+        );
+    }
+
+    /// A `status` result, reporting the specified height.
+    fn status(height: u64, syncing: bool) -> serde_json::Value {
+        serde_json::json!({
+            "version": { "version": "1.0.0", "build": "test" },
+            "chain_id": "testnet",
+            "protocol_version": 1,
+            "latest_protocol_version": 1,
+            "validators": [],
+            "sync_info": {
+                "latest_block_hash": "11111111111111111111111111111111",
+                "latest_block_height": height,
+                "latest_state_root": "11111111111111111111111111111111",
+                "latest_block_time": "2022-01-01T00:00:00Z",
+                "syncing": syncing,
+                "earliest_block_hash": null,
+                "earliest_block_height": null,
+                "earliest_block_time": null,
+            },
+            "validator_account_id": null,
+        })
+    }
+
+    #[tokio::test]
+    async fn health_check() {
+        let connector = JsonRpcClient::with_transport(results_transport(vec![
+            ("http://a", status(100, true)),
+            ("http://b", status(80, false)),
+            ("http://c", status(98, false)),
+            ("http://d", status(97, false)),
+        ]));
+        let client = JsonRpcMultiClient::new(
+            ["http://a", "http://b", "http://c", "http://d", "http://e"]
+                .iter()
+                .map(|addr| connector.connect(*addr)),
+        );
+
+        client.check_health(2).await;
+
+        let healthy: Vec<_> = client
+            .endpoint_stats()
+            .iter()
+            .map(|stats| stats.healthy)
+            .collect();
+        assert_eq!(healthy, [false, false, true, true, false]);
+        let stale: Vec<_> = client
+            .endpoint_stats()
+            .iter()
+            .map(|stats| stats.stale)
+            .collect();
+        assert_eq!(stale, [false, true, false, false, false]);
+        assert_eq!(client.endpoint_stats()[1].latest_block_height, Some(80));
+
+        let (_, meta) = client
+            .call_with_meta(methods::status::RpcStatusRequest)
+            .await
+            .unwrap();
+        assert_eq!(meta.url, "http://c");
+    }
+
+    #[tokio::test]
+    async fn validate_endpoints() {
+        let mainnet = {
+            let mut status = status(100, false);
+            status["chain_id"] = "mainnet".into();
+            status
+        };
+        let connector = JsonRpcClient::with_transport(results_transport(vec![
+            ("http://a", status(100, false)),
+            ("http://b", status(100, false)),
+            ("http://c", mainnet),
+        ]));
+        let client = JsonRpcMultiClient::new(
+            ["http://a", "http://b"]
+                .iter()
+                .map(|addr| connector.connect(*addr)),
+        );
+        assert_eq!(client.validate_endpoints().await.unwrap(), "testnet");
+
+        let client = JsonRpcMultiClient::new(
+            ["http://a", "http://b", "http://c"]
+                .iter()
+                .map(|addr| connector.connect(*addr)),
+        );
+        match client.validate_endpoints().await {
+            Err(ValidationError::Mismatch(err)) => assert_eq!(
+                err.chain_ids,
+                [
+                    ("http://a".to_string(), "testnet".to_string()),
+                    ("http://b".to_string(), "testnet".to_string()),
+                    ("http://c".to_string(), "mainnet".to_string()),
+                ]
+            ),
+            result => panic!("expected a chain id mismatch, found [{:?}]", result),
+        }
+    }
+
+    #[tokio::test]
+    async fn evicts_stale_endpoints() {
+        let heights = Arc::new(Mutex::new(vec![
+            ("http://a", 50),
+            ("http://b", 100),
+            ("http://c", 100),
+        ]));
+        // reports the current height of each endpoint
+        let connector = JsonRpcClient::with_transport(FnTransport::new({
+            let heights = heights.clone();
+            move |request, payload| {
+                let results: Vec<_> = heights
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .map(|(addr, height)| (*addr, status(*height, false)))
+                    .collect();
+                Ok(endpoint_result(&results, request, &payload))
+            }
+        }));
+        let client = JsonRpcMultiClient::new(
+            ["http://a", "http://b", "http://c"]
+                .iter()
+                .map(|addr| connector.connect(*addr)),
+        );
+
+        // `a` responds, but is too far behind to be called at all
+        client.check_health(10).await;
+        assert_eq!(client.order(false), [1, 2]);
+        let (_, meta) = client
+            .call_with_meta(methods::status::RpcStatusRequest)
+            .await
+            .unwrap();
+        assert_eq!(meta.url, "http://b");
+
+        // until it catches up
+        heights.lock().unwrap()[0].1 = 95;
+        client.check_health(10).await;
+        assert!(client.endpoint_stats().iter().all(|stats| !stats.stale));
+        let (_, meta) = client
+            .call_with_meta(methods::status::RpcStatusRequest)
+            .await
+            .unwrap();
+        assert_eq!(meta.url, "http://a");
+    }
+
+    #[tokio::test]
+    async fn sticky_transactions() {
+        use near_primitives::transaction::{SignedTransaction, Transaction};
+
+        let signed_transaction = SignedTransaction::new(
+            near_crypto::Signature::empty(near_crypto::KeyType::ED25519),
+            Transaction {
+                signer_id: "alice.near".parse().unwrap(),
+                public_key: near_crypto::PublicKey::empty(near_crypto::KeyType::ED25519),
+                nonce: 1,
+                receiver_id: "bob.near".parse().unwrap(),
+                block_hash: Default::default(),
+                actions: vec![],
+            },
+        );
+        let hash = serde_json::to_value(signed_transaction.get_hash()).unwrap();
+        let connector = JsonRpcClient::with_transport(results_transport(vec![
+            ("http://a", hash.clone()),
+            ("http://b", hash),
+        ]));
+        // reads start at `a`, writes at `b`
+        let client = JsonRpcMultiClient::new(
+            ["http://a", "http://b"]
+                .iter()
+                .map(|addr| connector.connect(*addr)),
+        )
+        .load_balancing(LoadBalancing::Weighted(vec![1, 0]))
+        .primary(1)
+        .sticky_transactions(Duration::from_millis(50));
+
+        let status_request = methods::tx::RpcTransactionStatusRequest {
+            transaction_info: methods::tx::TransactionInfo::TransactionId {
+                hash: signed_transaction.get_hash(),
+                account_id: "alice.near".parse().unwrap(),
+            },
+        };
+        let calls = || -> Vec<_> {
+            client
+                .endpoint_stats()
+                .iter()
+                .map(|stats| stats.calls)
+                .collect()
+        };
+
+        assert_eq!(client.sticky_route(&status_request), None);
+        client
+            .call(methods::broadcast_tx_async::RpcBroadcastTxAsyncRequest { signed_transaction })
+            .await
+            .unwrap();
+        assert_eq!(client.sticky_route(&status_request), Some(1));
+
+        // the status poll doesn't parse, but it was sent where the transaction was
+        let _ = client.call(&status_request).await;
+        assert_eq!(calls(), [0, 2]);
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(client.sticky_route(&status_request), None);
+        let _ = client.call(&status_request).await;
+        assert_eq!(calls(), [1, 2]);
+    }
+
+    #[tokio::test]
+    async fn read_only_degradation() {
+        use std::sync::atomic::AtomicBool;
+
+        use near_primitives::transaction::{SignedTransaction, Transaction};
+
+        let (a_failing, b_down) = (
+            Arc::new(AtomicBool::new(true)),
+            Arc::new(AtomicBool::new(false)),
+        );
+        // fails writes to `a` with `503 Service Unavailable` while `a_failing` is set, and to
+        // `b` with a connection error while `b_down` is set, reporting the status otherwise
+        let connector = JsonRpcClient::with_transport(FnTransport::new({
+            let (a_failing, b_down) = (a_failing.clone(), b_down.clone());
+            move |request, payload| {
+                let result = match payload["method"].as_str() {
+                    Some("broadcast_tx_async") => match request.server_addr.as_str() {
+                        "http://a" if a_failing.load(Ordering::SeqCst) => {
+                            return Ok(TransportResponse::new(
+                                http::StatusCode::SERVICE_UNAVAILABLE,
+                                vec![],
+                            ))
+                        }
+                        "http://b" if b_down.load(Ordering::SeqCst) => {
+                            return Err(TransportError::SendError("connection refused".into()))
+                        }
+                        _ => serde_json::to_value(CryptoHash::default()).unwrap(),
+                    },
+                    _ => status(100, false),
+                };
+                Ok(json_rpc_result(&payload["id"], result))
+            }
+        }));
+        let changes = Arc::new(Mutex::new(vec![]));
+        let client = JsonRpcMultiClient::new(
+            ["http://a", "http://b"]
+                .iter()
+                .map(|addr| connector.connect(*addr)),
+        )
+        .read_only_degradation(2)
+        .on_mode_change({
+            let changes = changes.clone();
+            move |change| {
+                changes
+                    .lock()
+                    .unwrap()
+                    .push((change.server_addr.clone(), change.to))
+            }
+        });
+
+        let signed_transaction = SignedTransaction::new(
+            near_crypto::Signature::empty(near_crypto::KeyType::ED25519),
+            Transaction {
+                signer_id: "alice.near".parse().unwrap(),
+                public_key: near_crypto::PublicKey::empty(near_crypto::KeyType::ED25519),
+                nonce: 1,
+                receiver_id: "bob.near".parse().unwrap(),
+                block_hash: Default::default(),
+                actions: vec![],
+            },
+        );
+        let broadcast = || {
+            client.call_with_meta(methods::broadcast_tx_async::RpcBroadcastTxAsyncRequest {
+                signed_transaction: signed_transaction.clone(),
+            })
+        };
+        let modes = || -> Vec<_> {
+            client
+                .endpoint_stats()
+                .iter()
+                .map(|stats| stats.mode)
+                .collect()
+        };
+
+        for _ in 0..2 {
+            assert!(broadcast().await.is_err());
+        }
+        assert_eq!(modes(), [EndpointMode::ReadOnly, EndpointMode::ReadWrite]);
+        assert_eq!(
+            *changes.lock().unwrap(),
+            [("http://a".to_string(), EndpointMode::ReadOnly)]
+        );
+
+        // writes go elsewhere, while `a` still serves reads
+        let (_, meta) = broadcast().await.unwrap();
+        assert_eq!(meta.url, "http://b");
+        let (_, meta) = client
+            .call_with_meta(methods::status::RpcStatusRequest)
+            .await
+            .unwrap();
+        assert_eq!(meta.url, "http://a");
+
+        // a write falling back to `a` succeeding restores it
+        a_failing.store(false, Ordering::SeqCst);
+        b_down.store(true, Ordering::SeqCst);
+        let (_, meta) = broadcast().await.unwrap();
+        assert_eq!(meta.url, "http://a");
+        assert_eq!(modes(), [EndpointMode::ReadWrite, EndpointMode::ReadWrite]);
+        assert_eq!(
+            changes.lock().unwrap().last(),
+            Some(&("http://a".to_string(), EndpointMode::ReadWrite))
+        );
+    }
+}
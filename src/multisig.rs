@@ -0,0 +1,603 @@
+//! Working with multisig accounts, running the standard [multisig contract].
+//!
+//! A multisig account holds a number of access keys, each allowed to call the contract on the
+//! account only. Actions on the account are added as requests, which execute once enough of
+//! the keys confirmed them. Requests and confirmations are calls the multisig account makes
+//! on itself, so the [signers](crate::transaction::TransactionSigner) passed here sign as the
+//! multisig account, each with one of its keys.
+//!
+//! [multisig contract]: https://github.com/near/core-contracts/tree/master/multisig
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use near_jsonrpc_client::multisig::{Multisig, MultisigAction, MultisigRequest};
+//! use near_jsonrpc_client::JsonRpcClient;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! # let signer: near_crypto::InMemorySigner = unimplemented!();
+//! # let other_signer: near_crypto::InMemorySigner = unimplemented!();
+//! let client = JsonRpcClient::connect("https://rpc.testnet.near.org");
+//! let multisig = Multisig::new(&client, "multisig.testnet".parse()?);
+//!
+//! let request_id = multisig
+//!     .add_request_and_confirm(
+//!         &signer,
+//!         MultisigRequest {
+//!             receiver_id: "miraclx.testnet".parse()?,
+//!             actions: vec![MultisigAction::Transfer {
+//!                 amount: 10u128.pow(24), // 1 NEAR
+//!             }],
+//!         },
+//!     )
+//!     .await?;
+//!
+//! for pending in multisig.pending().await? {
+//!     println!(
+//!         "request {} has {} of {} confirmations",
+//!         pending.id,
+//!         pending.confirmations.len(),
+//!         pending.num_confirmations
+//!     );
+//! }
+//!
+//! multisig.confirm(&other_signer, request_id).await?;
+//! # Ok(())
+//! # }
+//! ```
+use serde::{Deserialize, Serialize};
+
+use near_crypto::PublicKey;
+use near_primitives::serialize::{
+    base64_format, from_base64, option_u128_dec_format, u128_dec_format, u64_dec_format,
+};
+use near_primitives::transaction::{Action, FunctionCallAction};
+use near_primitives::types::{AccountId, Balance, BlockId, BlockReference, Finality, Gas};
+use near_primitives::views::FinalExecutionStatus;
+
+use crate::errors::MultisigError;
+use crate::methods::broadcast_tx_commit::RpcBroadcastTxCommitResponse;
+use crate::methods::query::RpcCallFunctionRequest;
+use crate::transaction::TransactionSigner;
+use crate::JsonRpcClient;
+
+/// The ID of a request, increasing with each request added.
+pub type RequestId = u32;
+
+/// A request for the multisig account to execute actions on a receiver.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultisigRequest {
+    pub receiver_id: AccountId,
+    pub actions: Vec<MultisigAction>,
+}
+
+/// An action of a [`MultisigRequest`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum MultisigAction {
+    Transfer {
+        #[serde(with = "u128_dec_format")]
+        amount: Balance,
+    },
+    CreateAccount,
+    DeployContract {
+        #[serde(with = "base64_format")]
+        code: Vec<u8>,
+    },
+    /// Add a key, with full access unless it has a permission.
+    AddKey {
+        public_key: PublicKey,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        permission: Option<FunctionCallPermission>,
+    },
+    DeleteKey {
+        public_key: PublicKey,
+    },
+    FunctionCall {
+        method_name: String,
+        #[serde(with = "base64_format")]
+        args: Vec<u8>,
+        #[serde(with = "u128_dec_format")]
+        deposit: Balance,
+        #[serde(with = "u64_dec_format")]
+        gas: Gas,
+    },
+    /// Change the number of confirmations requests need, only allowed on the multisig account
+    /// itself.
+    SetNumConfirmations {
+        num_confirmations: u32,
+    },
+    /// Change the number of requests each key can have pending, only allowed on the multisig
+    /// account itself.
+    SetActiveRequestsLimit {
+        active_requests_limit: u32,
+    },
+}
+
+/// The permission of a key added with [`MultisigAction::AddKey`], to call methods of a
+/// receiver only.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionCallPermission {
+    #[serde(default, with = "option_u128_dec_format")]
+    pub allowance: Option<Balance>,
+    pub receiver_id: AccountId,
+    pub method_names: Vec<String>,
+}
+
+/// A request waiting for confirmations, see [`Multisig::pending`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingRequest {
+    pub id: RequestId,
+    pub request: MultisigRequest,
+    /// The keys that confirmed the request.
+    pub confirmations: Vec<PublicKey>,
+    /// The number of confirmations requests need to execute.
+    pub num_confirmations: u32,
+}
+
+impl PendingRequest {
+    /// The number of confirmations the request still needs to execute.
+    pub fn remaining(&self) -> u32 {
+        self.num_confirmations
+            .saturating_sub(self.confirmations.len() as u32)
+    }
+}
+
+/// A multisig account, see the [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct Multisig<'a> {
+    client: &'a JsonRpcClient,
+    account_id: AccountId,
+    gas: Gas,
+}
+
+impl<'a> Multisig<'a> {
+    pub fn new(client: &'a JsonRpcClient, account_id: AccountId) -> Self {
+        Self {
+            client,
+            account_id,
+            gas: 300_000_000_000_000,
+        }
+    }
+
+    /// The gas attached to calls on the contract, 300 Tgas by default.
+    ///
+    /// The last confirmation of a request executes it, with this gas.
+    pub fn gas(mut self, gas: Gas) -> Self {
+        self.gas = gas;
+        self
+    }
+
+    /// The multisig account.
+    pub fn account_id(&self) -> &AccountId {
+        &self.account_id
+    }
+
+    /// The IDs of the requests waiting for confirmations.
+    pub async fn request_ids(&self) -> Result<Vec<RequestId>, MultisigError> {
+        Ok(self
+            .view(
+                Finality::Final.into(),
+                "list_request_ids",
+                serde_json::json!({}),
+            )
+            .await?
+            .0)
+    }
+
+    /// A request waiting for confirmations.
+    pub async fn request(&self, request_id: RequestId) -> Result<MultisigRequest, MultisigError> {
+        Ok(self
+            .view(
+                Finality::Final.into(),
+                "get_request",
+                serde_json::json!({ "request_id": request_id }),
+            )
+            .await?
+            .0)
+    }
+
+    /// The keys that confirmed a request.
+    pub async fn confirmations(
+        &self,
+        request_id: RequestId,
+    ) -> Result<Vec<PublicKey>, MultisigError> {
+        Ok(self
+            .view(
+                Finality::Final.into(),
+                "get_confirmations",
+                serde_json::json!({ "request_id": request_id }),
+            )
+            .await?
+            .0)
+    }
+
+    /// The number of confirmations requests need to execute.
+    pub async fn num_confirmations(&self) -> Result<u32, MultisigError> {
+        Ok(self
+            .view(
+                Finality::Final.into(),
+                "get_num_confirmations",
+                serde_json::json!({}),
+            )
+            .await?
+            .0)
+    }
+
+    /// The requests waiting for confirmations, with their confirmations, in order of ID.
+    ///
+    /// They're all viewed as of the same final block.
+    pub async fn pending(&self) -> Result<Vec<PendingRequest>, MultisigError> {
+        let (mut request_ids, block_hash): (Vec<RequestId>, _) = self
+            .view(
+                Finality::Final.into(),
+                "list_request_ids",
+                serde_json::json!({}),
+            )
+            .await?;
+        request_ids.sort_unstable();
+        let block_reference = || BlockReference::from(BlockId::Hash(block_hash));
+
+        let (num_confirmations, _) = self
+            .view(
+                block_reference(),
+                "get_num_confirmations",
+                serde_json::json!({}),
+            )
+            .await?;
+        let mut pending = Vec::with_capacity(request_ids.len());
+        for id in request_ids {
+            let args = serde_json::json!({ "request_id": id });
+            let (request, _) = self
+                .view(block_reference(), "get_request", args.clone())
+                .await?;
+            let (confirmations, _) = self
+                .view(block_reference(), "get_confirmations", args)
+                .await?;
+            pending.push(PendingRequest {
+                id,
+                request,
+                confirmations,
+                num_confirmations,
+            });
+        }
+        Ok(pending)
+    }
+
+    /// Add a request, returning its ID.
+    pub async fn add_request<S: TransactionSigner + ?Sized>(
+        &self,
+        signer: &S,
+        request: MultisigRequest,
+    ) -> Result<RequestId, MultisigError> {
+        let outcome = self
+            .call(
+                signer,
+                "add_request",
+                serde_json::json!({ "request": request }),
+            )
+            .await?;
+        parse_result(&outcome.status)
+    }
+
+    /// Add a request and confirm it with the signer's key, returning its ID.
+    pub async fn add_request_and_confirm<S: TransactionSigner + ?Sized>(
+        &self,
+        signer: &S,
+        request: MultisigRequest,
+    ) -> Result<RequestId, MultisigError> {
+        let outcome = self
+            .call(
+                signer,
+                "add_request_and_confirm",
+                serde_json::json!({ "request": request }),
+            )
+            .await?;
+        parse_result(&outcome.status)
+    }
+
+    /// Confirm a request with the signer's key, returning the outcome.
+    ///
+    /// If this is the last confirmation the request needs, the outcome includes executing it.
+    pub async fn confirm<S: TransactionSigner + ?Sized>(
+        &self,
+        signer: &S,
+        request_id: RequestId,
+    ) -> Result<RpcBroadcastTxCommitResponse, MultisigError> {
+        self.call(
+            signer,
+            "confirm",
+            serde_json::json!({ "request_id": request_id }),
+        )
+        .await
+    }
+
+    /// Delete a request, which only the key that added it can do, and only after it expired.
+    pub async fn delete_request<S: TransactionSigner + ?Sized>(
+        &self,
+        signer: &S,
+        request_id: RequestId,
+    ) -> Result<RpcBroadcastTxCommitResponse, MultisigError> {
+        self.call(
+            signer,
+            "delete_request",
+            serde_json::json!({ "request_id": request_id }),
+        )
+        .await
+    }
+
+    async fn view<T: serde::de::DeserializeOwned>(
+        &self,
+        block_reference: BlockReference,
+        method_name: &str,
+        args: serde_json::Value,
+    ) -> Result<(T, near_primitives::hash::CryptoHash), MultisigError> {
+        let response = self
+            .client
+            .call(RpcCallFunctionRequest {
+                block_reference,
+                account_id: self.account_id.clone(),
+                method_name: method_name.to_string(),
+                args: args.to_string().into_bytes(),
+            })
+            .await
+            .map_err(MultisigError::View)?;
+        let value = response.json().map_err(MultisigError::Result)?;
+        Ok((value, response.block_hash))
+    }
+
+    async fn call<S: TransactionSigner + ?Sized>(
+        &self,
+        signer: &S,
+        method_name: &str,
+        args: serde_json::Value,
+    ) -> Result<RpcBroadcastTxCommitResponse, MultisigError> {
+        let outcome = self
+            .client
+            .send_transaction(
+                signer,
+                self.account_id.clone(),
+                vec![Action::FunctionCall(FunctionCallAction {
+                    method_name: method_name.to_string(),
+                    args: args.to_string().into_bytes(),
+                    gas: self.gas,
+                    deposit: 0,
+                })],
+            )
+            .await
+            .map_err(MultisigError::Send)?;
+        match outcome.status {
+            FinalExecutionStatus::Failure(err) => Err(MultisigError::Failure(err)),
+            _ => Ok(outcome),
+        }
+    }
+}
+
+/// Parse the value a call on the contract returned, as JSON.
+#[allow(clippy::result_large_err)]
+fn parse_result<T: serde::de::DeserializeOwned>(
+    status: &FinalExecutionStatus,
+) -> Result<T, MultisigError> {
+    let value = match status {
+        FinalExecutionStatus::SuccessValue(value) => value,
+        status => {
+            return Err(MultisigError::Result(serde::de::Error::custom(format!(
+                "expected a value, found [{:?}]",
+                status
+            ))))
+        }
+    };
+    let value =
+        from_base64(value).map_err(|err| MultisigError::Result(serde::de::Error::custom(err)))?;
+    serde_json::from_slice(&value).map_err(MultisigError::Result)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::transport::testing::{
+        broadcast_transaction, json_rpc_result, transaction_outcome, FnTransport,
+    };
+    use crate::transport::Transport;
+
+    /// The contract calls made, with their arguments.
+    type Calls = Arc<Mutex<Vec<(String, serde_json::Value)>>>;
+
+    /// Serves a multisig contract with one pending request, recording the contract calls.
+    fn multisig_transport() -> (impl Transport, Calls) {
+        let calls = Arc::new(Mutex::new(vec![]));
+        let transport = FnTransport::new({
+            let calls = calls.clone();
+            move |_, payload| {
+                let params = &payload["params"];
+                let result = match (payload["method"].as_str(), params["request_type"].as_str()) {
+                    (Some("query"), Some("view_access_key")) => serde_json::json!({
+                        "nonce": 5,
+                        "permission": "FullAccess",
+                        "block_height": 100,
+                        "block_hash": "9FtHUFBQsZ2MG77K3x3MJ9wjX3UT8zE1TczCrhZEcG8U",
+                    }),
+                    (Some("query"), Some("call_function")) => {
+                        let method_name = params["method_name"].as_str().unwrap();
+                        let args: serde_json::Value = serde_json::from_slice(
+                            &from_base64(params["args_base64"].as_str().unwrap()).unwrap(),
+                        )
+                        .unwrap();
+                        let value = match method_name {
+                            "list_request_ids" => serde_json::json!([3]),
+                            "get_num_confirmations" => serde_json::json!(2),
+                            "get_request" => serde_json::json!({
+                                "receiver_id": "bob.near",
+                                "actions": [{ "type": "Transfer", "amount": "1" }],
+                            }),
+                            "get_confirmations" => serde_json::json!([
+                                "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"
+                            ]),
+                            method_name => panic!("unexpected view [{}]", method_name),
+                        };
+                        calls.lock().unwrap().push((method_name.to_string(), args));
+                        serde_json::json!({
+                            "result": serde_json::to_vec(&value).unwrap(),
+                            "logs": [],
+                            "block_height": 100,
+                            "block_hash": "9FtHUFBQsZ2MG77K3x3MJ9wjX3UT8zE1TczCrhZEcG8U",
+                        })
+                    }
+                    (Some("broadcast_tx_commit"), _) => {
+                        let signed_transaction = broadcast_transaction(&payload);
+                        let (method_name, args) = match &signed_transaction.transaction.actions[..]
+                        {
+                            [Action::FunctionCall(call)] => (
+                                call.method_name.clone(),
+                                serde_json::from_slice(&call.args).unwrap(),
+                            ),
+                            actions => panic!("unexpected actions [{:?}]", actions),
+                        };
+                        let value = match method_name.as_str() {
+                            "add_request" | "add_request_and_confirm" => "Mw==", // 3
+                            _ => "",
+                        };
+                        calls.lock().unwrap().push((method_name, args));
+                        serde_json::to_value(transaction_outcome(
+                            &signed_transaction,
+                            FinalExecutionStatus::SuccessValue(value.to_string()),
+                        ))
+                        .unwrap()
+                    }
+                    method => panic!("unexpected method [{:?}]", method),
+                };
+                Ok(json_rpc_result(&payload["id"], result))
+            }
+        });
+        (transport, calls)
+    }
+
+    #[test]
+    fn actions() {
+        let request: MultisigRequest = serde_json::from_value(serde_json::json!({
+            "receiver_id": "bob.near",
+            "actions": [
+                { "type": "Transfer", "amount": "1000" },
+                {
+                    "type": "AddKey",
+                    "public_key": "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp",
+                    "permission": {
+                        "allowance": null,
+                        "receiver_id": "bob.near",
+                        "method_names": ["confirm"],
+                    },
+                },
+                {
+                    "type": "FunctionCall",
+                    "method_name": "ping",
+                    "args": "e30=",
+                    "deposit": "0",
+                    "gas": "5000000000000",
+                },
+                { "type": "SetNumConfirmations", "num_confirmations": 3 },
+            ],
+        }))
+        .unwrap();
+        assert_eq!(
+            request.actions,
+            [
+                MultisigAction::Transfer { amount: 1000 },
+                MultisigAction::AddKey {
+                    public_key: "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"
+                        .parse()
+                        .unwrap(),
+                    permission: Some(FunctionCallPermission {
+                        allowance: None,
+                        receiver_id: "bob.near".parse().unwrap(),
+                        method_names: vec!["confirm".to_string()],
+                    }),
+                },
+                MultisigAction::FunctionCall {
+                    method_name: "ping".to_string(),
+                    args: b"{}".to_vec(),
+                    deposit: 0,
+                    gas: 5_000_000_000_000,
+                },
+                MultisigAction::SetNumConfirmations {
+                    num_confirmations: 3
+                },
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(&MultisigAction::AddKey {
+                public_key: "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"
+                    .parse()
+                    .unwrap(),
+                permission: None,
+            })
+            .unwrap(),
+            serde_json::json!({
+                "type": "AddKey",
+                "public_key": "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn requests() {
+        let (transport, calls) = multisig_transport();
+        let client = JsonRpcClient::with_transport(transport).connect("http://localhost:3030");
+        let multisig = Multisig::new(&client, "multisig.near".parse().unwrap());
+
+        let pending = multisig.pending().await.unwrap();
+        assert_eq!(
+            pending,
+            [PendingRequest {
+                id: 3,
+                request: MultisigRequest {
+                    receiver_id: "bob.near".parse().unwrap(),
+                    actions: vec![MultisigAction::Transfer { amount: 1 }],
+                },
+                confirmations: vec!["ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"
+                    .parse()
+                    .unwrap()],
+                num_confirmations: 2,
+            }]
+        );
+        assert_eq!(pending[0].remaining(), 1);
+
+        let signer = near_crypto::InMemorySigner::from_seed(
+            "multisig.near".parse().unwrap(),
+            near_crypto::KeyType::ED25519,
+            "multisig.near",
+        );
+        let request_id = multisig
+            .add_request(&signer, pending[0].request.clone())
+            .await
+            .unwrap();
+        assert_eq!(request_id, 3);
+        multisig.confirm(&signer, request_id).await.unwrap();
+
+        let calls = calls.lock().unwrap();
+        let calls = calls
+            .iter()
+            .map(|(method_name, args)| (method_name.as_str(), args));
+        assert_eq!(
+            calls.collect::<Vec<_>>(),
+            [
+                ("list_request_ids", &serde_json::json!({})),
+                ("get_num_confirmations", &serde_json::json!({})),
+                ("get_request", &serde_json::json!({ "request_id": 3 })),
+                ("get_confirmations", &serde_json::json!({ "request_id": 3 })),
+                (
+                    "add_request",
+                    &serde_json::json!({
+                        "request": {
+                            "receiver_id": "bob.near",
+                            "actions": [{ "type": "Transfer", "amount": "1" }],
+                        },
+                    })
+                ),
+                ("confirm", &serde_json::json!({ "request_id": 3 })),
+            ]
+        );
+    }
+}
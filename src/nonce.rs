@@ -0,0 +1,226 @@
+//! Handing out nonces locally, for sending transactions concurrently with the same access key.
+//!
+//! A [`NonceManager`] views the nonce of an access key the first time it's used, then hands
+//! out increasing nonces without viewing it again, so that tasks sending transactions with the
+//! same key don't race each other to the same nonce.
+//!
+//! Transactions sent with the key by other means can get ahead of the manager, in which case
+//! the node rejects the manager's nonces as [`InvalidNonce`](InvalidTxError::InvalidNonce),
+//! which the manager can then [reconcile](NonceManager::reconcile) with.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use near_jsonrpc_client::nonce::NonceManager;
+//! use near_jsonrpc_client::transaction::TransactionBuilder;
+//! use near_jsonrpc_client::{methods, JsonRpcClient};
+//! use near_primitives::types::{BlockReference, Finality};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! # let signer: near_crypto::InMemorySigner = unimplemented!();
+//! let client = JsonRpcClient::connect("https://rpc.testnet.near.org");
+//! let nonces = NonceManager::new();
+//!
+//! let block = client
+//!     .call(methods::block::RpcBlockRequest {
+//!         block_reference: BlockReference::Finality(Finality::Final),
+//!     })
+//!     .await?;
+//!
+//! let receiver_id: near_primitives::types::AccountId = "bob.testnet".parse()?;
+//! loop {
+//!     let nonce = nonces
+//!         .next(&client, &signer.account_id, &signer.public_key)
+//!         .await?;
+//!     let transaction = TransactionBuilder::new(signer.account_id.clone(), receiver_id.clone())
+//!         .public_key(signer.public_key.clone())
+//!         .nonce(nonce)
+//!         .block_hash(block.header.hash)
+//!         .transfer(1)
+//!         .build()?;
+//!
+//!     match client.commit_transaction(transaction.sign(&signer)).await {
+//!         Ok(outcome) => break println!("{:?}", outcome.status),
+//!         // the key was used elsewhere, try again with a newer nonce
+//!         Err(err) if nonces.reconcile(&signer.account_id, &signer.public_key, &err) => {}
+//!         Err(err) => Err(err)?,
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use near_crypto::PublicKey;
+use near_jsonrpc_primitives::types::query::QueryResponseKind;
+use near_primitives::errors::InvalidTxError;
+use near_primitives::types::{AccountId, Finality, Nonce};
+use near_primitives::views::QueryRequest;
+
+use crate::errors::{JsonRpcError, JsonRpcServerError};
+use crate::methods::query::RpcQueryError;
+use crate::methods::tx::RpcTransactionError;
+use crate::{methods, JsonRpcClient};
+
+/// Hands out the nonces of access keys, see the [module documentation](self).
+///
+/// Clones share their nonces.
+#[derive(Debug, Clone, Default)]
+pub struct NonceManager {
+    /// The last nonce handed out, or seen on chain, for each access key.
+    nonces: Arc<Mutex<HashMap<(AccountId, PublicKey), Nonce>>>,
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The next nonce of the access key, viewing its nonce as of the final block with
+    /// `client` the first time.
+    pub async fn next(
+        &self,
+        client: &JsonRpcClient,
+        account_id: &AccountId,
+        public_key: &PublicKey,
+    ) -> Result<Nonce, JsonRpcError<RpcQueryError>> {
+        let key = (account_id.clone(), public_key.clone());
+        if let Some(nonce) = self.nonces.lock().unwrap().get_mut(&key) {
+            *nonce += 1;
+            return Ok(*nonce);
+        }
+
+        let response = client
+            .call(methods::query::RpcQueryRequest {
+                block_reference: Finality::Final.into(),
+                request: QueryRequest::ViewAccessKey {
+                    account_id: account_id.clone(),
+                    public_key: public_key.clone(),
+                },
+            })
+            .await?;
+        let onchain = match response.kind {
+            QueryResponseKind::AccessKey(access_key) => access_key.nonce,
+            kind => return Err(crate::unexpected_query_response(kind)),
+        };
+
+        // other tasks may have viewed it meanwhile, and handed out nonces already
+        let mut nonces = self.nonces.lock().unwrap();
+        let nonce = nonces.entry(key).or_insert(onchain);
+        *nonce = (*nonce).max(onchain) + 1;
+        Ok(*nonce)
+    }
+
+    /// Catch up with the nonce of the access key on chain, if sending a transaction failed
+    /// because its nonce was too low, returning whether it did.
+    ///
+    /// The next nonce handed out is then higher than the nonce on chain.
+    pub fn reconcile(
+        &self,
+        account_id: &AccountId,
+        public_key: &PublicKey,
+        err: &JsonRpcError<RpcTransactionError>,
+    ) -> bool {
+        let ak_nonce = match err {
+            JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+                RpcTransactionError::InvalidTransaction {
+                    context: InvalidTxError::InvalidNonce { ak_nonce, .. },
+                },
+            )) => *ak_nonce,
+            _ => return false,
+        };
+        let mut nonces = self.nonces.lock().unwrap();
+        let nonce = nonces
+            .entry((account_id.clone(), public_key.clone()))
+            .or_insert(ak_nonce);
+        *nonce = (*nonce).max(ak_nonce);
+        true
+    }
+
+    /// Forget the nonce of the access key, so that it's viewed again on next use.
+    pub fn reset(&self, account_id: &AccountId, public_key: &PublicKey) {
+        self.nonces
+            .lock()
+            .unwrap()
+            .remove(&(account_id.clone(), public_key.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::transport::testing::{json_rpc_result, FnTransport};
+
+    #[tokio::test]
+    async fn nonces() {
+        let queries = Arc::new(AtomicUsize::new(0));
+        // serves an access key with nonce 5, counting the queries
+        let transport = FnTransport::new({
+            let queries = queries.clone();
+            move |_, payload| {
+                queries.fetch_add(1, Ordering::SeqCst);
+                Ok(json_rpc_result(
+                    &payload["id"],
+                    serde_json::json!({
+                        "nonce": 5,
+                        "permission": "FullAccess",
+                        "block_height": 100,
+                        "block_hash": "11111111111111111111111111111111",
+                    }),
+                ))
+            }
+        });
+        let client = JsonRpcClient::with_transport(transport).connect("http://localhost:3030");
+
+        let account_id: AccountId = "alice.near".parse().unwrap();
+        let public_key = PublicKey::empty(near_crypto::KeyType::ED25519);
+        let nonces = NonceManager::new();
+
+        let mut handed_out = futures_util::future::try_join_all(
+            (0..4).map(|_| nonces.next(&client, &account_id, &public_key)),
+        )
+        .await
+        .unwrap();
+        handed_out.sort_unstable();
+        assert_eq!(handed_out, [6, 7, 8, 9]);
+        let queried = queries.load(Ordering::SeqCst);
+
+        // the key was used elsewhere, up to nonce 20
+        let err = JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+            RpcTransactionError::InvalidTransaction {
+                context: InvalidTxError::InvalidNonce {
+                    tx_nonce: 10,
+                    ak_nonce: 20,
+                },
+            },
+        ));
+        assert!(nonces.reconcile(&account_id, &public_key, &err));
+        assert_eq!(
+            nonces
+                .next(&client, &account_id, &public_key)
+                .await
+                .unwrap(),
+            21
+        );
+        assert_eq!(queries.load(Ordering::SeqCst), queried);
+
+        let err = JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+            RpcTransactionError::TimeoutError,
+        ));
+        assert!(!nonces.reconcile(&account_id, &public_key, &err));
+
+        nonces.reset(&account_id, &public_key);
+        assert_eq!(
+            nonces
+                .next(&client, &account_id, &public_key)
+                .await
+                .unwrap(),
+            6
+        );
+        assert_eq!(queries.load(Ordering::SeqCst), queried + 1);
+    }
+}
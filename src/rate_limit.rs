@@ -0,0 +1,142 @@
+//! Client-side rate limiting.
+//!
+//! A [`RateLimiter`] configured on the client, with [`JsonRpcClient::rate_limiter`](crate::JsonRpcClient::rate_limiter),
+//! spaces out requests before they are sent, so as to stay within the limits of the RPC provider.
+//!
+//! Limiting is done with a token bucket: the bucket holds up to `burst` tokens, refilled at a
+//! steady rate of `requests_per_second`, and each request, retries included, takes one token,
+//! waiting for it if the bucket is empty.
+//!
+//! Cloning a limiter shares its bucket, so the same limit can be applied across clients. With a
+//! [`JsonRpcMultiClient`](crate::JsonRpcMultiClient), a limiter can instead be applied to each
+//! endpoint separately, with [`JsonRpcMultiClient::rate_limiter`](crate::JsonRpcMultiClient::rate_limiter).
+//!
+//! ## Example
+//!
+//! ```
+//! use near_jsonrpc_client::{rate_limit::RateLimiter, JsonRpcClient};
+//!
+//! let limiter = RateLimiter::new(10.0).burst(20);
+//!
+//! let client = JsonRpcClient::connect("https://rpc.testnet.near.org").rate_limiter(limiter.clone());
+//! let archival_client =
+//!     JsonRpcClient::connect("https://archival-rpc.testnet.near.org").rate_limiter(limiter);
+//! ```
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::retry::Instant;
+
+#[derive(Debug)]
+struct Bucket {
+    /// Available tokens, negative when requests are waiting for tokens not yet refilled.
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Limits the rate of requests, see the [module-level documentation](self).
+///
+/// Cloning the limiter is cheap, and shares its bucket with the original.
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    requests_per_second: f64,
+    burst: u32,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+impl RateLimiter {
+    /// Create a new rate limiter, allowing the specified number of requests per second,
+    /// with a burst of one request.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the rate isn't a positive number.
+    pub fn new(requests_per_second: f64) -> Self {
+        assert!(
+            requests_per_second > 0.0 && requests_per_second.is_finite(),
+            "expected a positive rate of requests"
+        );
+        Self::with_config(requests_per_second, 1)
+    }
+
+    /// Set the number of requests that can be sent at once, after a period of inactivity.
+    ///
+    /// Values below 1 are treated as 1. The bucket starts full.
+    pub fn burst(self, burst: u32) -> Self {
+        Self::with_config(self.requests_per_second, burst.max(1))
+    }
+
+    /// Get the number of requests allowed per second.
+    pub fn get_requests_per_second(&self) -> f64 {
+        self.requests_per_second
+    }
+
+    /// Get the number of requests that can be sent at once.
+    pub fn get_burst(&self) -> u32 {
+        self.burst
+    }
+
+    fn with_config(requests_per_second: f64, burst: u32) -> Self {
+        Self {
+            requests_per_second,
+            burst,
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: burst as f64,
+                refilled_at: Instant::now(),
+            })),
+        }
+    }
+
+    /// Create a limiter with the same configuration, but its own bucket.
+    pub(crate) fn unshared(&self) -> Self {
+        Self::with_config(self.requests_per_second, self.burst)
+    }
+
+    /// Take a token, returning how long to wait for before sending the request.
+    fn reserve(&self) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap();
+        let now = Instant::now();
+        let refill =
+            now.duration_since(bucket.refilled_at).as_secs_f64() * self.requests_per_second;
+        bucket.tokens = (bucket.tokens + refill).min(self.burst as f64) - 1.0;
+        bucket.refilled_at = now;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.requests_per_second)
+        }
+    }
+
+    /// Wait for a token to be available.
+    pub(crate) async fn acquire(&self) {
+        crate::retry::sleep(self.reserve()).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_bucket() {
+        let limiter = RateLimiter::new(10.0).burst(2);
+
+        assert_eq!(limiter.reserve(), Duration::ZERO);
+        assert_eq!(limiter.clone().reserve(), Duration::ZERO);
+
+        // the bucket is empty, requests queue up 100ms apart
+        let wait = limiter.reserve();
+        assert!(wait > Duration::from_millis(90) && wait <= Duration::from_millis(100));
+        let wait = limiter.reserve();
+        assert!(wait > Duration::from_millis(190) && wait <= Duration::from_millis(200));
+
+        // a separate bucket starts full
+        assert_eq!(limiter.unshared().reserve(), Duration::ZERO);
+
+        std::thread::sleep(Duration::from_millis(450));
+        assert_eq!(limiter.reserve(), Duration::ZERO);
+        assert_eq!(limiter.reserve(), Duration::ZERO);
+        assert!(limiter.reserve() > Duration::ZERO);
+    }
+}
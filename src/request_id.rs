@@ -0,0 +1,108 @@
+//! JSON RPC request ids.
+//!
+//! By default, every call is sent with a random UUID as its JSON RPC request id. An
+//! [`IdGenerator`] configured on the client, with [`JsonRpcClient::id_generator`](crate::JsonRpcClient::id_generator),
+//! generates them instead, like the [`Sequential`] generator, or any closure returning a
+//! `String`. To correlate a single call with an id supplied from elsewhere, like the id of the
+//! incoming request being served, use [`CallOptions::request_id`](crate::CallOptions::request_id).
+//!
+//! The id a call was sent with is reported in [`CallMeta::request_id`](crate::CallMeta::request_id),
+//! and, with the `tracing` feature flag, recorded on the `rpc_attempt` span. Retries of a call
+//! are sent with the same id. Errors don't carry the id, so calls that need correlating on
+//! failure should supply their own.
+//!
+//! ## Example
+//!
+//! ```
+//! use near_jsonrpc_client::{request_id::Sequential, JsonRpcClient};
+//!
+//! let client = JsonRpcClient::connect("https://rpc.testnet.near.org")
+//!     .id_generator(Sequential::new());
+//!
+//! // or, with a prefix identifying the application
+//! let client = JsonRpcClient::connect("https://rpc.testnet.near.org")
+//!     .id_generator(|| format!("explorer-{}", std::process::id()));
+//! ```
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Generates JSON RPC request ids, see the [module-level documentation](self).
+pub trait IdGenerator: Send + Sync + 'static {
+    /// Generate the id of the next call.
+    fn generate(&self) -> String;
+}
+
+impl<F> IdGenerator for F
+where
+    F: Fn() -> String + Send + Sync + 'static,
+{
+    fn generate(&self) -> String {
+        self()
+    }
+}
+
+/// Generates sequential ids, starting at `1`.
+#[derive(Debug, Default)]
+pub struct Sequential(AtomicU64);
+
+impl Sequential {
+    /// Create a new generator, starting at `1`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl IdGenerator for Sequential {
+    fn generate(&self) -> String {
+        (self.0.fetch_add(1, Ordering::Relaxed) + 1).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::methods;
+    use crate::transport::testing::{json_rpc_result, FnTransport};
+    use crate::{CallOptions, JsonRpcClient};
+
+    #[tokio::test]
+    async fn request_ids() {
+        let ids = Arc::new(Mutex::new(vec![]));
+        // echoes the request id back, recording it
+        let transport = FnTransport::new({
+            let ids = ids.clone();
+            move |_, payload| {
+                ids.lock().unwrap().push(payload["id"].clone());
+                Ok(json_rpc_result(&payload["id"], serde_json::Value::Null))
+            }
+        });
+        let client = JsonRpcClient::with_transport(transport).connect("http://localhost:3030");
+
+        let (_, meta) = client
+            .call_with_meta(methods::health::RpcHealthRequest)
+            .await
+            .unwrap();
+        assert_eq!(meta.request_id.len(), 36, "expected a UUID by default");
+        assert_eq!(ids.lock().unwrap()[0], meta.request_id.as_str());
+
+        let client = client.id_generator(Sequential::new());
+        for expected in ["1", "2"] {
+            let (_, meta) = client
+                .call_with_meta(methods::health::RpcHealthRequest)
+                .await
+                .unwrap();
+            assert_eq!(meta.request_id, expected);
+        }
+
+        client
+            .call_with_options(
+                methods::health::RpcHealthRequest,
+                CallOptions::new().request_id("correlation-id"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(ids.lock().unwrap()[1..], ["1", "2", "correlation-id"]);
+    }
+}
@@ -0,0 +1,445 @@
+//! Automatic retries for failed RPC calls.
+//!
+//! A [`RetryPolicy`] configured on the client, with [`JsonRpcClient::retry_policy`](crate::JsonRpcClient::retry_policy),
+//! retries calls that fail for reasons that are likely to go away on their own: connection
+//! failures, timeouts, rate limiting, `5xx` responses and internal server errors,
+//! see [`JsonRpcError::is_retryable`](crate::errors::JsonRpcError::is_retryable).
+//!
+//! Errors that would just repeat themselves, like request validation errors, are returned
+//! straight away. So are all errors from methods that aren't safe to call more than once,
+//! like `broadcast_tx_commit`, see [`RpcMethod::is_idempotent`](crate::methods::RpcMethod::is_idempotent).
+//!
+//! The exception is calls [rate limited](crate::errors::RpcTransportError::RateLimited) by the
+//! server, which are always retried, as the server didn't process them. They wait for at least
+//! as long as the server's `Retry-After` header asks for, and fail straight away if that's
+//! longer than the [maximum delay](RetryPolicy::max_delay).
+//!
+//! A [`CallOptions::timeout`](crate::CallOptions::timeout) applies to each attempt separately,
+//! while a [deadline](RetryPolicy::deadline) bounds the call as a whole.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use near_jsonrpc_client::{retry::RetryPolicy, JsonRpcClient};
+//!
+//! let client = JsonRpcClient::connect("https://rpc.testnet.near.org").retry_policy(
+//!     RetryPolicy::new()
+//!         .max_attempts(5)
+//!         .base_delay(Duration::from_millis(250))
+//!         .max_delay(Duration::from_secs(5)),
+//! );
+//! ```
+
+use std::time::Duration;
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) use instant::Instant;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use std::time::Instant;
+
+use rand::Rng;
+
+/// How failed calls should be retried.
+///
+/// Between attempts, the client waits for an exponentially growing delay, starting at
+/// the base delay and doubling after each attempt, up to the maximum delay.
+///
+/// With jitter enabled (the default), each delay is instead picked at random between
+/// zero and the exponential delay, so that clients failing at the same time don't all
+/// retry at the same time.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+    deadline: Option<Duration>,
+}
+
+impl RetryPolicy {
+    /// Create a new retry policy, making up to 3 attempts, with delays starting at
+    /// 100 milliseconds, capped at 10 seconds, with jitter.
+    pub fn new() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+            jitter: true,
+            deadline: None,
+        }
+    }
+
+    /// Set the maximum number of attempts, including the first one.
+    ///
+    /// Values below 1 are treated as 1, which disables retries.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Set the delay before the first retry.
+    pub fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// Set the upper bound on the delay between attempts.
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Enable or disable jitter.
+    pub fn jitter(mut self, enabled: bool) -> Self {
+        self.jitter = enabled;
+        self
+    }
+
+    /// Bound the total time spent on a call, across all attempts and the delays between them.
+    ///
+    /// Calls that don't complete in time fail with a
+    /// [`DeadlineExceeded`](crate::errors::JsonRpcError::DeadlineExceeded) error, instead of
+    /// sleeping past the deadline. This can be overridden for a single call with
+    /// [`CallOptions::deadline`](crate::CallOptions::deadline).
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Get the total time allowed for a call, if any.
+    pub fn get_deadline(&self) -> Option<Duration> {
+        self.deadline
+    }
+
+    /// Get the upper bound on the delay between attempts.
+    pub fn get_max_delay(&self) -> Duration {
+        self.max_delay
+    }
+
+    /// Get the maximum number of attempts, including the first one.
+    pub fn get_max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Get the delay to wait for after the specified (1-based) failed attempt.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let delay = self
+            .base_delay
+            .checked_mul(1 << exponent)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+        if self.jitter && delay > Duration::ZERO {
+            let nanos =
+                rand::thread_rng().gen_range(0..=delay.as_nanos().min(u64::MAX as u128) as u64);
+            Duration::from_nanos(nanos)
+        } else {
+            delay
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub(crate) async fn sleep(delay: Duration) {
+    if delay > Duration::ZERO {
+        futures_timer::Delay::new(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use super::*;
+    use crate::errors::{JsonRpcError, JsonRpcServerError, JsonRpcServerResponseStatusError};
+    use crate::transport::testing::{json_rpc_error, json_rpc_result, FnTransport};
+    use crate::transport::TransportResponse;
+    use crate::{methods, JsonRpcClient};
+
+    #[test]
+    fn backoff() {
+        let policy = RetryPolicy::new()
+            .base_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_millis(350))
+            .jitter(false);
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(350));
+        assert_eq!(policy.backoff(100), Duration::from_millis(350));
+
+        let policy = policy.jitter(true);
+        for attempt in 1..10 {
+            assert!(policy.backoff(attempt) <= Duration::from_millis(350));
+        }
+    }
+
+    /// A client whose requests fail with the specified statuses, in order, then succeed.
+    fn flaky_client(failures: Vec<http::StatusCode>) -> (JsonRpcClient, Arc<AtomicUsize>) {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let transport = FnTransport::new({
+            let attempts = attempts.clone();
+            move |_, payload| {
+                let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+                Ok(match failures.get(attempt) {
+                    Some(status) => TransportResponse::new(*status, vec![]),
+                    None => json_rpc_result(&payload["id"], serde_json::Value::Null),
+                })
+            }
+        });
+        let client = JsonRpcClient::with_transport(transport)
+            .connect("http://localhost:3030")
+            .retry_policy(RetryPolicy::new().base_delay(Duration::from_millis(1)));
+        (client, attempts)
+    }
+
+    #[tokio::test]
+    async fn retries_transient_errors() {
+        let (client, attempts) = flaky_client(vec![
+            http::StatusCode::SERVICE_UNAVAILABLE,
+            http::StatusCode::TOO_MANY_REQUESTS,
+        ]);
+
+        let response = client.call(methods::health::RpcHealthRequest).await;
+
+        assert!(
+            matches!(response, Ok(methods::health::RpcHealthResponse)),
+            "expected an Ok(RpcHealthResponse), found [{:?}]",
+            response
+        );
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts() {
+        let (client, attempts) = flaky_client(vec![http::StatusCode::BAD_GATEWAY; 5]);
+
+        let response = client.call(methods::health::RpcHealthRequest).await;
+
+        assert!(
+            matches!(
+                response,
+                Err(JsonRpcError::ServerError(JsonRpcServerError::ResponseStatusError(
+                    JsonRpcServerResponseStatusError::Unexpected { status }
+                ))) if status == http::StatusCode::BAD_GATEWAY
+            ),
+            "expected a 502 error, found [{:?}]",
+            response
+        );
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn skips_permanent_errors() {
+        let (client, attempts) = flaky_client(vec![http::StatusCode::UNAUTHORIZED]);
+
+        let response = client.call(methods::health::RpcHealthRequest).await;
+
+        assert!(
+            matches!(
+                response,
+                Err(JsonRpcError::ServerError(
+                    JsonRpcServerError::ResponseStatusError(
+                        JsonRpcServerResponseStatusError::Unauthorized
+                    )
+                ))
+            ),
+            "expected an unauthorized error, found [{:?}]",
+            response
+        );
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn skips_non_idempotent_methods() {
+        let (client, attempts) = flaky_client(vec![http::StatusCode::SERVICE_UNAVAILABLE]);
+
+        let signed_transaction = near_primitives::transaction::SignedTransaction::new(
+            near_crypto::Signature::empty(near_crypto::KeyType::ED25519),
+            near_primitives::transaction::Transaction {
+                signer_id: "alice.near".parse().unwrap(),
+                public_key: near_crypto::PublicKey::empty(near_crypto::KeyType::ED25519),
+                nonce: 1,
+                receiver_id: "bob.near".parse().unwrap(),
+                block_hash: Default::default(),
+                actions: vec![],
+            },
+        );
+
+        let response = client
+            .call(methods::broadcast_tx_commit::RpcBroadcastTxCommitRequest { signed_transaction })
+            .await;
+
+        assert!(
+            matches!(response, Err(ref err) if err.is_retryable()),
+            "expected a retryable error, found [{:?}]",
+            response
+        );
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn rate_limited() {
+        use crate::errors::RpcTransportError;
+
+        // rate limits the first two requests, with a status then with a provider-specific error
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let transport = FnTransport::new({
+            let attempts = attempts.clone();
+            move |_, payload| {
+                Ok(match attempts.fetch_add(1, Ordering::SeqCst) {
+                    0 => {
+                        let mut headers = http::HeaderMap::new();
+                        headers.insert(
+                            http::header::RETRY_AFTER,
+                            http::HeaderValue::from_static("0"),
+                        );
+                        TransportResponse::new(http::StatusCode::TOO_MANY_REQUESTS, vec![])
+                            .with_headers(headers)
+                    }
+                    1 => json_rpc_error(
+                        &payload["id"],
+                        serde_json::json!({ "code": -32005, "message": "Limit exceeded" }),
+                    ),
+                    _ => json_rpc_result(
+                        &payload["id"],
+                        serde_json::json!(near_primitives::hash::CryptoHash::default()),
+                    ),
+                })
+            }
+        });
+        let client = JsonRpcClient::with_transport(transport).connect("http://localhost:3030");
+
+        let response = client.call(methods::health::RpcHealthRequest).await;
+        assert!(
+            matches!(
+                response,
+                Err(JsonRpcError::TransportError(RpcTransportError::RateLimited {
+                    retry_after: Some(delay)
+                })) if delay == Duration::ZERO
+            ),
+            "expected a rate limited error, found [{:?}]",
+            response
+        );
+        let response = client.call(methods::health::RpcHealthRequest).await;
+        assert!(
+            matches!(
+                response,
+                Err(JsonRpcError::TransportError(
+                    RpcTransportError::RateLimited { retry_after: None }
+                ))
+            ),
+            "expected a rate limited error, found [{:?}]",
+            response
+        );
+
+        // rate limited writes are retried too, as they weren't processed
+        attempts.store(0, Ordering::SeqCst);
+        let client = client.retry_policy(RetryPolicy::new().base_delay(Duration::from_millis(1)));
+        let signed_transaction = near_primitives::transaction::SignedTransaction::new(
+            near_crypto::Signature::empty(near_crypto::KeyType::ED25519),
+            near_primitives::transaction::Transaction {
+                signer_id: "alice.near".parse().unwrap(),
+                public_key: near_crypto::PublicKey::empty(near_crypto::KeyType::ED25519),
+                nonce: 1,
+                receiver_id: "bob.near".parse().unwrap(),
+                block_hash: Default::default(),
+                actions: vec![],
+            },
+        );
+        let response = client
+            .call(methods::broadcast_tx_async::RpcBroadcastTxAsyncRequest { signed_transaction })
+            .await;
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        assert!(
+            response.is_ok(),
+            "expected the call to get through, found [{:?}]",
+            response
+        );
+
+        // asked to wait for longer than the maximum delay, the call fails straight away
+        let transport = FnTransport::new(|_, _| {
+            let mut headers = http::HeaderMap::new();
+            headers.insert(
+                http::header::RETRY_AFTER,
+                http::HeaderValue::from_static("86400"),
+            );
+            Ok(
+                TransportResponse::new(http::StatusCode::TOO_MANY_REQUESTS, vec![])
+                    .with_headers(headers),
+            )
+        });
+        let client = JsonRpcClient::with_transport(transport)
+            .connect("http://localhost:3030")
+            .retry_policy(RetryPolicy::new());
+        let response = client.call(methods::health::RpcHealthRequest).await;
+        assert!(
+            matches!(
+                response,
+                Err(JsonRpcError::TransportError(RpcTransportError::RateLimited {
+                    retry_after: Some(delay)
+                })) if delay == Duration::from_secs(86400)
+            ),
+            "expected a rate limited error, found [{:?}]",
+            response
+        );
+    }
+
+    #[test]
+    fn retryable_handler_errors() {
+        use methods::query::RpcQueryError;
+
+        let err: JsonRpcError<RpcQueryError> = JsonRpcError::ServerError(
+            JsonRpcServerError::HandlerError(RpcQueryError::NoSyncedBlocks),
+        );
+        assert!(err.is_retryable());
+
+        let err: JsonRpcError<RpcQueryError> = JsonRpcError::ServerError(
+            JsonRpcServerError::HandlerError(RpcQueryError::UnknownAccount {
+                requested_account_id: "alice.near".parse().unwrap(),
+                block_height: 1,
+                block_hash: Default::default(),
+            }),
+        );
+        assert!(!err.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn deadline() {
+        let (client, attempts) = flaky_client(vec![http::StatusCode::SERVICE_UNAVAILABLE; 100]);
+        let client = client.retry_policy(
+            RetryPolicy::new()
+                .max_attempts(100)
+                .base_delay(Duration::from_millis(100))
+                .jitter(false)
+                .deadline(Duration::from_millis(250)),
+        );
+
+        let response = client.call(methods::health::RpcHealthRequest).await;
+
+        let history = match response {
+            Err(JsonRpcError::DeadlineExceeded(err)) => err.attempts,
+            response => panic!("expected a deadline exceeded error, found [{:?}]", response),
+        };
+        // fails at ~0ms, ~100ms, then gives up instead of sleeping for another 200ms
+        assert_eq!(history.len(), 2);
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        assert!(history[0].elapsed < history[1].elapsed);
+        assert!(history.iter().all(|attempt| matches!(
+            attempt.error,
+            JsonRpcError::ServerError(JsonRpcServerError::ResponseStatusError(
+                JsonRpcServerResponseStatusError::Unexpected { .. }
+            ))
+        )));
+    }
+}
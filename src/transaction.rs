@@ -0,0 +1,763 @@
+//! Building transactions, one action at a time.
+//!
+//! A [`TransactionBuilder`] accumulates the actions of a transaction, and checks that they'd
+//! pass the node's validation when [building](TransactionBuilder::build) the [`Transaction`],
+//! which is then ready to be signed.
+//!
+//! The nonce has to be one more than the nonce of the access key signing the transaction, and
+//! the block hash that of a recent block, which both come from viewing the access key.
+//!
+//! The node also checks transactions against the limits of the runtime, which the builder
+//! checks too when it has [limits](TransactionBuilder::limits), as parsed from the
+//! [protocol configuration](crate::methods::EXPERIMENTAL_protocol_config). They rarely change,
+//! so one copy of them can serve many transactions.
+//!
+//! Transactions can be signed in process with [`Transaction::sign`], or with a
+//! [`TransactionSigner`], for keys held elsewhere, with [`sign`].
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use near_jsonrpc_client::transaction::TransactionBuilder;
+//! use near_jsonrpc_client::{methods, JsonRpcClient};
+//! use near_jsonrpc_primitives::types::query::QueryResponseKind;
+//! use near_primitives::types::{BlockReference, Finality};
+//! use near_primitives::views::QueryRequest;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! # let signer: near_crypto::InMemorySigner = unimplemented!();
+//! let client = JsonRpcClient::connect("https://rpc.testnet.near.org");
+//!
+//! let access_key = client
+//!     .call(methods::query::RpcQueryRequest {
+//!         block_reference: BlockReference::Finality(Finality::Final),
+//!         request: QueryRequest::ViewAccessKey {
+//!             account_id: signer.account_id.clone(),
+//!             public_key: signer.public_key.clone(),
+//!         },
+//!     })
+//!     .await?;
+//! let nonce = match access_key.kind {
+//!     QueryResponseKind::AccessKey(view) => view.nonce + 1,
+//!     _ => Err("failed to extract the nonce")?,
+//! };
+//!
+//! let receiver_id = "nosedive.testnet".parse()?;
+//! let transaction = TransactionBuilder::new(signer.account_id.clone(), receiver_id)
+//!     .public_key(signer.public_key.clone())
+//!     .nonce(nonce)
+//!     .block_hash(access_key.block_hash)
+//!     .function_call(
+//!         "rate",
+//!         br#"{"account_id":"miraclx.testnet","rating":5.0}"#.to_vec(),
+//!         100_000_000_000_000, // 100 TeraGas
+//!         0,
+//!     )
+//!     .build()?;
+//!
+//! let tx_hash = client
+//!     .call(methods::broadcast_tx_async::RpcBroadcastTxAsyncRequest {
+//!         signed_transaction: transaction.sign(&signer),
+//!     })
+//!     .await?;
+//!
+//! println!("sent transaction {}", tx_hash);
+//! # Ok(())
+//! # }
+//! ```
+use std::sync::Arc;
+
+use thiserror::Error;
+
+use near_crypto::{InMemorySigner, PublicKey, Signature, Signer};
+use near_primitives::account::AccessKey;
+use near_primitives::account::AccessKeyPermission;
+use near_primitives::hash::CryptoHash;
+use near_primitives::transaction::{
+    Action, AddKeyAction, CreateAccountAction, DeleteAccountAction, DeleteKeyAction,
+    DeployContractAction, FunctionCallAction, SignedTransaction, StakeAction, Transaction,
+    TransferAction,
+};
+use near_primitives::types::{AccountId, Balance, Gas, Nonce};
+
+use crate::methods::EXPERIMENTAL_protocol_config::LimitConfig;
+use crate::transport::{BoxError, BoxFuture};
+
+/// A transaction that wouldn't pass the node's validation, see [`TransactionBuilder::build`].
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TransactionBuilderError {
+    #[error("the {0} of the transaction is missing")]
+    Missing(&'static str),
+    #[error("the transaction has no actions")]
+    NoActions,
+    #[error("only the first action can create an account, action #{index} does")]
+    CreateAccountNotFirst { index: usize },
+    #[error("only the last action can delete the account, action #{index} does")]
+    DeleteAccountNotLast { index: usize },
+    #[error("the function call of action #{index} has no method name")]
+    EmptyMethodName { index: usize },
+    #[error("the function call of action #{index} has no gas attached")]
+    ZeroGas { index: usize },
+    #[error("the transaction has {count} actions, over the limit of {limit}")]
+    TooManyActions { count: u64, limit: u64 },
+    #[error("the function calls attach {total} gas, over the limit of {limit}")]
+    TooMuchGas { total: Gas, limit: Gas },
+    #[error("action #{index} deploys {size} bytes of code, over the limit of {limit}")]
+    ContractTooLarge { index: usize, size: u64, limit: u64 },
+    #[error("action #{index} has a {length} byte method name, over the limit of {limit}")]
+    MethodNameTooLong {
+        index: usize,
+        length: u64,
+        limit: u64,
+    },
+    #[error("action #{index} adds a key with {length} bytes of method names, over {limit}")]
+    MethodNamesTooLong {
+        index: usize,
+        length: u64,
+        limit: u64,
+    },
+    #[error("action #{index} has {length} bytes of arguments, over the limit of {limit}")]
+    ArgumentsTooLong {
+        index: usize,
+        length: u64,
+        limit: u64,
+    },
+    #[error("the transaction has {size} bytes, over the limit of {limit}")]
+    TooLarge { size: u64, limit: u64 },
+}
+
+/// A builder for a [`Transaction`], see the [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct TransactionBuilder {
+    signer_id: AccountId,
+    receiver_id: AccountId,
+    public_key: Option<PublicKey>,
+    nonce: Option<Nonce>,
+    block_hash: Option<CryptoHash>,
+    actions: Vec<Action>,
+    limits: Option<LimitConfig>,
+}
+
+impl TransactionBuilder {
+    /// Build a transaction signed by `signer_id`, with actions on `receiver_id`.
+    pub fn new(signer_id: AccountId, receiver_id: AccountId) -> Self {
+        Self {
+            signer_id,
+            receiver_id,
+            public_key: None,
+            nonce: None,
+            block_hash: None,
+            actions: vec![],
+            limits: None,
+        }
+    }
+
+    /// The public key of the access key signing the transaction.
+    pub fn public_key(mut self, public_key: PublicKey) -> Self {
+        self.public_key = Some(public_key);
+        self
+    }
+
+    /// The nonce of the transaction, one more than the nonce of the access key signing it.
+    pub fn nonce(mut self, nonce: Nonce) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    /// The hash of a recent block, the transaction expiring some blocks after it.
+    pub fn block_hash(mut self, block_hash: CryptoHash) -> Self {
+        self.block_hash = Some(block_hash);
+        self
+    }
+
+    /// Check the transaction against the limits of the runtime when building it.
+    pub fn limits(mut self, limits: LimitConfig) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+
+    /// Add an action.
+    pub fn action(mut self, action: Action) -> Self {
+        self.actions.push(action);
+        self
+    }
+
+    /// Create the receiver account, this has to be the first action.
+    pub fn create_account(self) -> Self {
+        self.action(Action::CreateAccount(CreateAccountAction {}))
+    }
+
+    /// Deploy a contract to the receiver account.
+    pub fn deploy_contract(self, code: Vec<u8>) -> Self {
+        self.action(Action::DeployContract(DeployContractAction { code }))
+    }
+
+    /// Call a method of the contract of the receiver account.
+    pub fn function_call<M: Into<String>>(
+        self,
+        method_name: M,
+        args: Vec<u8>,
+        gas: Gas,
+        deposit: Balance,
+    ) -> Self {
+        self.action(Action::FunctionCall(FunctionCallAction {
+            method_name: method_name.into(),
+            args,
+            gas,
+            deposit,
+        }))
+    }
+
+    /// Transfer tokens to the receiver account.
+    pub fn transfer(self, deposit: Balance) -> Self {
+        self.action(Action::Transfer(TransferAction { deposit }))
+    }
+
+    /// Stake tokens of the receiver account, validating with the specified key.
+    pub fn stake(self, stake: Balance, public_key: PublicKey) -> Self {
+        self.action(Action::Stake(StakeAction { stake, public_key }))
+    }
+
+    /// Add an access key to the receiver account.
+    pub fn add_key(self, public_key: PublicKey, access_key: AccessKey) -> Self {
+        self.action(Action::AddKey(AddKeyAction {
+            public_key,
+            access_key,
+        }))
+    }
+
+    /// Delete an access key of the receiver account.
+    pub fn delete_key(self, public_key: PublicKey) -> Self {
+        self.action(Action::DeleteKey(DeleteKeyAction { public_key }))
+    }
+
+    /// Delete the receiver account, sending its balance to `beneficiary_id`, this has to be
+    /// the last action.
+    pub fn delete_account(self, beneficiary_id: AccountId) -> Self {
+        self.action(Action::DeleteAccount(DeleteAccountAction {
+            beneficiary_id,
+        }))
+    }
+
+    /// Build the transaction, checking that it has all it needs, that its actions are valid
+    /// and in a valid order, and that it's within the [limits](Self::limits), if any.
+    pub fn build(self) -> Result<Transaction, TransactionBuilderError> {
+        let public_key = self
+            .public_key
+            .ok_or(TransactionBuilderError::Missing("public key"))?;
+        let nonce = self
+            .nonce
+            .ok_or(TransactionBuilderError::Missing("nonce"))?;
+        let block_hash = self
+            .block_hash
+            .ok_or(TransactionBuilderError::Missing("block hash"))?;
+        if self.actions.is_empty() {
+            return Err(TransactionBuilderError::NoActions);
+        }
+        let last = self.actions.len() - 1;
+        for (index, action) in self.actions.iter().enumerate() {
+            match action {
+                Action::CreateAccount(_) if index != 0 => {
+                    return Err(TransactionBuilderError::CreateAccountNotFirst { index })
+                }
+                Action::DeleteAccount(_) if index != last => {
+                    return Err(TransactionBuilderError::DeleteAccountNotLast { index })
+                }
+                Action::FunctionCall(call) if call.method_name.is_empty() => {
+                    return Err(TransactionBuilderError::EmptyMethodName { index })
+                }
+                Action::FunctionCall(call) if call.gas == 0 => {
+                    return Err(TransactionBuilderError::ZeroGas { index })
+                }
+                _ => {}
+            }
+        }
+        let transaction = Transaction {
+            signer_id: self.signer_id,
+            public_key,
+            nonce,
+            receiver_id: self.receiver_id,
+            block_hash,
+            actions: self.actions,
+        };
+        if let Some(limits) = &self.limits {
+            check_limits(&transaction, limits)?;
+        }
+        Ok(transaction)
+    }
+}
+
+/// Check a transaction against the limits of the runtime, in the order the node does.
+fn check_limits(
+    transaction: &Transaction,
+    limits: &LimitConfig,
+) -> Result<(), TransactionBuilderError> {
+    let (_, size) = transaction.get_hash_and_size();
+    if size > limits.max_transaction_size {
+        return Err(TransactionBuilderError::TooLarge {
+            size,
+            limit: limits.max_transaction_size,
+        });
+    }
+    let actions = &transaction.actions;
+    let count = actions.len() as u64;
+    if count > limits.max_actions_per_receipt {
+        return Err(TransactionBuilderError::TooManyActions {
+            count,
+            limit: limits.max_actions_per_receipt,
+        });
+    }
+    let total = actions
+        .iter()
+        .map(|action| match action {
+            Action::FunctionCall(call) => call.gas,
+            _ => 0,
+        })
+        .fold(0, Gas::saturating_add);
+    if total > limits.max_total_prepaid_gas {
+        return Err(TransactionBuilderError::TooMuchGas {
+            total,
+            limit: limits.max_total_prepaid_gas,
+        });
+    }
+    let check_method_name = |index, method_name: &str| {
+        let length = method_name.len() as u64;
+        if length > limits.max_length_method_name {
+            return Err(TransactionBuilderError::MethodNameTooLong {
+                index,
+                length,
+                limit: limits.max_length_method_name,
+            });
+        }
+        Ok(())
+    };
+    for (index, action) in actions.iter().enumerate() {
+        match action {
+            Action::DeployContract(deploy) => {
+                let size = deploy.code.len() as u64;
+                if size > limits.max_contract_size {
+                    return Err(TransactionBuilderError::ContractTooLarge {
+                        index,
+                        size,
+                        limit: limits.max_contract_size,
+                    });
+                }
+            }
+            Action::FunctionCall(call) => {
+                check_method_name(index, &call.method_name)?;
+                let length = call.args.len() as u64;
+                if length > limits.max_arguments_length {
+                    return Err(TransactionBuilderError::ArgumentsTooLong {
+                        index,
+                        length,
+                        limit: limits.max_arguments_length,
+                    });
+                }
+            }
+            Action::AddKey(add_key) => {
+                if let AccessKeyPermission::FunctionCall(permission) =
+                    &add_key.access_key.permission
+                {
+                    for method_name in &permission.method_names {
+                        check_method_name(index, method_name)?;
+                    }
+                    // each name counts with a terminating byte
+                    let length = permission
+                        .method_names
+                        .iter()
+                        .map(|method_name| method_name.len() as u64 + 1)
+                        .sum();
+                    if length > limits.max_number_bytes_method_names {
+                        return Err(TransactionBuilderError::MethodNamesTooLong {
+                            index,
+                            length,
+                            limit: limits.max_number_bytes_method_names,
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// A transaction sent with
+/// [`JsonRpcClient::send_transaction_with_attempts`](crate::JsonRpcClient::send_transaction_with_attempts).
+#[derive(Debug)]
+pub struct SentTransaction {
+    pub outcome: crate::methods::broadcast_tx_commit::RpcBroadcastTxCommitResponse,
+    /// The attempts it took, more than one if the node rejected the transaction for its nonce
+    /// or block hash before.
+    pub attempts: u32,
+}
+
+/// Signs transactions, with a key that can be held out of process, like in a KMS or on a
+/// hardware wallet.
+///
+/// Implemented for [`InMemorySigner`], signing in process.
+///
+/// ## Example
+///
+/// ```
+/// use near_crypto::{PublicKey, Signature};
+/// use near_jsonrpc_client::transaction::TransactionSigner;
+/// use near_jsonrpc_client::transport::{BoxError, BoxFuture};
+/// use near_primitives::types::AccountId;
+///
+/// struct KmsSigner {
+///     account_id: AccountId,
+///     public_key: PublicKey,
+///     key_id: String,
+/// }
+///
+/// # async fn kms_sign(key_id: &str, message: &[u8]) -> Result<Vec<u8>, BoxError> { unimplemented!() }
+/// impl TransactionSigner for KmsSigner {
+///     fn account_id(&self) -> &AccountId {
+///         &self.account_id
+///     }
+///
+///     fn public_key(&self) -> &PublicKey {
+///         &self.public_key
+///     }
+///
+///     fn sign<'a>(&'a self, message: &'a [u8]) -> BoxFuture<'a, Result<Signature, BoxError>> {
+///         Box::pin(async move {
+///             let signature = kms_sign(&self.key_id, message).await?;
+///             Ok(Signature::from_parts(self.public_key.key_type(), &signature)?)
+///         })
+///     }
+/// }
+/// ```
+pub trait TransactionSigner: Send + Sync {
+    /// The account signing the transactions.
+    fn account_id(&self) -> &AccountId;
+
+    /// The public key of the access key signing the transactions.
+    fn public_key(&self) -> &PublicKey;
+
+    /// Sign a message, the hash of a transaction.
+    fn sign<'a>(&'a self, message: &'a [u8]) -> BoxFuture<'a, Result<Signature, BoxError>>;
+
+    /// Sign a transaction.
+    ///
+    /// Defaults to [signing](TransactionSigner::sign) the hash of the transaction, signers
+    /// that need the whole transaction, like hardware wallets displaying it for approval,
+    /// override this.
+    fn sign_transaction<'a>(
+        &'a self,
+        transaction: &'a Transaction,
+    ) -> BoxFuture<'a, Result<Signature, BoxError>> {
+        let (hash, _) = transaction.get_hash_and_size();
+        Box::pin(async move { self.sign(hash.as_ref()).await })
+    }
+}
+
+impl TransactionSigner for InMemorySigner {
+    fn account_id(&self) -> &AccountId {
+        &self.account_id
+    }
+
+    fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    fn sign<'a>(&'a self, message: &'a [u8]) -> BoxFuture<'a, Result<Signature, BoxError>> {
+        let signature = Signer::sign(self, message);
+        Box::pin(async move { Ok(signature) })
+    }
+}
+
+impl<S: TransactionSigner + ?Sized> TransactionSigner for Arc<S> {
+    fn account_id(&self) -> &AccountId {
+        (**self).account_id()
+    }
+
+    fn public_key(&self) -> &PublicKey {
+        (**self).public_key()
+    }
+
+    fn sign<'a>(&'a self, message: &'a [u8]) -> BoxFuture<'a, Result<Signature, BoxError>> {
+        (**self).sign(message)
+    }
+
+    fn sign_transaction<'a>(
+        &'a self,
+        transaction: &'a Transaction,
+    ) -> BoxFuture<'a, Result<Signature, BoxError>> {
+        (**self).sign_transaction(transaction)
+    }
+}
+
+/// Sign a transaction with a [`TransactionSigner`].
+pub async fn sign<S: TransactionSigner + ?Sized>(
+    transaction: Transaction,
+    signer: &S,
+) -> Result<SignedTransaction, BoxError> {
+    let signature = signer.sign_transaction(&transaction).await?;
+    Ok(SignedTransaction::new(signature, transaction))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builder() -> TransactionBuilder {
+        TransactionBuilder::new(
+            "alice.near".parse().unwrap(),
+            "bob.alice.near".parse().unwrap(),
+        )
+        .public_key(PublicKey::empty(near_crypto::KeyType::ED25519))
+        .nonce(1)
+        .block_hash(CryptoHash::default())
+    }
+
+    #[test]
+    fn build() {
+        let transaction = builder()
+            .create_account()
+            .transfer(100)
+            .add_key(
+                PublicKey::empty(near_crypto::KeyType::ED25519),
+                AccessKey::full_access(),
+            )
+            .function_call("new", b"{}".to_vec(), 10, 0)
+            .build()
+            .unwrap();
+
+        assert_eq!(transaction.signer_id.as_ref(), "alice.near");
+        assert_eq!(transaction.receiver_id.as_ref(), "bob.alice.near");
+        assert_eq!(transaction.nonce, 1);
+        assert_eq!(transaction.actions.len(), 4);
+        assert!(matches!(
+            transaction.actions[3],
+            Action::FunctionCall(FunctionCallAction { gas: 10, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn external_signer() {
+        /// Signs with an in-memory key, through the trait only.
+        struct RemoteSigner(Arc<InMemorySigner>);
+
+        impl TransactionSigner for RemoteSigner {
+            fn account_id(&self) -> &AccountId {
+                self.0.account_id()
+            }
+
+            fn public_key(&self) -> &PublicKey {
+                TransactionSigner::public_key(&*self.0)
+            }
+
+            fn sign<'a>(&'a self, message: &'a [u8]) -> BoxFuture<'a, Result<Signature, BoxError>> {
+                TransactionSigner::sign(&*self.0, message)
+            }
+        }
+
+        let signer = InMemorySigner::from_seed(
+            "alice.near".parse().unwrap(),
+            near_crypto::KeyType::ED25519,
+            "alice.near",
+        );
+        let transaction =
+            TransactionBuilder::new("alice.near".parse().unwrap(), "bob.near".parse().unwrap())
+                .public_key(signer.public_key.clone())
+                .nonce(1)
+                .block_hash(CryptoHash::default())
+                .transfer(1)
+                .build()
+                .unwrap();
+
+        let signed_transaction = sign(transaction.clone(), &RemoteSigner(Arc::new(signer.clone())))
+            .await
+            .unwrap();
+        assert_eq!(signed_transaction, transaction.sign(&signer));
+        assert!(signed_transaction
+            .signature
+            .verify(signed_transaction.get_hash().as_ref(), &signer.public_key));
+    }
+
+    #[test]
+    fn validation() {
+        assert_eq!(
+            TransactionBuilder::new("alice.near".parse().unwrap(), "bob.near".parse().unwrap())
+                .transfer(1)
+                .build()
+                .unwrap_err(),
+            TransactionBuilderError::Missing("public key")
+        );
+        assert_eq!(
+            builder().build().unwrap_err(),
+            TransactionBuilderError::NoActions
+        );
+        assert_eq!(
+            builder().transfer(1).create_account().build().unwrap_err(),
+            TransactionBuilderError::CreateAccountNotFirst { index: 1 }
+        );
+        assert_eq!(
+            builder()
+                .delete_account("alice.near".parse().unwrap())
+                .transfer(1)
+                .build()
+                .unwrap_err(),
+            TransactionBuilderError::DeleteAccountNotLast { index: 0 }
+        );
+        assert_eq!(
+            builder()
+                .function_call("", vec![], 10, 0)
+                .build()
+                .unwrap_err(),
+            TransactionBuilderError::EmptyMethodName { index: 0 }
+        );
+        assert_eq!(
+            builder()
+                .transfer(1)
+                .function_call("rate", vec![], 0, 0)
+                .build()
+                .unwrap_err(),
+            TransactionBuilderError::ZeroGas { index: 1 }
+        );
+    }
+
+    #[test]
+    fn limits() {
+        let limits = LimitConfig {
+            max_total_prepaid_gas: 300,
+            max_actions_per_receipt: 3,
+            max_length_method_name: 8,
+            max_number_bytes_method_names: 14,
+            max_arguments_length: 16,
+            max_contract_size: 32,
+            max_transaction_size: 256,
+        };
+        let builder = || builder().limits(limits);
+
+        assert!(builder()
+            .function_call("rate", vec![0; 16], 200, 0)
+            .function_call("rate", vec![], 100, 0)
+            .build()
+            .is_ok());
+        assert_eq!(
+            builder()
+                .transfer(1)
+                .transfer(1)
+                .transfer(1)
+                .transfer(1)
+                .build()
+                .unwrap_err(),
+            TransactionBuilderError::TooManyActions { count: 4, limit: 3 }
+        );
+        assert_eq!(
+            builder()
+                .function_call("rate", vec![], 200, 0)
+                .function_call("rate", vec![], 101, 0)
+                .build()
+                .unwrap_err(),
+            TransactionBuilderError::TooMuchGas {
+                total: 301,
+                limit: 300
+            }
+        );
+        assert_eq!(
+            builder()
+                .transfer(1)
+                .deploy_contract(vec![0; 33])
+                .build()
+                .unwrap_err(),
+            TransactionBuilderError::ContractTooLarge {
+                index: 1,
+                size: 33,
+                limit: 32
+            }
+        );
+        assert_eq!(
+            builder()
+                .function_call("rate_account", vec![], 10, 0)
+                .build()
+                .unwrap_err(),
+            TransactionBuilderError::MethodNameTooLong {
+                index: 0,
+                length: 12,
+                limit: 8
+            }
+        );
+        assert_eq!(
+            builder()
+                .function_call("rate", vec![0; 17], 10, 0)
+                .build()
+                .unwrap_err(),
+            TransactionBuilderError::ArgumentsTooLong {
+                index: 0,
+                length: 17,
+                limit: 16
+            }
+        );
+
+        let key = |method_names: &[&str]| AccessKey {
+            nonce: 0,
+            permission: AccessKeyPermission::FunctionCall(
+                near_primitives::account::FunctionCallPermission {
+                    allowance: None,
+                    receiver_id: "bob.near".to_string(),
+                    method_names: method_names.iter().map(|name| name.to_string()).collect(),
+                },
+            ),
+        };
+        let public_key = PublicKey::empty(near_crypto::KeyType::ED25519);
+        assert_eq!(
+            builder()
+                .add_key(public_key.clone(), key(&["rate", "view", "ping"]))
+                .build()
+                .unwrap_err(),
+            TransactionBuilderError::MethodNamesTooLong {
+                index: 0,
+                length: 15,
+                limit: 14
+            }
+        );
+        assert_eq!(
+            builder()
+                .add_key(public_key.clone(), key(&["rate_account"]))
+                .build()
+                .unwrap_err(),
+            TransactionBuilderError::MethodNameTooLong {
+                index: 0,
+                length: 12,
+                limit: 8
+            }
+        );
+        // over both limits, the length of each name is checked first
+        assert_eq!(
+            builder()
+                .add_key(public_key, key(&["rate", "rate_account"]))
+                .build()
+                .unwrap_err(),
+            TransactionBuilderError::MethodNameTooLong {
+                index: 0,
+                length: 12,
+                limit: 8
+            }
+        );
+
+        // the size is checked before any action, and four transfers are too many
+        let transfers =
+            |builder: TransactionBuilder| builder.transfer(1).transfer(1).transfer(1).transfer(1);
+        let (_, size) = transfers(self::builder())
+            .build()
+            .unwrap()
+            .get_hash_and_size();
+        assert_eq!(
+            transfers(builder().limits(LimitConfig {
+                max_transaction_size: size - 1,
+                ..limits
+            }))
+            .build()
+            .unwrap_err(),
+            TransactionBuilderError::TooLarge {
+                size,
+                limit: size - 1
+            }
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use super::{BoxFuture, Transport, TransportError, TransportRequest, TransportResponse};
+
+use hyper::client::connect::Connect;
+use hyper::client::HttpConnector;
+use hyper_tls::HttpsConnector;
+
+/// A [`Transport`] backed by a raw [`hyper::Client`], bypassing `reqwest` entirely.
+///
+/// Available with the `hyper-transport` feature flag.
+#[derive(Clone, Debug)]
+pub struct HyperTransport<C = HttpsConnector<HttpConnector>> {
+    client: hyper::Client<C, hyper::Body>,
+}
+
+impl HyperTransport {
+    /// Create a new transport with a default HTTPS-capable `hyper::Client`.
+    pub fn new() -> Self {
+        Self::with_client(hyper::Client::builder().build(HttpsConnector::new()))
+    }
+}
+
+impl<C> HyperTransport<C> {
+    /// Create a new transport using a custom `hyper::Client`.
+    ///
+    /// ## Example
+    ///
+    /// Tuning the underlying sockets.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use hyper::client::HttpConnector;
+    /// use hyper_tls::HttpsConnector;
+    /// use near_jsonrpc_client::{transport::HyperTransport, JsonRpcClient};
+    ///
+    /// let mut http = HttpConnector::new();
+    /// http.enforce_http(false);
+    /// http.set_keepalive(Some(Duration::from_secs(60)));
+    /// http.set_nodelay(true);
+    /// http.set_send_buffer_size(Some(256 * 1024));
+    /// http.set_recv_buffer_size(Some(1024 * 1024));
+    ///
+    /// let client = hyper::Client::builder().build(HttpsConnector::new_with_connector(http));
+    ///
+    /// let connector = JsonRpcClient::with_transport(HyperTransport::with_client(client));
+    /// ```
+    pub fn with_client(client: hyper::Client<C, hyper::Body>) -> Self {
+        Self { client }
+    }
+
+    /// Get a shared reference to the underlying `hyper::Client`.
+    pub fn client(&self) -> &hyper::Client<C, hyper::Body> {
+        &self.client
+    }
+}
+
+impl Default for HyperTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> From<hyper::Client<C, hyper::Body>> for HyperTransport<C> {
+    fn from(client: hyper::Client<C, hyper::Body>) -> Self {
+        Self::with_client(client)
+    }
+}
+
+impl<C> Transport for HyperTransport<C>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    fn send(
+        &self,
+        request: TransportRequest,
+    ) -> BoxFuture<'_, Result<TransportResponse, TransportError>> {
+        let timeout = request.timeout;
+        Box::pin(super::with_timeout(timeout, async move {
+            let mut http_request = http::Request::post(request.server_addr)
+                .body(hyper::Body::from(request.body))
+                .map_err(|err| TransportError::SendError(err.into()))?;
+            *http_request.headers_mut() = request.headers;
+
+            let response = self
+                .client
+                .request(http_request)
+                .await
+                .map_err(|err| TransportError::SendError(err.into()))?;
+
+            let (parts, body) = response.into_parts();
+
+            let body = hyper::body::to_bytes(body)
+                .await
+                .map_err(|err| TransportError::RecvError(err.into()))?;
+
+            Ok(TransportResponse::new(parts.status, body.to_vec()).with_headers(parts.headers))
+        }))
+    }
+
+    fn warm_up<'a>(&'a self, server_addr: &'a str) -> BoxFuture<'a, Result<(), TransportError>> {
+        Box::pin(async move {
+            let http_request = http::Request::head(server_addr)
+                .body(hyper::Body::empty())
+                .map_err(|err| TransportError::SendError(err.into()))?;
+
+            // whatever the response, the connection is established
+            let response = self
+                .client
+                .request(http_request)
+                .await
+                .map_err(|err| TransportError::SendError(err.into()))?;
+            hyper::body::to_bytes(response.into_body())
+                .await
+                .map_err(|err| TransportError::RecvError(err.into()))?;
+            Ok(())
+        })
+    }
+}
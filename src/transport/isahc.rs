@@ -0,0 +1,137 @@
+use isahc::config::Configurable;
+use isahc::{AsyncReadResponseExt, HttpClient, ResponseExt};
+
+use super::{BoxFuture, Transport, TransportError, TransportRequest, TransportResponse};
+
+/// A [`Transport`] backed by an [`isahc::HttpClient`].
+///
+/// isahc drives its connections on a background thread of its own, so unlike the
+/// `reqwest` and `hyper` transports, it doesn't require a tokio runtime. This makes it
+/// suitable for applications built on `async-std`, `smol`, or any other executor.
+///
+/// Available with the `isahc-transport` feature flag.
+#[derive(Clone, Debug)]
+pub struct IsahcTransport {
+    client: HttpClient,
+}
+
+impl IsahcTransport {
+    /// Create a new transport with a default `isahc::HttpClient`.
+    pub fn new() -> Self {
+        Self::with_client(
+            HttpClient::new().expect("fatal: failed to initialize the default isahc client"),
+        )
+    }
+
+    /// Create a new transport using a custom `isahc::HttpClient`.
+    pub fn with_client(client: HttpClient) -> Self {
+        Self { client }
+    }
+
+    /// Get a shared reference to the underlying `isahc::HttpClient`.
+    pub fn client(&self) -> &HttpClient {
+        &self.client
+    }
+}
+
+impl Default for IsahcTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<HttpClient> for IsahcTransport {
+    fn from(client: HttpClient) -> Self {
+        Self::with_client(client)
+    }
+}
+
+impl Transport for IsahcTransport {
+    fn send(
+        &self,
+        request: TransportRequest,
+    ) -> BoxFuture<'_, Result<TransportResponse, TransportError>> {
+        Box::pin(async move {
+            let mut builder = http::Request::post(request.server_addr);
+            if let Some(timeout) = request.timeout {
+                builder = builder.timeout(timeout);
+            }
+            let mut http_request = builder
+                .body(request.body)
+                .map_err(|err| TransportError::SendError(err.into()))?;
+            *http_request.headers_mut() = request.headers;
+
+            let mut response =
+                self.client
+                    .send_async(http_request)
+                    .await
+                    .map_err(|err| match err {
+                        err if err.is_timeout() => TransportError::TimedOut,
+                        err => TransportError::SendError(err.into()),
+                    })?;
+
+            let url = response.effective_uri().map(ToString::to_string);
+
+            let body = response.bytes().await.map_err(|err| match err.kind() {
+                std::io::ErrorKind::TimedOut => TransportError::TimedOut,
+                _ => TransportError::RecvError(err.into()),
+            })?;
+
+            let (parts, _) = response.into_parts();
+            let response = TransportResponse::new(parts.status, body).with_headers(parts.headers);
+
+            Ok(match url {
+                Some(url) => response.with_url(url),
+                None => response,
+            })
+        })
+    }
+
+    fn warm_up<'a>(&'a self, server_addr: &'a str) -> BoxFuture<'a, Result<(), TransportError>> {
+        Box::pin(async move {
+            // whatever the response, the connection is established
+            self.client
+                .head_async(server_addr)
+                .await
+                .map_err(|err| match err {
+                    err if err.is_timeout() => TransportError::TimedOut,
+                    err => TransportError::SendError(err.into()),
+                })?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{methods, JsonRpcClient};
+
+    #[test]
+    fn without_tokio() -> Result<(), Box<dyn std::error::Error>> {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let server_addr = format!("http://{}", listener.local_addr()?);
+        let server = std::thread::spawn(move || -> std::io::Result<()> {
+            let (mut stream, _) = listener.accept()?;
+            let mut request = vec![0; 4096];
+            let _ = stream.read(&mut request)?;
+            let body = r#"{"jsonrpc":"2.0","id":"dontcare","result":null}"#;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        });
+
+        let client = JsonRpcClient::with_transport(IsahcTransport::new()).connect(server_addr);
+
+        // no tokio runtime in sight
+        futures_executor::block_on(client.call(methods::health::RpcHealthRequest))?;
+        server.join().unwrap()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use reqwest_middleware::ClientWithMiddleware;
+
+use super::reqwest::{read_response, send_error};
+use super::{BoxFuture, Transport, TransportError, TransportRequest, TransportResponse};
+
+/// A [`Transport`] backed by a [`reqwest_middleware::ClientWithMiddleware`].
+///
+/// This lets applications reuse a shared, middleware-wrapped `reqwest::Client`
+/// instead of maintaining a second connection pool.
+///
+/// Available with the `middleware` feature flag.
+#[derive(Clone, Debug)]
+pub struct ReqwestMiddlewareTransport {
+    client: ClientWithMiddleware,
+}
+
+impl ReqwestMiddlewareTransport {
+    /// Create a new transport using a middleware-wrapped client.
+    pub fn with_client(client: ClientWithMiddleware) -> Self {
+        Self { client }
+    }
+
+    /// Get a shared reference to the underlying client.
+    pub fn client(&self) -> &ClientWithMiddleware {
+        &self.client
+    }
+}
+
+impl From<ClientWithMiddleware> for ReqwestMiddlewareTransport {
+    fn from(client: ClientWithMiddleware) -> Self {
+        Self::with_client(client)
+    }
+}
+
+impl Transport for ReqwestMiddlewareTransport {
+    fn send(
+        &self,
+        request: TransportRequest,
+    ) -> BoxFuture<'_, Result<TransportResponse, TransportError>> {
+        Box::pin(async move {
+            #[allow(unused_mut)]
+            let mut builder = self
+                .client
+                .post(&request.server_addr)
+                .headers(request.headers)
+                .body(request.body);
+
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(timeout) = request.timeout {
+                builder = builder.timeout(timeout);
+            }
+
+            let response = builder.send().await.map_err(|err| match err {
+                reqwest_middleware::Error::Reqwest(err) => send_error(err),
+                reqwest_middleware::Error::Middleware(err) => TransportError::SendError(err.into()),
+            })?;
+
+            read_response(response).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use reqwest_middleware::{Middleware, Next};
+    use task_local_extensions::Extensions;
+
+    use crate::{methods, JsonRpcClient};
+
+    /// Records requests, and answers them without hitting the network.
+    #[derive(Clone, Default)]
+    struct Recorder {
+        requests: Arc<Mutex<Vec<reqwest::Request>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Middleware for Recorder {
+        async fn handle(
+            &self,
+            request: reqwest::Request,
+            _extensions: &mut Extensions,
+            _next: Next<'_>,
+        ) -> reqwest_middleware::Result<reqwest::Response> {
+            self.requests.lock().unwrap().push(request);
+            let response = http::Response::builder()
+                .status(200)
+                .body(r#"{"jsonrpc":"2.0","id":"dontcare","result":null}"#)
+                .unwrap();
+            Ok(response.into())
+        }
+    }
+
+    #[tokio::test]
+    async fn middleware_transport() -> Result<(), Box<dyn std::error::Error>> {
+        let recorder = Recorder::default();
+        let requests = recorder.requests.clone();
+
+        let web_client = reqwest_middleware::ClientBuilder::new(reqwest::Client::new())
+            .with(recorder)
+            .build();
+
+        let client = JsonRpcClient::with_middleware(web_client).connect("http://localhost:3030");
+
+        client.call(methods::health::RpcHealthRequest).await?;
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url().as_str(), "http://localhost:3030/");
+        assert_eq!(
+            requests[0].headers()[http::header::CONTENT_TYPE],
+            "application/json"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,415 @@
+//! Pluggable HTTP transports.
+//!
+//! A [`JsonRpcClient`](crate::JsonRpcClient) doesn't talk to the network by itself, it hands the
+//! serialized JSON RPC payload over to a [`Transport`] and parses whatever comes back.
+//!
+//! By default, the client uses `ReqwestTransport` (behind the `reqwest-transport` feature flag).
+//! Its TLS stack is selected with either the `rustls-tls` (the default, pure-Rust) or
+//! the `native-tls` feature flag. Without either, only plain `http://` servers can be reached.
+//! The `gzip` and `brotli` feature flags enable compressed responses, which are decompressed
+//! transparently.
+//! With the `middleware` feature flag, `ReqwestMiddlewareTransport` wraps a
+//! [`reqwest_middleware`](https://docs.rs/reqwest-middleware) client instead.
+//! Alternatively, the `hyper-transport` feature flag provides `HyperTransport`, which is
+//! implemented on raw `hyper`, for when you'd rather not pull in the `reqwest` dependency tree.
+//!
+//! The `reqwest` and `hyper` transports need to be driven from within a tokio runtime.
+//! For other executors, like `async-std` or `smol`, the `isahc-transport` feature flag
+//! provides `IsahcTransport`, which is backed by libcurl and is executor agnostic.
+//!
+//! For nodes running on the same host, the `unix-socket` feature flag provides
+//! `UnixSocketTransport`, which speaks HTTP over unix domain sockets.
+//!
+//! If none of these suffice, you can supply your own HTTP stack by implementing the [`Transport`] trait.
+//!
+//! ## WebAssembly
+//!
+//! When targeting `wasm32-unknown-unknown`, `ReqwestTransport` is backed by the browser's `fetch`
+//! API (via `wasm-bindgen`). In this configuration, the futures returned by transports aren't
+//! required to be `Send`, and the `hyper-transport` feature flag is unavailable.
+//!
+//! ## Example
+//!
+//! ```
+//! use near_jsonrpc_client::transport::{
+//!     BoxFuture, StatusCode, Transport, TransportError, TransportRequest, TransportResponse,
+//! };
+//! use near_jsonrpc_client::JsonRpcClient;
+//!
+//! struct EchoTransport;
+//!
+//! impl Transport for EchoTransport {
+//!     fn send(&self, request: TransportRequest) -> BoxFuture<'_, Result<TransportResponse, TransportError>> {
+//!         Box::pin(async move {
+//!             // a real implementation would POST `request.body` to `request.server_addr`
+//!             Ok(TransportResponse::new(StatusCode::OK, request.body))
+//!         })
+//!     }
+//! }
+//!
+//! let client = JsonRpcClient::with_transport(EchoTransport).connect("https://rpc.testnet.near.org");
+//! ```
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+use thiserror::Error;
+
+pub use http::{HeaderMap, StatusCode};
+
+#[cfg(feature = "reqwest-transport")]
+pub(crate) mod reqwest;
+#[cfg(feature = "reqwest-transport")]
+pub use self::reqwest::ReqwestTransport;
+
+#[cfg(feature = "middleware")]
+mod middleware;
+#[cfg(feature = "middleware")]
+pub use self::middleware::ReqwestMiddlewareTransport;
+
+#[cfg(all(feature = "hyper-transport", target_arch = "wasm32"))]
+compile_error!("the `hyper-transport` feature flag is not supported on `wasm32` targets");
+
+#[cfg(feature = "hyper-transport")]
+mod hyper;
+#[cfg(feature = "hyper-transport")]
+pub use self::hyper::HyperTransport;
+
+#[cfg(all(feature = "isahc-transport", target_arch = "wasm32"))]
+compile_error!("the `isahc-transport` feature flag is not supported on `wasm32` targets");
+
+#[cfg(feature = "isahc-transport")]
+mod isahc;
+#[cfg(feature = "isahc-transport")]
+pub use self::isahc::IsahcTransport;
+
+#[cfg(all(feature = "unix-socket", unix))]
+mod unix;
+#[cfg(all(feature = "unix-socket", unix))]
+pub use self::unix::UnixSocketTransport;
+
+/// A type-erased error, as returned by a [`Transport`].
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// An owned, dynamically typed future returned by a [`Transport`].
+#[cfg(not(target_arch = "wasm32"))]
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// An owned, dynamically typed future returned by a [`Transport`].
+#[cfg(target_arch = "wasm32")]
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+/// An error encountered by a [`Transport`] while exchanging a payload with the server.
+#[derive(Debug, Error)]
+pub enum TransportError {
+    /// The payload couldn't be delivered to the server.
+    #[error(transparent)]
+    SendError(BoxError),
+    /// The server's response couldn't be read.
+    #[error(transparent)]
+    RecvError(BoxError),
+    /// The exchange didn't complete within the request's timeout.
+    #[error("the request timed out")]
+    TimedOut,
+    /// The server didn't present any of the pinned certificates.
+    #[error("the server's certificate doesn't match any of the pinned certificates")]
+    CertificatePinMismatch,
+}
+
+/// Bound a transport exchange by the request's timeout, using tokio's timer.
+#[cfg(any(feature = "hyper-transport", all(feature = "unix-socket", unix)))]
+pub(crate) async fn with_timeout<F>(
+    timeout: Option<Duration>,
+    exchange: F,
+) -> Result<TransportResponse, TransportError>
+where
+    F: Future<Output = Result<TransportResponse, TransportError>>,
+{
+    match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, exchange)
+            .await
+            .unwrap_or(Err(TransportError::TimedOut)),
+        None => exchange.await,
+    }
+}
+
+/// A serialized JSON RPC request, ready to be sent to the server.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct TransportRequest {
+    /// The address of the server this request is intended for.
+    pub server_addr: String,
+    /// Headers to be sent along with the request.
+    pub headers: HeaderMap,
+    /// The serialized JSON RPC payload.
+    pub body: Vec<u8>,
+    /// How long the exchange is allowed to take, if bounded.
+    ///
+    /// Transports should give up with [`TransportError::TimedOut`] once it elapses.
+    pub timeout: Option<Duration>,
+}
+
+/// The raw response returned by the server.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct TransportResponse {
+    /// The HTTP status code of the response.
+    pub status: StatusCode,
+    /// The headers the server responded with.
+    pub headers: HeaderMap,
+    /// The raw response body.
+    pub body: Vec<u8>,
+    /// The URL that served the response, if the transport followed any redirects.
+    pub url: Option<String>,
+}
+
+impl TransportResponse {
+    /// Creates a new response from a status code and a body.
+    pub fn new(status: StatusCode, body: Vec<u8>) -> Self {
+        Self {
+            status,
+            headers: HeaderMap::new(),
+            body,
+            url: None,
+        }
+    }
+
+    /// Attach the headers the server responded with.
+    pub fn with_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Attach the URL that served the response, after following redirects.
+    pub fn with_url(mut self, url: String) -> Self {
+        self.url = Some(url);
+        self
+    }
+}
+
+/// Trait for types that can deliver serialized JSON RPC payloads to a server.
+pub trait Transport: Send + Sync + 'static {
+    /// Send a request to the server, returning its raw response.
+    fn send(
+        &self,
+        request: TransportRequest,
+    ) -> BoxFuture<'_, Result<TransportResponse, TransportError>>;
+
+    /// Drop any pooled connections to `server_addr`, so that new ones are established for
+    /// subsequent requests to it, resolving its address anew. Connections to other servers
+    /// are kept.
+    ///
+    /// Called by [`JsonRpcClient::refresh_connections`](crate::JsonRpcClient::refresh_connections),
+    /// and after repeated connection failures. Does nothing by default.
+    fn refresh(&self, _server_addr: &str) {}
+
+    /// Establish a connection to the server, kept for subsequent requests.
+    ///
+    /// Called by [`JsonRpcClient::warm_up`](crate::JsonRpcClient::warm_up). Does nothing by default.
+    fn warm_up<'a>(&'a self, _server_addr: &'a str) -> BoxFuture<'a, Result<(), TransportError>> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// Transports and responses for the crate's tests.
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+
+    /// A transport answering each request with a closure, handed the request and its payload.
+    pub(crate) struct FnTransport<F>(F);
+
+    impl<F> FnTransport<F>
+    where
+        F: Fn(&TransportRequest, serde_json::Value) -> Result<TransportResponse, TransportError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        pub(crate) fn new(respond: F) -> Self {
+            Self(respond)
+        }
+    }
+
+    impl<F> Transport for FnTransport<F>
+    where
+        F: Fn(&TransportRequest, serde_json::Value) -> Result<TransportResponse, TransportError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        fn send(
+            &self,
+            request: TransportRequest,
+        ) -> BoxFuture<'_, Result<TransportResponse, TransportError>> {
+            let payload = serde_json::from_slice(&request.body).unwrap_or_default();
+            let response = (self.0)(&request, payload);
+            Box::pin(async move { response })
+        }
+    }
+
+    /// A JSON RPC response to the request with the specified id, succeeding with `result`.
+    pub(crate) fn json_rpc_result(
+        id: &serde_json::Value,
+        result: serde_json::Value,
+    ) -> TransportResponse {
+        json_rpc_response(serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+    }
+
+    /// A JSON RPC response to the request with the specified id, failing with `error`.
+    pub(crate) fn json_rpc_error(
+        id: &serde_json::Value,
+        error: serde_json::Value,
+    ) -> TransportResponse {
+        json_rpc_response(serde_json::json!({ "jsonrpc": "2.0", "id": id, "error": error }))
+    }
+
+    /// A JSON RPC response to the request with the specified id, failing with a handler error.
+    pub(crate) fn json_rpc_handler_error(
+        id: &serde_json::Value,
+        cause: serde_json::Value,
+        data: serde_json::Value,
+    ) -> TransportResponse {
+        json_rpc_error(
+            id,
+            serde_json::json!({
+                "name": "HANDLER_ERROR",
+                "cause": cause,
+                "code": -32000,
+                "message": "Server error",
+                "data": data,
+            }),
+        )
+    }
+
+    fn json_rpc_response(response: serde_json::Value) -> TransportResponse {
+        TransportResponse::new(StatusCode::OK, serde_json::to_vec(&response).unwrap())
+    }
+
+    /// The signed transaction sent with `broadcast_tx_commit` or `broadcast_tx_async`.
+    pub(crate) fn broadcast_transaction(
+        payload: &serde_json::Value,
+    ) -> near_primitives::transaction::SignedTransaction {
+        let bytes = near_primitives::serialize::from_base64(payload["params"][0].as_str().unwrap())
+            .unwrap();
+        borsh::BorshDeserialize::try_from_slice(&bytes).unwrap()
+    }
+
+    /// The outcome of a transaction having executed, with the specified status.
+    pub(crate) fn transaction_outcome(
+        signed_transaction: &near_primitives::transaction::SignedTransaction,
+        status: near_primitives::views::FinalExecutionStatus,
+    ) -> near_primitives::views::FinalExecutionOutcomeView {
+        use near_primitives::views::{
+            ExecutionOutcomeView, ExecutionOutcomeWithIdView, ExecutionStatusView,
+            FinalExecutionOutcomeView,
+        };
+
+        FinalExecutionOutcomeView {
+            status,
+            transaction: signed_transaction.clone().into(),
+            transaction_outcome: ExecutionOutcomeWithIdView {
+                proof: vec![],
+                block_hash: Default::default(),
+                id: signed_transaction.get_hash(),
+                outcome: ExecutionOutcomeView {
+                    logs: vec![],
+                    receipt_ids: vec![],
+                    gas_burnt: 0,
+                    tokens_burnt: 0,
+                    executor_id: signed_transaction.transaction.signer_id.clone(),
+                    status: ExecutionStatusView::SuccessValue(String::new()),
+                    metadata: Default::default(),
+                },
+            },
+            receipts_outcome: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::{methods, JsonRpcClient};
+
+    #[derive(Clone, Default)]
+    struct MockTransport {
+        requests: Arc<Mutex<Vec<TransportRequest>>>,
+    }
+
+    impl Transport for MockTransport {
+        fn send(
+            &self,
+            request: TransportRequest,
+        ) -> BoxFuture<'_, Result<TransportResponse, TransportError>> {
+            Box::pin(async move {
+                let payload: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+                self.requests.lock().unwrap().push(request);
+                let body = serde_json::to_vec(&serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": payload["id"],
+                    "result": null,
+                }))
+                .unwrap();
+                Ok(TransportResponse::new(StatusCode::OK, body))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn custom_transport() {
+        let transport = MockTransport::default();
+        let requests = transport.requests.clone();
+
+        let client = JsonRpcClient::with_transport(transport).connect("http://localhost:3030");
+
+        let response = client.call(methods::health::RpcHealthRequest).await;
+
+        assert!(
+            matches!(response, Ok(methods::health::RpcHealthResponse)),
+            "expected an Ok(RpcHealthResponse), found [{:?}]",
+            response
+        );
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].server_addr, "http://localhost:3030");
+        assert_eq!(
+            requests[0].headers.get(http::header::CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+
+        let payload: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(payload["method"], "health");
+        assert_eq!(requests[0].timeout, None);
+    }
+
+    #[tokio::test]
+    async fn call_options() -> Result<(), Box<dyn std::error::Error>> {
+        let transport = MockTransport::default();
+        let requests = transport.requests.clone();
+
+        let client = JsonRpcClient::with_transport(transport)
+            .connect("http://localhost:3030")
+            .header(("x-tenant-id", "default"))?
+            .header(("x-client", "indexer"))?;
+
+        let options = crate::CallOptions::new()
+            .timeout(std::time::Duration::from_secs(5))
+            .header("x-tenant-id", http::HeaderValue::from_static("acme"));
+
+        client
+            .call_with_options(methods::health::RpcHealthRequest, options)
+            .await?;
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0].timeout, Some(std::time::Duration::from_secs(5)));
+        assert_eq!(requests[0].headers.get_all("x-tenant-id").iter().count(), 1);
+        assert_eq!(requests[0].headers.get("x-tenant-id").unwrap(), "acme");
+        assert_eq!(requests[0].headers.get("x-client").unwrap(), "indexer");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use super::{BoxFuture, Transport, TransportError, TransportRequest, TransportResponse};
+
+/// A [`Transport`] backed by a [`reqwest::Client`].
+///
+/// Transports created with [`new`](Self::new), [`with_factory`](Self::with_factory), or by a
+/// [`JsonRpcClientConnectorBuilder`](crate::connector::JsonRpcClientConnectorBuilder), rebuild
+/// a client for the server [refreshed](Transport::refresh), dropping the pooled connections
+/// to it, while other servers keep using the connections they have. Transports created with
+/// [`with_client`](Self::with_client) can't be refreshed.
+#[derive(Clone)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+    /// The clients built for the servers refreshed, by server address, used instead of `client`.
+    refreshed: Arc<RwLock<HashMap<String, reqwest::Client>>>,
+    factory: Option<Arc<ClientFactory>>,
+}
+
+type ClientFactory = dyn Fn() -> reqwest::Result<reqwest::Client> + Send + Sync;
+
+impl ReqwestTransport {
+    /// Create a new transport with a default `reqwest::Client`.
+    pub fn new() -> Self {
+        Self::with_factory(|| client_builder().build())
+            .expect("fatal: failed to initialize the default reqwest client")
+    }
+
+    /// Create a new transport using a custom `reqwest::Client`.
+    pub fn with_client(client: reqwest::Client) -> Self {
+        Self {
+            client,
+            refreshed: Default::default(),
+            factory: None,
+        }
+    }
+
+    /// Create a new transport with a `reqwest::Client` built by `factory`, which is
+    /// called again to build a client for each server the transport is refreshed for.
+    pub fn with_factory<F>(factory: F) -> reqwest::Result<Self>
+    where
+        F: Fn() -> reqwest::Result<reqwest::Client> + Send + Sync + 'static,
+    {
+        Ok(Self {
+            client: factory()?,
+            refreshed: Default::default(),
+            factory: Some(Arc::new(factory)),
+        })
+    }
+
+    /// Get the underlying `reqwest::Client`.
+    pub fn client(&self) -> reqwest::Client {
+        self.client.clone()
+    }
+
+    /// Get the `reqwest::Client` sending requests to `server_addr`.
+    fn client_for(&self, server_addr: &str) -> reqwest::Client {
+        match self.refreshed.read().unwrap().get(server_addr) {
+            Some(client) => client.clone(),
+            None => self.client.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for ReqwestTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReqwestTransport")
+            .field("client", &self.client)
+            .field("refreshable", &self.factory.is_some())
+            .finish()
+    }
+}
+
+/// Create a `reqwest::ClientBuilder` configured with the TLS backend selected by the feature flags.
+///
+/// `rustls-tls` takes precedence over `native-tls` when both are enabled.
+pub(crate) fn client_builder() -> reqwest::ClientBuilder {
+    let builder = reqwest::Client::builder();
+
+    #[cfg(all(feature = "rustls-tls", not(target_arch = "wasm32")))]
+    let builder = builder.use_rustls_tls();
+    #[cfg(all(
+        feature = "native-tls",
+        not(feature = "rustls-tls"),
+        not(target_arch = "wasm32")
+    ))]
+    let builder = builder.use_native_tls();
+
+    builder
+}
+
+impl Default for ReqwestTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<reqwest::Client> for ReqwestTransport {
+    fn from(client: reqwest::Client) -> Self {
+        Self::with_client(client)
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(
+        &self,
+        request: TransportRequest,
+    ) -> BoxFuture<'_, Result<TransportResponse, TransportError>> {
+        Box::pin(async move {
+            #[allow(unused_mut)]
+            let mut builder = self
+                .client_for(&request.server_addr)
+                .post(&request.server_addr)
+                .headers(request.headers)
+                .body(request.body);
+
+            // the fetch API backing reqwest on wasm32 has no notion of timeouts
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(timeout) = request.timeout {
+                builder = builder.timeout(timeout);
+            }
+
+            let response = builder.send().await.map_err(send_error)?;
+
+            read_response(response).await
+        })
+    }
+
+    fn warm_up<'a>(&'a self, server_addr: &'a str) -> BoxFuture<'a, Result<(), TransportError>> {
+        Box::pin(async move {
+            // whatever the response, the connection is established
+            self.client_for(server_addr)
+                .head(server_addr)
+                .send()
+                .await
+                .map_err(send_error)?;
+            Ok(())
+        })
+    }
+
+    fn refresh(&self, server_addr: &str) {
+        if let Some(ref factory) = self.factory {
+            // the client was already built once, so this shouldn't fail,
+            // but if it does, keep the current one
+            if let Ok(client) = factory() {
+                self.refreshed
+                    .write()
+                    .unwrap()
+                    .insert(server_addr.to_string(), client);
+            }
+        }
+    }
+}
+
+/// Classify an error encountered while sending a request.
+pub(super) fn send_error(err: reqwest::Error) -> TransportError {
+    match err {
+        err if is_timeout(&err) => TransportError::TimedOut,
+        #[cfg(all(feature = "tls-pinning", not(target_arch = "wasm32")))]
+        err if crate::connector::is_pin_mismatch(&err) => TransportError::CertificatePinMismatch,
+        err => TransportError::SendError(err.into()),
+    }
+}
+
+/// Read the server's response in full.
+pub(super) async fn read_response(
+    response: reqwest::Response,
+) -> Result<TransportResponse, TransportError> {
+    let status = response.status();
+    let headers = response.headers().clone();
+    let url = response.url().to_string();
+
+    let body = response.bytes().await.map_err(|err| match err {
+        err if is_timeout(&err) => TransportError::TimedOut,
+        err => TransportError::RecvError(err.into()),
+    })?;
+
+    Ok(TransportResponse::new(status, body.to_vec())
+        .with_headers(headers)
+        .with_url(url))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn is_timeout(err: &reqwest::Error) -> bool {
+    err.is_timeout()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn is_timeout(_err: &reqwest::Error) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[test]
+    fn refresh() {
+        let builds = Arc::new(AtomicUsize::new(0));
+        let transport = ReqwestTransport::with_factory({
+            let builds = builds.clone();
+            move || {
+                builds.fetch_add(1, Ordering::SeqCst);
+                client_builder().build()
+            }
+        })
+        .unwrap();
+
+        transport.refresh("http://a");
+        transport.refresh("http://a");
+        assert_eq!(builds.load(Ordering::SeqCst), 3);
+        // only the server refreshed gets a client of its own
+        let refreshed = transport.refreshed.read().unwrap();
+        assert_eq!(refreshed.keys().collect::<Vec<_>>(), ["http://a"]);
+
+        let transport = ReqwestTransport::with_client(reqwest::Client::new());
+        transport.refresh("http://a");
+        assert!(transport.refreshed.read().unwrap().is_empty());
+    }
+}
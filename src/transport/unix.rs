@@ -0,0 +1,70 @@
+use super::{BoxFuture, Transport, TransportError, TransportRequest, TransportResponse};
+
+/// A [`Transport`] that speaks HTTP over unix domain sockets.
+///
+/// Server addresses take the form `unix:/path/to/socket`.
+///
+/// Available with the `unix-socket` feature flag, on unix platforms.
+#[derive(Clone, Debug, Default)]
+pub struct UnixSocketTransport {
+    _priv: (),
+}
+
+impl UnixSocketTransport {
+    /// Create a new unix domain socket transport.
+    pub fn new() -> Self {
+        Self { _priv: () }
+    }
+}
+
+impl Transport for UnixSocketTransport {
+    fn send(
+        &self,
+        request: TransportRequest,
+    ) -> BoxFuture<'_, Result<TransportResponse, TransportError>> {
+        let timeout = request.timeout;
+        Box::pin(super::with_timeout(timeout, async move {
+            let socket_path =
+                crate::connector::unix_socket_path(&request.server_addr).ok_or_else(|| {
+                    TransportError::SendError(
+                        format!("not a unix socket address: {}", request.server_addr).into(),
+                    )
+                })?;
+
+            let stream = tokio::net::UnixStream::connect(socket_path)
+                .await
+                .map_err(|err| TransportError::SendError(err.into()))?;
+
+            let (mut sender, connection) = hyper::client::conn::handshake(stream)
+                .await
+                .map_err(|err| TransportError::SendError(err.into()))?;
+
+            tokio::spawn(async move {
+                // errors are surfaced through the request future
+                let _ = connection.await;
+            });
+
+            let mut http_request = http::Request::post("http://localhost/")
+                .body(hyper::Body::from(request.body))
+                .map_err(|err| TransportError::SendError(err.into()))?;
+            *http_request.headers_mut() = request.headers;
+            http_request
+                .headers_mut()
+                .entry(http::header::HOST)
+                .or_insert_with(|| http::HeaderValue::from_static("localhost"));
+
+            let response = sender
+                .send_request(http_request)
+                .await
+                .map_err(|err| TransportError::SendError(err.into()))?;
+
+            let (parts, body) = response.into_parts();
+
+            let body = hyper::body::to_bytes(body)
+                .await
+                .map_err(|err| TransportError::RecvError(err.into()))?;
+
+            Ok(TransportResponse::new(parts.status, body.to_vec()).with_headers(parts.headers))
+        }))
+    }
+}